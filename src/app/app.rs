@@ -2,54 +2,220 @@ use std::sync::Arc;
 
 use winit::{
     application::ApplicationHandler,
-    event::WindowEvent,
+    event::{ElementState, KeyEvent, WindowEvent},
     event_loop::{ActiveEventLoop},
+    keyboard::PhysicalKey,
     window::{Window, WindowId},
 };
 
-use crate::app::{graphics::gpu_resources::GPU_Resources, states::planets::StatePlanets as State};
+use winit::keyboard::KeyCode;
+
+use crate::app::graphics::gpu_resources::GPU_Resources;
+use crate::app::graphics::screen::Screen;
+use crate::app::states::{galaxy::StateGalaxy, planets::StatePlanets, replay::ReplayMode};
+use crate::error::{Error, Result};
+
+// Цвет окна, если сцена не загрузилась
+const FAILED_BG_COLOR: wgpu::Color = wgpu::Color { r: 0.35, g: 0.02, b: 0.02, a: 1.0 };
+
+// Сцена выбирается первым аргументом командной строки: `planets` (по умолчанию) или `galaxy`.
+// Сцену планет можно записать или воспроизвести, см. ReplayMode
+enum State {
+    Planets(Box<StatePlanets>),
+    Galaxy(Box<StateGalaxy>),
+    // сцена не загрузилась: окно остаётся открытым, ошибка видна в заголовке и в stderr
+    Failed(Box<Screen>),
+}
+
+// Имя сцены - первый аргумент, если это не флаг: `--seed 3` и `planets --seed 3` равнозначны
+fn split_scene(args: &[String]) -> (Option<&str>, &[String]) {
+    match args.split_first() {
+        Some((scene, flags)) if !scene.starts_with("--") => (Some(scene.as_str()), flags),
+        _ => (None, args),
+    }
+}
+
+impl State {
+    fn new(window: Arc<Window>, resources: Arc<GPU_Resources>) -> State {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let (scene, flags) = split_scene(&args);
+        let loaded = match scene {
+            Some("galaxy") => StateGalaxy::new(window.clone(), resources.clone()).map(|state| State::Galaxy(Box::new(state))),
+            _ => {
+                ReplayMode::from_args(flags)
+                    .and_then(|mode| StatePlanets::new(window.clone(), resources.clone(), mode))
+                    .map(|state| State::Planets(Box::new(state)))
+            }
+        };
+        loaded.unwrap_or_else(|error| State::failed(window, resources, &error))
+    }
+
+    fn failed(window: Arc<Window>, resources: Arc<GPU_Resources>, error: &Error) -> State {
+        log::error!("Failed to load the scene: {}", error);
+        window.set_title(&format!("Error: {}", error));
+        let mut screen = Screen::new(window, resources);
+        screen.set_bg_color(FAILED_BG_COLOR);
+        State::Failed(Box::new(screen))
+    }
+
+    fn screen(&self) -> &Screen {
+        match self {
+            State::Planets(state) => &state.screen,
+            State::Galaxy(state) => &state.screen,
+            State::Failed(screen) => screen,
+        }
+    }
+
+    fn window(&self) -> &Window {
+        self.screen().get_window()
+    }
+
+    fn fail(&mut self, error: &Error) {
+        let screen = self.screen();
+        let (window, resources) = (screen.window.clone(), screen.resources.clone());
+        *self = State::failed(window, resources, error);
+    }
+
+    fn update(&mut self) {
+        match self {
+            State::Planets(state) => state.update(),
+            State::Galaxy(state) => state.update(),
+            State::Failed(_) => (),
+        }
+    }
+
+    // Сцена, не отрисовавшая кадр, сменяется экраном ошибки. Ошибку возвращает
+    // только сам экран ошибки: показать её больше негде
+    fn render(&mut self) -> Result<()> {
+        let result = match self {
+            State::Planets(state) => state.render(),
+            State::Galaxy(state) => state.render(),
+            State::Failed(screen) => return screen.render(|_, _| (), None),
+        };
+        if let Err(error) = result {
+            self.fail(&error);
+        }
+        Ok(())
+    }
+
+    // сцена, не пересоздавшая буферы под новый размер, сменяется экраном ошибки
+    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        match self {
+            State::Planets(state) => {
+                if let Err(error) = state.resize(new_size) {
+                    self.fail(&error);
+                }
+            }
+            State::Galaxy(state) => state.resize(new_size),
+            State::Failed(screen) => screen.resize(new_size),
+        }
+    }
+
+    fn close(&mut self) {
+        if let State::Planets(state) = self {
+            state.close();
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyCode) {
+        match self {
+            State::Planets(state) => state.handle_key(key),
+            State::Galaxy(state) => state.handle_key(key),
+            State::Failed(_) => (),
+        }
+    }
+}
 
 #[derive(Default)]
 pub struct App {
     resources: Arc<GPU_Resources>,
     state: Option<State>,
+    // ошибка, остановившая цикл событий; main получает её после выхода из цикла
+    error: Option<Error>,
+}
+
+impl App {
+    pub fn take_error(&mut self) -> Option<Error> {
+        self.error.take()
+    }
+
+    fn stop(&mut self, event_loop: &ActiveEventLoop, error: Error) {
+        self.error = Some(error);
+        event_loop.exit();
+    }
 }
 
 impl ApplicationHandler for App {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         // Create window object
-        let window = Arc::new(
-            event_loop
-                .create_window(Window::default_attributes())
-                .unwrap(),
-        );
+        let window = match event_loop.create_window(Window::default_attributes()) {
+            Ok(window) => Arc::new(window),
+            Err(source) => return self.stop(event_loop, Error::Window(source)),
+        };
 
         let mut state = State::new(window.clone(), self.resources.clone());
-        state.render();
+        if let Err(error) = state.render() {
+            return self.stop(event_loop, error);
+        }
         self.state = Some(state);
 
         window.request_redraw();
     }
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
-        let state = self.state.as_mut().unwrap();
+        let Some(state) = self.state.as_mut() else {
+            return;
+        };
         match event {
             WindowEvent::CloseRequested => {
                 println!("The close button was pressed; stopping");
+                state.close();
                 event_loop.exit();
             }
             WindowEvent::RedrawRequested => {
                 state.update();
-                state.render();
+                if let Err(error) = state.render() {
+                    return self.stop(event_loop, error);
+                }
                 // Emits a new redraw requested event.
-                state.screen.get_window().request_redraw();
+                state.window().request_redraw();
             }
             WindowEvent::Resized(size) => {
                 // Reconfigures the size of the surface. We do not re-render
                 // here as this event is always followed up by redraw request.
                 state.resize(size);
             }
+            WindowEvent::KeyboardInput {
+                event: KeyEvent { physical_key: PhysicalKey::Code(code), state: ElementState::Pressed, .. },
+                ..
+            } => {
+                state.handle_key(code);
+            }
             _ => (),
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn the_scene_name_is_optional() {
+        let (scene, flags) = (None, args("--record run.json --seed 4"));
+        assert_eq!(split_scene(&flags), (scene, flags.as_slice()));
+
+        let line = args("planets --record run.json --seed 4");
+        assert_eq!(split_scene(&line), (Some("planets"), &line[1..]));
+        assert_eq!(ReplayMode::from_args(split_scene(&line).1).unwrap(), ReplayMode::from_args(&flags).unwrap());
+
+        let line = args("--replay run.json");
+        assert_eq!(ReplayMode::from_args(split_scene(&line).1).unwrap(), ReplayMode::Replay("run.json".into()));
+        assert_eq!(split_scene(&args("galaxy")).0, Some("galaxy"));
+        assert_eq!(split_scene(&[]), (None, &[][..]));
+    }
+}
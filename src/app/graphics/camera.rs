@@ -0,0 +1,79 @@
+use std::f64::consts::PI;
+
+use crate::physics::geometry::Point3;
+
+// Камера хранит положение в f64 и рисует сцену относительно себя (floating origin):
+// мировые координаты вычитаются из положения камеры до перевода в f32,
+// поэтому точность не зависит от удалённости сцены от начала координат
+pub struct Camera {
+    pub eye: Point3,
+    pub target: Point3,
+    pub up: Point3,
+    pub fovy: f32,
+    pub znear: f32,
+}
+
+impl Camera {
+    pub fn new(eye: Point3, target: Point3) -> Self {
+        Self {
+            eye,
+            target,
+            up: Point3::new(0.0, 0.0, 1.0),
+            fovy: PI as f32 / 4.0,
+            znear: 1e-7,
+        }
+    }
+
+    pub fn to_relative(&self, p: &Point3) -> [f32; 3] {
+        let d = p - self.eye;
+        [d.x as f32, d.y as f32, d.z as f32]
+    }
+
+    // Матрица вида и проекции для камеры, помещённой в начало координат.
+    // Бесконечная проекция с обратной глубиной (reverse-Z): ближе = больше
+    pub fn relative_transform(&self, aspect_ratio: f32) -> glam::Mat4 {
+        let dir = (self.target - self.eye).normalize();
+        let up = self.up.normalize();
+        let projection = glam::Mat4::perspective_infinite_reverse_rh(self.fovy, aspect_ratio, self.znear);
+        let view = glam::Mat4::look_to_rh(
+            glam::Vec3::ZERO,
+            glam::Vec3::new(dir.x as f32, dir.y as f32, dir.z as f32),
+            glam::Vec3::new(up.x as f32, up.y as f32, up.z as f32),
+        );
+        projection * view
+    }
+
+    // factor < 1 приближает камеру к цели, factor > 1 отдаляет
+    pub fn zoom(&mut self, factor: f64) {
+        self.eye = self.target + (self.eye - self.target) * factor;
+    }
+
+    // Цель сдвинулась в target: камера переносится вместе с ней, вид не меняется
+    pub fn follow(&mut self, target: Point3) {
+        self.eye = target + (self.eye - self.target);
+        self.target = target;
+    }
+
+    // Новая цель: камера смотрит на неё с прежнего направления с расстояния distance
+    pub fn retarget(&mut self, target: Point3, distance: f64) {
+        let direction = (self.eye - self.target).try_normalize(0.0).unwrap_or(Point3::new(1.0, 0.0, 0.0));
+        self.target = target;
+        self.eye = target + direction * distance;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follow_keeps_the_view_and_retarget_keeps_the_direction() {
+        let mut camera = Camera::new(Point3::new(10.0, 0.0, 10.0), Point3::zeros());
+        camera.follow(Point3::new(100.0, 50.0, 0.0));
+        assert_eq!(camera.eye, Point3::new(110.0, 50.0, 10.0));
+
+        camera.retarget(Point3::new(-5.0, 0.0, 0.0), 2.0_f64.sqrt());
+        assert!((camera.eye - Point3::new(-4.0, 0.0, 1.0)).magnitude() < 1e-12);
+        assert_eq!(camera.target, Point3::new(-5.0, 0.0, 0.0));
+    }
+}
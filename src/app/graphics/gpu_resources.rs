@@ -44,6 +44,18 @@ impl BufferFabric {
             wgpu::BufferUsages::INDEX
         )
     }
+    // Индексы хранятся в u32, в буфер пишутся в u16, если все вершины адресуются 16 битами
+    pub fn create_mesh_index_buffer_init(&self, indices: &[u32], vertex_count: usize, label: Option<&str>) -> (wgpu::Buffer, wgpu::IndexFormat) {
+        let format = index_format_for(vertex_count);
+        let buffer = match format {
+            wgpu::IndexFormat::Uint16 => {
+                let indices: Vec<u16> = indices.iter().map(|&i| i as u16).collect();
+                self.create_index_buffer_init(&indices, label)
+            }
+            wgpu::IndexFormat::Uint32 => self.create_index_buffer_init(indices, label),
+        };
+        (buffer, format)
+    }
     pub fn create_uniform_buffer_init<T>(&self, data: &[T], label: Option<&str>) -> wgpu::Buffer 
         where T: Pod + Zeroable
     {
@@ -55,6 +67,16 @@ impl BufferFabric {
     }
 }
 
+// Формат индексов для меша из vertex_count вершин: Uint16 вдвое компактнее, но адресует
+// только 65536 вершин
+pub fn index_format_for(vertex_count: usize) -> wgpu::IndexFormat {
+    if vertex_count <= u16::MAX as usize + 1 {
+        wgpu::IndexFormat::Uint16
+    } else {
+        wgpu::IndexFormat::Uint32
+    }
+}
+
 impl GPU_Resources {
     pub async fn new() -> GPU_Resources {
         let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
@@ -78,27 +100,25 @@ impl GPU_Resources {
     }
 
     pub fn create_pipeline_layout(&self) -> wgpu::PipelineLayout {
-        let pipeline_layout = self.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        self.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: None,
             bind_group_layouts: &[],
             push_constant_ranges: &[],
-        });
-
-        pipeline_layout
+        })
     }
 
     pub fn create_render_pipeline(&self, pipeline_layout: &PipelineLayout, shader: &ShaderModule, swapchain_format: wgpu::TextureFormat) -> wgpu::RenderPipeline {
-        let render_pipeline = self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: None,
-            layout: Some(&pipeline_layout),
+            layout: Some(pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &shader,
+                module: shader,
                 entry_point: Some("vs_main"),
                 buffers: &[],
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
-                module: &shader,
+                module: shader,
                 entry_point: Some("fs_main"),
                 compilation_options: Default::default(),
                 targets: &[Some(wgpu::ColorTargetState {
@@ -112,8 +132,7 @@ impl GPU_Resources {
             multisample: wgpu::MultisampleState::default(),
             multiview: None,
             cache: None,
-        });
-        render_pipeline
+        })
     }
 
     pub fn create_command_encoder(&self, desc: &wgpu::CommandEncoderDescriptor<'_>) -> wgpu::CommandEncoder {
@@ -132,4 +151,18 @@ impl Default for GPU_Resources {
     fn default() -> GPU_Resources {
         pollster::block_on(GPU_Resources::new())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 65536 вершин - наибольший индекс 65535 = u16::MAX ещё помещается в Uint16
+    #[test]
+    fn index_format_switches_after_u16_range() {
+        assert_eq!(index_format_for(0), wgpu::IndexFormat::Uint16);
+        assert_eq!(index_format_for(u16::MAX as usize), wgpu::IndexFormat::Uint16);
+        assert_eq!(index_format_for(65536), wgpu::IndexFormat::Uint16);
+        assert_eq!(index_format_for(65537), wgpu::IndexFormat::Uint32);
+        assert_eq!(index_format_for(1 << 20), wgpu::IndexFormat::Uint32);
+    }
+}
@@ -1,8 +1,13 @@
+use std::sync::Arc;
+
 use nalgebra::Rotation3;
-use crate::physics::geometry::{Geometry, Point3, Mesh, MBV};
+use rayon::prelude::*;
+use crate::error::Result;
+use crate::physics::bounds::{Aabb, BoundingSphere, Obb, Ray, RayHit};
+use crate::physics::geometry::{Geometry, Point3, Mesh, MBV, PARALLEL_MIN_LEN};
 
 pub struct GraphicsGeometry {
-    pub geometry: Box<dyn Geometry>,
+    pub geometry: Arc<dyn Geometry>,
     pub rotation: Rotation3<f64>,
     pub scale: f64,
     pub center: Point3,
@@ -14,42 +19,114 @@ impl GraphicsGeometry {
         rotation_euler: (f64, f64, f64), // (roll, pitch, yaw) in radians
         scale: f64,
         center: Point3
+    ) -> Self {
+        Self::shared(Arc::from(geometry), rotation_euler, scale, center)
+    }
+
+    // Несколько тел с одной геометрией: её меш строится и загружается один раз
+    pub fn shared(
+        geometry: Arc<dyn Geometry>,
+        rotation_euler: (f64, f64, f64),
+        scale: f64,
+        center: Point3
     ) -> Self {
         let rotation = Rotation3::from_euler_angles(rotation_euler.0, rotation_euler.1, rotation_euler.2);
         Self { geometry, rotation, scale, center }
     }
 
-    pub fn get_surface(&self) -> Mesh {
-        let base = self.geometry.get_surface_mesh();
+    pub fn transform_point(&self, v: &Point3) -> Point3 {
+        self.rotation * (v * self.scale) + self.center
+    }
+
+    // Модельная матрица относительно origin (положения камеры): перенос считается в f64
+    pub fn model_matrix(&self, origin: &Point3) -> glam::Mat4 {
+        let m = self.rotation.matrix() * self.scale;
+        let t = self.center - origin;
+        glam::Mat4::from_cols_array_2d(&[
+            [m[(0, 0)] as f32, m[(1, 0)] as f32, m[(2, 0)] as f32, 0.0],
+            [m[(0, 1)] as f32, m[(1, 1)] as f32, m[(2, 1)] as f32, 0.0],
+            [m[(0, 2)] as f32, m[(1, 2)] as f32, m[(2, 2)] as f32, 0.0],
+            [t.x as f32, t.y as f32, t.z as f32, 1.0],
+        ])
+    }
 
-        let transformed_vertices: Vec<Point3> = base.vertices.iter().map(|v| {
+    pub fn get_surface(&self) -> Result<Mesh> {
+        let base = self.geometry.get_surface_mesh()?;
+
+        let transformed_vertices: Vec<Point3> = base.vertices.par_iter().with_min_len(PARALLEL_MIN_LEN).map(|v| {
             let scaled = *v * self.scale;
             let rotated = self.rotation * scaled;
             rotated + self.center
         }).collect();
 
-        Mesh {
+        Ok(Mesh {
             vertices: transformed_vertices,
-            indices: base.indices.clone(),
-        }
+            indices: base.indices,
+            normals: base.normals.map(|normals| normals.iter().map(|n| self.rotation * n).collect()),
+            uvs: base.uvs,
+            tangents: base.tangents.map(|tangents| tangents.iter().map(|t| self.rotation * t).collect()),
+            bitangents: base.bitangents.map(|bitangents| bitangents.iter().map(|b| self.rotation * b).collect()),
+        })
     }
 
-    pub fn get_edges(&self, bold: f32) -> Mesh {
-        let base = self.geometry.get_edges_mesh(bold);
+    pub fn get_edges(&self, bold: f32) -> Result<Mesh> {
+        let base = self.geometry.get_edges_mesh(bold)?;
 
-        let transformed_vertices: Vec<Point3> = base.vertices.iter().map(|v| {
+        let transformed_vertices: Vec<Point3> = base.vertices.par_iter().with_min_len(PARALLEL_MIN_LEN).map(|v| {
             let scaled = *v * self.scale;
             let rotated = self.rotation * scaled;
             rotated + self.center
         }).collect();
 
-        Mesh {
+        Ok(Mesh {
             vertices: transformed_vertices,
-            indices: base.indices.clone(),
-        }
+            indices: base.indices,
+            normals: base.normals.map(|normals| normals.iter().map(|n| self.rotation * n).collect()),
+            uvs: base.uvs,
+            tangents: base.tangents.map(|tangents| tangents.iter().map(|t| self.rotation * t).collect()),
+            bitangents: base.bitangents.map(|bitangents| bitangents.iter().map(|b| self.rotation * b).collect()),
+        })
+    }
+
+    pub fn get_wireframe(&self) -> Result<Vec<[Point3; 2]>> {
+        Ok(self.geometry.get_wireframe()?.par_iter().with_min_len(PARALLEL_MIN_LEN).map(|segment| {
+            segment.map(|v| self.transform_point(&v))
+        }).collect())
     }
 
     pub fn minimal_bounding_volume(&self) -> MBV {
         self.geometry.minimal_bounding_volume()
     }
+
+    // Ограничивающие объёмы в мировых координатах
+    pub fn obb(&self) -> Obb {
+        let local = self.geometry.bounding_box();
+        Obb {
+            center: self.transform_point(&local.center()),
+            half_extents: local.half_extents() * self.scale,
+            rotation: self.rotation,
+        }
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        self.obb().aabb()
+    }
+
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        let local = self.geometry.bounding_sphere();
+        BoundingSphere::new(self.transform_point(&local.center), local.radius * self.scale)
+    }
+
+    // Луч в мировых координатах переносится в координаты модели, t при этом не меняется.
+    // Лучи мимо ограничивающей сферы отсекаются без построения меша
+    pub fn ray_cast(&self, ray: &Ray) -> Option<RayHit> {
+        self.bounding_sphere().ray_intersect(ray)?;
+        let inverse = self.rotation.inverse();
+        let local = Ray::new(
+            inverse * (ray.origin - self.center) / self.scale,
+            inverse * ray.direction / self.scale,
+        );
+        let hit = self.geometry.ray_cast(&local)?;
+        Some(RayHit { normal: self.rotation * hit.normal, ..hit })
+    }
 }
\ No newline at end of file
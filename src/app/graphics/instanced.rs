@@ -0,0 +1,285 @@
+use bytemuck::{Pod, Zeroable};
+
+use crate::app::graphics::gpu_resources::GPU_Resources;
+use crate::app::graphics::mesh_cache::GpuMesh;
+use crate::physics::geometry::Mesh;
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+// Экземпляр меша: модельная матрица относительно камеры, слой текстурного массива и оттенок,
+// на который умножается цвет текстуры
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct Instance {
+    pub model: [[f32; 4]; 4],
+    pub tint: [f32; 4],
+    pub layer: u32,
+    _padding: [u32; 3],
+}
+
+impl Instance {
+    pub fn new(model: glam::Mat4, layer: u32, tint: [f32; 4]) -> Self {
+        Self { model: model.to_cols_array_2d(), tint, layer, _padding: [0; 3] }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct InstancedUniforms {
+    transform: [[f32; 4]; 4],
+    light_origin: [f32; 3],
+    ambient_strength: f32,
+}
+
+// Один меш в собственных координатах, нарисованный много раз одним вызовом draw_indexed:
+// планеты, астероиды, частицы. Текстуры всех экземпляров - слои одного массива
+pub struct InstancedMeshes {
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    uniform_buf: wgpu::Buffer,
+    mesh: GpuMesh,
+    instance_buf: wgpu::Buffer,
+    capacity: usize,
+    count: usize,
+}
+
+impl InstancedMeshes {
+    pub fn new(
+        resources: &GPU_Resources,
+        color_format: wgpu::TextureFormat,
+        mesh: &Mesh,
+        textures: &wgpu::TextureView,
+        capacity: usize,
+    ) -> Self {
+        let device = &resources.device;
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Instanced meshes bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(size_of::<InstancedUniforms>() as u64),
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Instanced meshes pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let uniform_buf = resources.buffer_fabric.create_buffer_init(
+            &[InstancedUniforms {
+                transform: glam::Mat4::IDENTITY.to_cols_array_2d(),
+                light_origin: [0.0; 3],
+                ambient_strength: 1.0,
+            }],
+            "Instanced meshes uniform buffer",
+            wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        );
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Instanced meshes sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Instanced meshes bind group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buf.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(textures),
+                },
+            ],
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shaders/instanced.wgsl"));
+        let vertex_buffers = [
+            wgpu::VertexBufferLayout {
+                array_stride: size_of::<MeshVertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Float32x2],
+            },
+            wgpu::VertexBufferLayout {
+                array_stride: size_of::<Instance>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: &wgpu::vertex_attr_array![
+                    3 => Float32x4, 4 => Float32x4, 5 => Float32x4, 6 => Float32x4,
+                    7 => Float32x4, 8 => Uint32,
+                ],
+            },
+        ];
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Instanced meshes pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &vertex_buffers,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(color_format.into())],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Greater,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let (vertices, indices) = mesh_vertices_indices(mesh);
+        let mesh = GpuMesh::new(resources, &vertices, &indices, Some("Instanced mesh"));
+        let instance_buf = Self::create_instance_buffer(resources, capacity);
+        InstancedMeshes { pipeline, bind_group, uniform_buf, mesh, instance_buf, capacity, count: 0 }
+    }
+
+    fn create_instance_buffer(resources: &GPU_Resources, capacity: usize) -> wgpu::Buffer {
+        resources.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Instance buffer"),
+            size: (capacity.max(1) * size_of::<Instance>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    // light_origin - источник света относительно камеры
+    pub fn set_transform(&self, resources: &GPU_Resources, transform: glam::Mat4, light_origin: [f32; 3]) {
+        let uniforms = InstancedUniforms {
+            transform: transform.to_cols_array_2d(),
+            light_origin,
+            ambient_strength: 1.0,
+        };
+        resources.queue.write_buffer(&self.uniform_buf, 0, bytemuck::cast_slice(&[uniforms]));
+    }
+
+    pub fn set_instances(&mut self, resources: &GPU_Resources, instances: &[Instance]) {
+        if instances.len() > self.capacity {
+            self.capacity = instances.len();
+            self.instance_buf = Self::create_instance_buffer(resources, self.capacity);
+        }
+        resources.queue.write_buffer(&self.instance_buf, 0, bytemuck::cast_slice(instances));
+        self.count = instances.len();
+    }
+
+    pub fn draw(&self, rpass: &mut wgpu::RenderPass<'_>) {
+        if self.count == 0 {
+            return;
+        }
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.set_vertex_buffer(0, self.mesh.vertex_buf.slice(..));
+        rpass.set_vertex_buffer(1, self.instance_buf.slice(..));
+        rpass.set_index_buffer(self.mesh.index_buf.slice(..), self.mesh.index_format);
+        rpass.draw_indexed(0..self.mesh.index_count as u32, 0, 0..self.count as u32);
+    }
+}
+
+fn mesh_vertices_indices(mesh: &Mesh) -> (Vec<MeshVertex>, Vec<u32>) {
+    let normals = mesh.normals.clone().unwrap_or_else(|| mesh.smooth_normals());
+    let vertices = mesh.vertices.iter().enumerate().map(|(i, v)| {
+        let uv = mesh.uvs.as_ref().map_or([0.0; 2], |uvs| uvs[i]);
+        MeshVertex {
+            position: [v.x as f32, v.y as f32, v.z as f32],
+            normal: [normals[i].x as f32, normals[i].y as f32, normals[i].z as f32],
+            uv: [uv[0] as f32, uv[1] as f32],
+        }
+    }).collect();
+    let indices = mesh.indices.iter().flatten().map(|&i| i as u32).collect();
+    (vertices, indices)
+}
+
+// Текстурный массив из изображений: все слои приводятся к размеру наибольшего
+pub fn create_texture_array(resources: &GPU_Resources, label: &str, images: &[image::RgbaImage]) -> wgpu::TextureView {
+    let max_size = resources.device.limits().max_texture_dimension_2d;
+    let width = images.iter().map(|img| img.width()).max().unwrap_or(1).clamp(1, max_size);
+    let height = images.iter().map(|img| img.height()).max().unwrap_or(1).clamp(1, max_size);
+    let layers = images.len().max(1) as u32;
+
+    let texture = resources.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: layers },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    for (layer, img) in images.iter().enumerate() {
+        let resized;
+        let img = match img.dimensions() == (width, height) {
+            true => img,
+            false => {
+                resized = image::imageops::resize(img, width, height, image::imageops::FilterType::Triangle);
+                &resized
+            }
+        };
+        resources.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: 0, y: 0, z: layer as u32 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            img,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        );
+    }
+
+    texture.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(wgpu::TextureViewDimension::D2Array),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use bytemuck::Pod;
+
+use crate::app::graphics::gpu_resources::GPU_Resources;
+use crate::physics::geometry::Geometry;
+
+// Меш, загруженный на GPU один раз; положение тела задаётся модельной матрицей
+pub struct GpuMesh {
+    pub vertex_buf: wgpu::Buffer,
+    pub index_buf: wgpu::Buffer,
+    pub index_format: wgpu::IndexFormat,
+    pub index_count: usize,
+}
+
+impl GpuMesh {
+    pub fn new<V: Pod>(resources: &GPU_Resources, vertices: &[V], indices: &[u32], label: Option<&str>) -> Self {
+        let vertex_buf = resources.buffer_fabric.create_vertex_buffer_init(vertices, label);
+        let (index_buf, index_format) = resources.buffer_fabric.create_mesh_index_buffer_init(indices, vertices.len(), label);
+        Self { vertex_buf, index_buf, index_format, index_count: indices.len() }
+    }
+
+    pub fn draw(&self, rpass: &mut wgpu::RenderPass<'_>) {
+        rpass.set_index_buffer(self.index_buf.slice(..), self.index_format);
+        rpass.set_vertex_buffer(0, self.vertex_buf.slice(..));
+        rpass.draw_indexed(0..self.index_count as u32, 0, 0..1);
+    }
+}
+
+// Данные, построенные по геометрии один раз. Тела с общей геометрией (все планеты - одна
+// единичная сфера) делят одну запись. Запись держит свой Arc, поэтому геометрия живёт,
+// пока жива запись, и сравнение по Arc::ptr_eq не спутает её с другой
+pub struct GeometryCache<T> {
+    entries: Vec<(Arc<dyn Geometry>, T)>,
+}
+
+impl<T> GeometryCache<T> {
+    pub fn get_or_insert_with(&mut self, geometry: &Arc<dyn Geometry>, build: impl FnOnce(&dyn Geometry) -> T) -> usize {
+        if let Some(index) = self.index_of(geometry) {
+            return index;
+        }
+        self.entries.push((geometry.clone(), build(geometry.as_ref())));
+        self.entries.len() - 1
+    }
+
+    // То же для построения, которое может не удаться: при ошибке кэш не меняется
+    pub fn try_get_or_insert_with<E>(
+        &mut self,
+        geometry: &Arc<dyn Geometry>,
+        build: impl FnOnce(&dyn Geometry) -> Result<T, E>,
+    ) -> Result<usize, E> {
+        if let Some(index) = self.index_of(geometry) {
+            return Ok(index);
+        }
+        self.entries.push((geometry.clone(), build(geometry.as_ref())?));
+        Ok(self.entries.len() - 1)
+    }
+
+    // Уникальных геометрий в сцене единицы, линейного поиска достаточно
+    pub fn index_of(&self, geometry: &Arc<dyn Geometry>) -> Option<usize> {
+        self.entries.iter().position(|(cached, _)| Arc::ptr_eq(cached, geometry))
+    }
+
+    pub fn get(&self, index: usize) -> &T {
+        &self.entries[index].1
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> &mut T {
+        &mut self.entries[index].1
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.entries.iter().map(|(_, item)| item)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl<T> Default for GeometryCache<T> {
+    fn default() -> Self {
+        Self { entries: Vec::new() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::ball::Ball;
+
+    // Записи различаются по Arc, а не по содержимому; запись держит геометрию живой
+    #[test]
+    fn entries_are_keyed_by_arc() {
+        let sphere: Arc<dyn Geometry> = Arc::new(Ball::uv_sphere(1.0, 8, 4));
+        let same_shape: Arc<dyn Geometry> = Arc::new(Ball::uv_sphere(1.0, 8, 4));
+        let mut cache = GeometryCache::default();
+        assert_eq!(cache.get_or_insert_with(&sphere, |_| "sphere"), 0);
+        assert_eq!(cache.get_or_insert_with(&sphere.clone(), |_| "clone"), 0);
+        assert_eq!(cache.get_or_insert_with(&same_shape, |_| "same shape"), 1);
+        assert_eq!(*cache.get(1), "same shape");
+
+        let weak = Arc::downgrade(&sphere);
+        drop(sphere);
+        assert!(weak.upgrade().is_some());
+        cache.clear();
+        assert!(weak.upgrade().is_none());
+    }
+}
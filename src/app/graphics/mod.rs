@@ -2,4 +2,10 @@ pub mod gpu_resources;
 pub mod surface;
 pub mod screen;
 pub mod graphycs_geometry;
-pub mod planet;
\ No newline at end of file
+pub mod planet;
+pub mod camera;
+pub mod points;
+pub mod overlay;
+pub mod wireframe;
+pub mod mesh_cache;
+pub mod instanced;
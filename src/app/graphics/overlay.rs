@@ -0,0 +1,139 @@
+use crate::app::graphics::gpu_resources::GPU_Resources;
+use crate::app::graphics::points::PointVertex;
+
+// Полупрозрачные треугольники (сферы влияния и другие подсказки) поверх сцены.
+// Рисуются после непрозрачных объектов, глубину проверяют, но не записывают
+pub struct TranslucentMesh {
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    uniform_buf: wgpu::Buffer,
+    vertex_buf: wgpu::Buffer,
+    capacity: usize,
+    count: usize,
+}
+
+impl TranslucentMesh {
+    pub fn new(resources: &GPU_Resources, color_format: wgpu::TextureFormat, capacity: usize) -> Self {
+        let device = &resources.device;
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Translucent mesh bind group layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: wgpu::BufferSize::new(size_of::<[[f32; 4]; 4]>() as u64),
+                },
+                count: None,
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Translucent mesh pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let uniform_buf = resources.buffer_fabric.create_buffer_init(
+            &[glam::Mat4::IDENTITY.to_cols_array_2d()],
+            "Translucent mesh uniform buffer",
+            wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        );
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Translucent mesh bind group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buf.as_entire_binding(),
+            }],
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shaders/points.wgsl"));
+        let vertex_buffers = [wgpu::VertexBufferLayout {
+            array_stride: size_of::<PointVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x3,
+                    offset: 0,
+                    shader_location: 0,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                },
+            ],
+        }];
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Translucent mesh pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &vertex_buffers,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Greater,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let vertex_buf = Self::create_vertex_buffer(resources, capacity);
+        TranslucentMesh { pipeline, bind_group, uniform_buf, vertex_buf, capacity, count: 0 }
+    }
+
+    fn create_vertex_buffer(resources: &GPU_Resources, capacity: usize) -> wgpu::Buffer {
+        resources.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Translucent mesh vertex buffer"),
+            size: (capacity.max(1) * size_of::<PointVertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    pub fn set_transform(&self, resources: &GPU_Resources, transform: glam::Mat4) {
+        resources.queue.write_buffer(&self.uniform_buf, 0, bytemuck::cast_slice(&[transform.to_cols_array_2d()]));
+    }
+
+    pub fn set_triangles(&mut self, resources: &GPU_Resources, vertices: &[PointVertex]) {
+        if vertices.len() > self.capacity {
+            self.capacity = vertices.len();
+            self.vertex_buf = Self::create_vertex_buffer(resources, self.capacity);
+        }
+        resources.queue.write_buffer(&self.vertex_buf, 0, bytemuck::cast_slice(vertices));
+        self.count = vertices.len();
+    }
+
+    pub fn draw(&self, rpass: &mut wgpu::RenderPass<'_>) {
+        if self.count == 0 {
+            return;
+        }
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.set_vertex_buffer(0, self.vertex_buf.slice(..));
+        rpass.draw(0..self.count as u32, 0..1);
+    }
+}
@@ -1,7 +1,63 @@
 use crate::app::graphics::graphycs_geometry::GraphicsGeometry;
+use crate::physics::coords::Coord;
+use crate::physics::geometry::Point3;
+
+// Кольца планеты: плоское кольцо с той же ориентацией и масштабом, что и планета
+pub struct PlanetRing {
+    pub geom_obj: GraphicsGeometry,
+    // индекс текстуры в StatePlanets::ring_textures
+    pub texture: usize,
+}
+
+// Орбита планеты, вокруг которой обращается спутник
+#[derive(Clone)]
+pub struct ParentOrbit {
+    // индекс планеты в списке планет сцены
+    pub index: usize,
+    pub orbit_start: Coord,
+    pub angle_speed: f32,
+}
+
+impl ParentOrbit {
+    pub fn position_at(&self, t: f64) -> Point3 {
+        orbit_position(&self.orbit_start, self.angle_speed, t)
+    }
+}
 
 pub struct Planet {
+    pub name: String,
     pub geom_obj: GraphicsGeometry,
+    // слой в текстурном массиве StatePlanets::planet_textures
     pub texture: i32,
     pub angle_speed: f32,
-}
\ No newline at end of file
+    // положение на орбите в момент t = 0, у спутника - относительно планеты parent
+    pub orbit_start: Coord,
+    // масса и физический радиус в массах и радиусах Земли; размер на экране задаёт geom_obj
+    pub mass_me: f64,
+    pub radius_re: f64,
+    pub ring: Option<PlanetRing>,
+    pub parent: Option<ParentOrbit>,
+}
+
+// Положение на круговой орбите через t шагов симуляции
+pub fn orbit_position(start: &Coord, angle_speed: f32, t: f64) -> Point3 {
+    let coord = Coord::new_spherical(start.r(), start.azimuth() + angle_speed as f64 * t, start.elevation());
+    Point3::from(coord)
+}
+
+impl Planet {
+    pub fn position_at(&self, t: f64) -> Point3 {
+        let position = orbit_position(&self.orbit_start, self.angle_speed, t);
+        match &self.parent {
+            Some(parent) => position + parent.position_at(t),
+            None => position,
+        }
+    }
+
+    pub fn set_center(&mut self, center: Point3) {
+        self.geom_obj.center = center;
+        if let Some(ring) = self.ring.as_mut() {
+            ring.geom_obj.center = center;
+        }
+    }
+}
@@ -3,6 +3,7 @@ use winit::window::Window;
 
 use crate::app::graphics::gpu_resources::GPU_Resources;
 use crate::app::graphics::surface::Surface;
+use crate::error::{Error, Result};
 
 pub struct Screen {
     pub window: Arc<Window>,
@@ -74,16 +75,29 @@ impl Screen {
         self.configure_surface();
     }
 
+    // Текстура следующего кадра. Потерянная или устаревшая поверхность (свёрнутое окно,
+    // смена монитора) перенастраивается, и кадр пропускается; остальные ошибки возвращаются
+    pub fn acquire_texture(&self) -> Result<Option<wgpu::SurfaceTexture>> {
+        match self.surface.get_current_texture() {
+            Ok(texture) => Ok(Some(texture)),
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.configure_surface();
+                Ok(None)
+            }
+            Err(wgpu::SurfaceError::Timeout) => Ok(None),
+            Err(source) => Err(Error::Surface(source)),
+        }
+    }
+
     
-    pub fn render<F>(&mut self, process_renderpass: F, pipeline: Option<wgpu::RenderPipeline>)
+    pub fn render<F>(&mut self, process_renderpass: F, pipeline: Option<wgpu::RenderPipeline>) -> Result<()>
     where
         F: Fn(&mut wgpu::RenderPass<'_>, wgpu::RenderPipeline),
     {
         // Create texture view
-        let surface_texture = self
-            .surface
-            .get_current_texture()
-            .expect("failed to acquire next swapchain texture");
+        let Some(surface_texture) = self.acquire_texture()? else {
+            return Ok(());
+        };
         let texture_view = surface_texture
             .texture
             .create_view(&wgpu::TextureViewDescriptor {
@@ -126,5 +140,6 @@ impl Screen {
         self.resources.submit_to_queue([encoder.finish()]);
         self.window.pre_present_notify();
         surface_texture.present();
+        Ok(())
     }
 }
\ No newline at end of file
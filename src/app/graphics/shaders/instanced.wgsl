@@ -0,0 +1,57 @@
+struct Uniforms {
+    transform: mat4x4<f32>,
+    light_origin: vec3<f32>,
+    ambient_strength: f32,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) world_pos: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) tint: vec4<f32>,
+    @location(4) @interpolate(flat) layer: u32,
+};
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+@group(0) @binding(1) var texture_sampler: sampler;
+@group(0) @binding(2) var textures: texture_2d_array<f32>;
+
+@vertex
+fn vs_main(
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) model_0: vec4<f32>,
+    @location(4) model_1: vec4<f32>,
+    @location(5) model_2: vec4<f32>,
+    @location(6) model_3: vec4<f32>,
+    @location(7) tint: vec4<f32>,
+    @location(8) layer: u32,
+) -> VertexOutput {
+    let model = mat4x4<f32>(model_0, model_1, model_2, model_3);
+    let world_pos = model * vec4<f32>(position, 1.0);
+
+    var result: VertexOutput;
+    result.position = uniforms.transform * world_pos;
+    // масштаб экземпляра равномерный, нормали поворачиваются той же матрицей
+    result.normal = normalize((model * vec4<f32>(normal, 0.0)).xyz);
+    result.world_pos = world_pos.xyz;
+    result.uv = uv;
+    result.tint = tint;
+    result.layer = layer;
+    return result;
+}
+
+@fragment
+fn fs_main(vertex: VertexOutput) -> @location(0) vec4<f32> {
+    let texture_color = textureSample(textures, texture_sampler, vertex.uv, vertex.layer) * vertex.tint;
+
+    let normal = normalize(vertex.normal);
+    let light_dir = normalize(uniforms.light_origin - vertex.world_pos);
+
+    let ambient = uniforms.ambient_strength;
+    let intensity = (pow(1.0 + dot(normal, light_dir), 2.0) + ambient) / (4.0 + ambient);
+
+    return vec4<f32>(texture_color.rgb * intensity, texture_color.a);
+}
@@ -0,0 +1,84 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+struct Uniforms {
+    transform: mat4x4<f32>,
+    color: vec4<f32>,
+    // размер экрана в пикселях
+    viewport: vec2<f32>,
+    // толщина линии в пикселях
+    thickness: f32,
+    _padding: f32,
+};
+
+@group(0)
+@binding(0)
+var<uniform> uniforms: Uniforms;
+
+// Каркас лежит на поверхности: глубина чуть увеличивается (reverse-Z), чтобы линии не мерцали
+const DEPTH_PULL: f32 = 1e-4;
+// Отрезки, уходящие за камеру, обрезаются по w, иначе деление на w выворачивает их
+const MIN_W: f32 = 1e-6;
+
+fn pull(clip: vec4<f32>) -> vec4<f32> {
+    return vec4<f32>(clip.xy, clip.z * (1.0 + DEPTH_PULL), clip.w);
+}
+
+@vertex
+fn vs_line(
+    @location(0) position: vec3<f32>,
+    @location(2) model_0: vec4<f32>,
+    @location(3) model_1: vec4<f32>,
+    @location(4) model_2: vec4<f32>,
+    @location(5) model_3: vec4<f32>
+) -> VertexOutput {
+    let model = mat4x4<f32>(model_0, model_1, model_2, model_3);
+    var result: VertexOutput;
+    result.position = pull(uniforms.transform * model * vec4<f32>(position, 1.0));
+    return result;
+}
+
+// Отрезок раздувается в прямоугольник постоянной ширины на экране: 6 вершин, два треугольника.
+// Все 6 вершин несут отрезок целиком, угол прямоугольника задаёт номер вершины
+@vertex
+fn vs_thick(
+    @builtin(vertex_index) index: u32,
+    @location(0) start: vec3<f32>,
+    @location(1) end: vec3<f32>,
+    @location(2) model_0: vec4<f32>,
+    @location(3) model_1: vec4<f32>,
+    @location(4) model_2: vec4<f32>,
+    @location(5) model_3: vec4<f32>
+) -> VertexOutput {
+    var ends = array<f32, 6>(0.0, 0.0, 1.0, 0.0, 1.0, 1.0);
+    var sides = array<f32, 6>(-1.0, 1.0, -1.0, 1.0, 1.0, -1.0);
+    let corner = index % 6u;
+
+    let model = mat4x4<f32>(model_0, model_1, model_2, model_3);
+    var a = uniforms.transform * model * vec4<f32>(start, 1.0);
+    var b = uniforms.transform * model * vec4<f32>(end, 1.0);
+    if (a.w < MIN_W) {
+        a = mix(a, b, (MIN_W - a.w) / (b.w - a.w));
+    } else if (b.w < MIN_W) {
+        b = mix(b, a, (MIN_W - b.w) / (a.w - b.w));
+    }
+
+    let screen = (b.xy / b.w - a.xy / a.w) * uniforms.viewport;
+    var direction = vec2<f32>(1.0, 0.0);
+    if (length(screen) > 1e-6) {
+        direction = normalize(screen);
+    }
+    // половина толщины в пикселях = thickness / viewport в NDC
+    let offset = vec2<f32>(-direction.y, direction.x) * uniforms.thickness / uniforms.viewport;
+
+    let clip = select(a, b, ends[corner] > 0.5);
+    var result: VertexOutput;
+    result.position = pull(vec4<f32>(clip.xy + offset * sides[corner] * clip.w, clip.z, clip.w));
+    return result;
+}
+
+@fragment
+fn fs_main(vertex: VertexOutput) -> @location(0) vec4<f32> {
+    return uniforms.color;
+}
@@ -0,0 +1,227 @@
+use bytemuck::{Pod, Zeroable};
+
+use crate::app::graphics::gpu_resources::GPU_Resources;
+use crate::physics::geometry::Point3;
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct WireframeUniforms {
+    transform: [[f32; 4]; 4],
+    color: [f32; 4],
+    viewport: [f32; 2],
+    thickness: f32,
+    _padding: f32,
+}
+
+// Вершина толстой линии: отрезок целиком, угол прямоугольника шейдер берёт из номера вершины
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct ThickVertex {
+    start: [f32; 3],
+    end: [f32; 3],
+}
+
+// вершин на отрезок: у аппаратной линии два конца, у толстой - два треугольника
+const LINE_VERTICES: usize = 2;
+const THICK_VERTICES: usize = 6;
+
+// Цвет и толщина каркаса в пикселях. Толщина не больше 1 рисуется аппаратными линиями
+// (LineList), толще - прямоугольниками постоянной ширины на экране
+#[derive(Debug, Clone, Copy)]
+pub struct WireframeStyle {
+    pub color: [f32; 4],
+    pub thickness: f32,
+}
+
+// Рёбра одной геометрии (см. Geometry::get_wireframe) в собственных координатах: строятся
+// и загружаются на GPU один раз. Тела с этой геометрией - экземпляры с модельными матрицами,
+// как в InstancedMeshes
+pub struct WireframeMesh {
+    line_buf: wgpu::Buffer,
+    thick_buf: wgpu::Buffer,
+    segment_count: usize,
+    instance_buf: wgpu::Buffer,
+    capacity: usize,
+    count: usize,
+}
+
+impl WireframeMesh {
+    pub fn new(resources: &GPU_Resources, segments: &[[Point3; 2]]) -> Self {
+        let to_f32 = |p: &Point3| [p.x as f32, p.y as f32, p.z as f32];
+        let line_vertices: Vec<[f32; 3]> = segments.iter().flatten().map(to_f32).collect();
+        let thick_vertices: Vec<ThickVertex> = segments.iter()
+            .flat_map(|[start, end]| [ThickVertex { start: to_f32(start), end: to_f32(end) }; THICK_VERTICES])
+            .collect();
+        let line_buf = resources.buffer_fabric.create_vertex_buffer_init(&line_vertices, Some("Wireframe line vertices"));
+        let thick_buf = resources.buffer_fabric.create_vertex_buffer_init(&thick_vertices, Some("Wireframe thick vertices"));
+        let instance_buf = Self::create_instance_buffer(resources, 1);
+        Self { line_buf, thick_buf, segment_count: segments.len(), instance_buf, capacity: 1, count: 0 }
+    }
+
+    fn create_instance_buffer(resources: &GPU_Resources, capacity: usize) -> wgpu::Buffer {
+        resources.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Wireframe instance buffer"),
+            size: (capacity.max(1) * size_of::<[[f32; 4]; 4]>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    // модельные матрицы относительно камеры; пустой список прячет каркас
+    pub fn set_instances(&mut self, resources: &GPU_Resources, models: &[glam::Mat4]) {
+        if models.len() > self.capacity {
+            self.capacity = models.len();
+            self.instance_buf = Self::create_instance_buffer(resources, self.capacity);
+        }
+        let models: Vec<[[f32; 4]; 4]> = models.iter().map(glam::Mat4::to_cols_array_2d).collect();
+        resources.queue.write_buffer(&self.instance_buf, 0, bytemuck::cast_slice(&models));
+        self.count = models.len();
+    }
+}
+
+// Конвейеры каркаса и его вид, общие для всех WireframeMesh
+pub struct Wireframe {
+    line_pipeline: wgpu::RenderPipeline,
+    thick_pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    uniform_buf: wgpu::Buffer,
+    pub style: WireframeStyle,
+}
+
+impl Wireframe {
+    pub fn new(resources: &GPU_Resources, color_format: wgpu::TextureFormat, style: WireframeStyle) -> Self {
+        let device = &resources.device;
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Wireframe bind group layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: wgpu::BufferSize::new(size_of::<WireframeUniforms>() as u64),
+                },
+                count: None,
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Wireframe pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let uniform_buf = resources.buffer_fabric.create_buffer_init(
+            &[WireframeUniforms {
+                transform: glam::Mat4::IDENTITY.to_cols_array_2d(),
+                color: style.color,
+                viewport: [1.0, 1.0],
+                thickness: style.thickness,
+                _padding: 0.0,
+            }],
+            "Wireframe uniform buffer",
+            wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        );
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Wireframe bind group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buf.as_entire_binding(),
+            }],
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shaders/wireframe.wgsl"));
+        let instance_layout = wgpu::VertexBufferLayout {
+            array_stride: size_of::<[[f32; 4]; 4]>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &wgpu::vertex_attr_array![2 => Float32x4, 3 => Float32x4, 4 => Float32x4, 5 => Float32x4],
+        };
+        let line_buffers = [
+            wgpu::VertexBufferLayout {
+                array_stride: size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &wgpu::vertex_attr_array![0 => Float32x3],
+            },
+            instance_layout.clone(),
+        ];
+        let thick_buffers = [
+            wgpu::VertexBufferLayout {
+                array_stride: size_of::<ThickVertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3],
+            },
+            instance_layout,
+        ];
+
+        let create_pipeline = |entry_point: &str, buffers: &[wgpu::VertexBufferLayout], topology: wgpu::PrimitiveTopology| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Wireframe pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some(entry_point),
+                    compilation_options: Default::default(),
+                    buffers,
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_main"),
+                    compilation_options: Default::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: color_format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology,
+                    ..Default::default()
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: wgpu::TextureFormat::Depth32Float,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::Greater,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+        let line_pipeline = create_pipeline("vs_line", &line_buffers, wgpu::PrimitiveTopology::LineList);
+        let thick_pipeline = create_pipeline("vs_thick", &thick_buffers, wgpu::PrimitiveTopology::TriangleList);
+
+        Wireframe { line_pipeline, thick_pipeline, bind_group, uniform_buf, style }
+    }
+
+    // viewport - размер экрана в пикселях, нужен для постоянной толщины линий.
+    // Цвет и толщина берутся из style
+    pub fn set_transform(&self, resources: &GPU_Resources, transform: glam::Mat4, viewport: (u32, u32)) {
+        let uniforms = WireframeUniforms {
+            transform: transform.to_cols_array_2d(),
+            color: self.style.color,
+            viewport: [viewport.0.max(1) as f32, viewport.1.max(1) as f32],
+            thickness: self.style.thickness,
+            _padding: 0.0,
+        };
+        resources.queue.write_buffer(&self.uniform_buf, 0, bytemuck::cast_slice(&[uniforms]));
+    }
+
+    // Каждый меш - один вызов draw на все его экземпляры
+    pub fn draw<'a>(&self, rpass: &mut wgpu::RenderPass<'_>, meshes: impl IntoIterator<Item = &'a WireframeMesh>) {
+        let thick = self.style.thickness > 1.0;
+        rpass.set_pipeline(if thick { &self.thick_pipeline } else { &self.line_pipeline });
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        for mesh in meshes.into_iter().filter(|mesh| mesh.count > 0 && mesh.segment_count > 0) {
+            let (buffer, vertices) = if thick {
+                (&mesh.thick_buf, THICK_VERTICES)
+            } else {
+                (&mesh.line_buf, LINE_VERTICES)
+            };
+            rpass.set_vertex_buffer(0, buffer.slice(..));
+            rpass.set_vertex_buffer(1, mesh.instance_buf.slice(..));
+            rpass.draw(0..(vertices * mesh.segment_count) as u32, 0..mesh.count as u32);
+        }
+    }
+}
@@ -4,6 +4,7 @@ use wgpu::util::DeviceExt;
 use winit::window::Window;
 use crate::app::graphics::gpu_resources::GPU_Resources;
 use crate::app::graphics::screen::Screen;
+use crate::error::Result;
 use crate::physics::geometry::generate_transform;
 use bytemuck::{Pod, Zeroable};
 use glam;
@@ -28,7 +29,7 @@ struct Cube {
 }
 
 impl Cube {
-    pub fn create_v_i(&self) -> (Vec<Vertex>, Vec<u16>) {
+    pub fn create_v_i(&self) -> (Vec<Vertex>, Vec<u32>) {
         let vertices = [
             // bottom
             vertex([-1, -1, -1]),
@@ -42,7 +43,7 @@ impl Cube {
             vertex([1, 1, 1]),
         ];
 
-        let indices: &[u16] = &[
+        let indices: &[u32] = &[
             0, 1, 3, 0, 2, 3, // bottom (xy-plane)
             4, 5, 7, 4, 6, 7, // top (xy-plane + 1)
             0, 1, 5, 0, 4, 5, // (xz-plane)
@@ -64,6 +65,7 @@ pub struct StateCube {
     
     vertex_buf: wgpu::Buffer,
     index_buf: wgpu::Buffer,
+    index_format: wgpu::IndexFormat,
     index_count: usize,
     bind_group: wgpu::BindGroup,
     uniform_buf: wgpu::Buffer,
@@ -89,7 +91,7 @@ impl StateCube {
 
         // create buffers for vertices and indices to pass to shading phase
         let vertex_buf = resources.buffer_fabric.create_vertex_buffer_init(&vertex_data, None);
-        let index_buf = resources.buffer_fabric.create_index_buffer_init(&index_data, None);
+        let (index_buf, index_format) = resources.buffer_fabric.create_mesh_index_buffer_init(&index_data, vertex_data.len(), None);
 
         // Create pipeline layout
         let bind_group_layout = resources.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -181,6 +183,7 @@ impl StateCube {
             resources: resources.clone(),
             vertex_buf,
             index_buf,
+            index_format,
             index_count: index_data.len(),
             bind_group,
             uniform_buf,
@@ -188,13 +191,12 @@ impl StateCube {
         }
     }
 
-    pub fn render(&mut self) {
+    pub fn render(&mut self) -> Result<()> {
         let mut encoder = self.resources.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
 
-        let surface_texture = self
-            .screen.surface
-            .get_current_texture()
-            .expect("failed to acquire next swapchain texture");
+        let Some(surface_texture) = self.screen.acquire_texture()? else {
+            return Ok(());
+        };
         let texture_view = surface_texture
             .texture
             .create_view(&wgpu::TextureViewDescriptor {
@@ -223,7 +225,7 @@ impl StateCube {
             rpass.push_debug_group("Prepare data for draw.");
             rpass.set_pipeline(&self.pipeline);
             rpass.set_bind_group(0, &self.bind_group, &[]);
-            rpass.set_index_buffer(self.index_buf.slice(..), wgpu::IndexFormat::Uint16);
+            rpass.set_index_buffer(self.index_buf.slice(..), self.index_format);
             rpass.set_vertex_buffer(0, self.vertex_buf.slice(..));
             rpass.pop_debug_group();
             rpass.insert_debug_marker("Draw!");
@@ -233,5 +235,6 @@ impl StateCube {
         self.resources.queue.submit(Some(encoder.finish()));
         self.screen.window.pre_present_notify();
         surface_texture.present();
+        Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,296 @@
+use std::sync::Arc;
+use std::fs;
+use winit::window::Window;
+use winit::keyboard::KeyCode;
+use rayon::prelude::*;
+use serde;
+
+use crate::app::graphics::gpu_resources::GPU_Resources;
+use crate::error::{self, Result};
+use crate::app::graphics::screen::Screen;
+use crate::app::graphics::camera::Camera;
+use crate::app::graphics::points::{PointCloud, PointVertex};
+use crate::physics::geometry::{PARALLEL_MIN_LEN, Point3};
+use crate::physics::nbody::{MassiveBody, NBodySystem};
+use crate::physics::galaxy::{DiskConfig, PlummerConfig, exponential_disk, place, plummer_sphere};
+
+// Положение, ориентация и скорость системы в сцене
+#[derive(Debug, serde::Deserialize)]
+struct JsonPlacement {
+    rotation_deg: [f64; 3],
+    offset: [f64; 3],
+    velocity: [f64; 3],
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum JsonSystem {
+    Plummer {
+        count: usize,
+        total_mass: f64,
+        scale_radius: f64,
+        seed: u64,
+        #[serde(flatten)]
+        placement: JsonPlacement,
+    },
+    Disk {
+        count: usize,
+        disk_mass: f64,
+        central_mass: f64,
+        scale_length: f64,
+        scale_height: f64,
+        max_radius: f64,
+        velocity_dispersion: f64,
+        seed: u64,
+        #[serde(flatten)]
+        placement: JsonPlacement,
+    },
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct JsonConfig {
+    softening: f64,
+    dt: f64,
+    substeps: usize,
+    camera_distance: f64,
+    // скорость, которой соответствует самый «красный» цвет
+    color_speed: f64,
+    systems: Vec<JsonSystem>,
+}
+
+fn load_config(file_path: &str) -> Result<JsonConfig> {
+    let path = std::path::Path::new(file_path);
+    let contents = fs::read_to_string(path).map_err(|e| error::Error::io(path, e))?;
+    serde_json::from_str(&contents).map_err(|e| error::Error::json(path, e))
+}
+
+// множитель сглаживания на одно нажатие клавиш [ и ]
+const SOFTENING_STEP: f64 = 1.25;
+const ZOOM_STEP: f64 = 0.8;
+const SLOW_COLOR: [f32; 3] = [0.3, 0.5, 1.0];
+const MID_COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+const FAST_COLOR: [f32; 3] = [1.0, 0.35, 0.25];
+
+fn mix(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t, a[2] + (b[2] - a[2]) * t]
+}
+
+// Медленные звёзды синие, быстрые красные
+fn speed_color(speed: f64, color_speed: f64) -> [f32; 4] {
+    let t = (speed / color_speed).clamp(0.0, 1.0) as f32;
+    let c = if t < 0.5 {
+        mix(SLOW_COLOR, MID_COLOR, 2.0 * t)
+    } else {
+        mix(MID_COLOR, FAST_COLOR, 2.0 * t - 1.0)
+    };
+    [c[0], c[1], c[2], 1.0]
+}
+
+// Звёздное скопление или столкновение галактик: прямое N-body в единицах G = 1
+pub struct StateGalaxy {
+    pub screen: Screen,
+    pub resources: Arc<GPU_Resources>,
+    pub camera: Camera,
+    pub system: NBodySystem,
+    pub dt: f64,
+    pub substeps: usize,
+    pub color_speed: f64,
+    pub paused: bool,
+    point_cloud: PointCloud,
+}
+
+impl StateGalaxy {
+    pub fn configure_surface(&self) {
+        self.screen.configure_surface();
+    }
+
+    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        self.screen.resize(new_size);
+        self.update_points();
+    }
+
+    fn load_bodies(config: &JsonConfig) -> Vec<MassiveBody> {
+        let mut bodies = Vec::new();
+        for json_system in &config.systems {
+            let (mut system, placement) = match json_system {
+                JsonSystem::Plummer { count, total_mass, scale_radius, seed, placement } => (
+                    plummer_sphere(&PlummerConfig {
+                        count: *count,
+                        total_mass: *total_mass,
+                        scale_radius: *scale_radius,
+                        seed: *seed,
+                    }),
+                    placement,
+                ),
+                JsonSystem::Disk {
+                    count, disk_mass, central_mass, scale_length, scale_height,
+                    max_radius, velocity_dispersion, seed, placement,
+                } => (
+                    exponential_disk(&DiskConfig {
+                        count: *count,
+                        disk_mass: *disk_mass,
+                        central_mass: *central_mass,
+                        scale_length: *scale_length,
+                        scale_height: *scale_height,
+                        max_radius: *max_radius,
+                        velocity_dispersion: *velocity_dispersion,
+                        softening: config.softening,
+                        seed: *seed,
+                    }),
+                    placement,
+                ),
+            };
+            let r = placement.rotation_deg;
+            place(
+                &mut system,
+                (r[0].to_radians(), r[1].to_radians(), r[2].to_radians()),
+                Point3::from(placement.offset),
+                Point3::from(placement.velocity),
+            );
+            bodies.extend(system);
+        }
+        bodies
+    }
+
+    pub fn new(window: Arc<Window>, resources: Arc<GPU_Resources>) -> Result<StateGalaxy> {
+        let mut screen = Screen::new(window.clone(), resources.clone());
+        screen.set_bg_color(wgpu::Color::BLACK);
+        screen.configure_surface();
+
+        let config = load_config("src/app/states/configs/galaxy.json")?;
+        let bodies = Self::load_bodies(&config);
+        log::info!("Galaxy scene: {} bodies", bodies.len());
+
+        let point_cloud = PointCloud::new(&resources, screen.surface.get_format(), bodies.len());
+        let d = config.camera_distance;
+        let mut state = StateGalaxy {
+            screen,
+            resources,
+            camera: Camera::new(Point3::new(0.0, -0.8 * d, 0.6 * d), Point3::zeros()),
+            system: NBodySystem::new(bodies, config.softening),
+            dt: config.dt,
+            substeps: config.substeps.max(1),
+            color_speed: config.color_speed,
+            paused: false,
+            point_cloud,
+        };
+        state.log_energy();
+        state.update_points();
+        Ok(state)
+    }
+
+    fn log_energy(&self) {
+        let kinetic = self.system.kinetic_energy();
+        let potential = self.system.potential_energy();
+        log::info!(
+            "t={:.3}: E={:.6} (K={:.6}, U={:.6}), softening={:.4}",
+            self.system.time, kinetic + potential, kinetic, potential, self.system.softening
+        );
+    }
+
+    pub fn update(&mut self) {
+        if self.paused {
+            return;
+        }
+        for _ in 0..self.substeps {
+            self.system.step(self.dt);
+        }
+        self.update_points();
+    }
+
+    fn update_points(&mut self) {
+        let camera = &self.camera;
+        let color_speed = self.color_speed;
+        let points: Vec<PointVertex> = self.system.bodies.par_iter().with_min_len(PARALLEL_MIN_LEN).map(|body| PointVertex {
+            position: camera.to_relative(&body.position),
+            color: speed_color(body.velocity.magnitude(), color_speed),
+        }).collect();
+        self.point_cloud.set_points(&self.resources, &points);
+        self.point_cloud.set_transform(&self.resources, self.camera.relative_transform(self.screen.get_ratio()));
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Space => self.paused = !self.paused,
+            // энергия системы: её дрейф показывает, хватает ли шага и сглаживания
+            KeyCode::KeyE => self.log_energy(),
+            KeyCode::BracketLeft => {
+                self.system.set_softening(self.system.softening / SOFTENING_STEP);
+                self.log_energy();
+            }
+            KeyCode::BracketRight => {
+                self.system.set_softening(self.system.softening * SOFTENING_STEP);
+                self.log_energy();
+            }
+            KeyCode::Equal => {
+                self.camera.zoom(ZOOM_STEP);
+                self.update_points();
+            }
+            KeyCode::Minus => {
+                self.camera.zoom(1.0 / ZOOM_STEP);
+                self.update_points();
+            }
+            _ => (),
+        }
+    }
+
+    pub fn render(&mut self) -> Result<()> {
+        let mut encoder = self.resources.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let Some(surface_texture) = self.screen.acquire_texture()? else {
+            return Ok(());
+        };
+        let texture_view = surface_texture
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor {
+                format: Some(self.screen.surface.get_format().add_srgb_suffix()),
+                ..Default::default()
+            });
+
+        let depth_texture = self.resources.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("depth_texture"),
+            size: wgpu::Extent3d {
+                width: self.screen.get_width(),
+                height: self.screen.get_height(),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Depth32Float,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &texture_view,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.screen.get_bg_color()),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(0.0), // reverse-Z
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            self.point_cloud.draw(&mut rpass);
+        }
+
+        self.resources.submit_to_queue(Some(encoder.finish()));
+        self.screen.window.pre_present_notify();
+        surface_texture.present();
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@ use winit::window::Window;
 
 use crate::app::graphics::gpu_resources::GPU_Resources;
 use crate::app::graphics::screen::Screen;
+use crate::error::Result;
 
 pub struct StateGreenScreen {
     pub screen: Screen
@@ -25,11 +26,11 @@ impl StateGreenScreen {
         self.screen.resize(new_size)
     }
 
-    pub fn render(&mut self) {
+    pub fn render(&mut self) -> Result<()> {
         self.screen.render(|renderpass, pipeline| {
             // Ваша логика отрисовки здесь, например:
             // renderpass.set_pipeline(&self.your_pipeline);
             // renderpass.draw_indexed(0..CUBE_INDICES.len() as u32, 0, 0);
-        }, None);
+        }, None)
     }
 }
\ No newline at end of file
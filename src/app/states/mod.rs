@@ -3,4 +3,6 @@ pub mod cube;
 pub mod green_screen;
 pub mod ball;
 pub mod moving_ball;
-pub mod planets;
\ No newline at end of file
+pub mod planets;
+pub mod galaxy;
+pub mod replay;
\ No newline at end of file
@@ -5,6 +5,7 @@ use glam;
 use std::f64::consts::PI;
 
 use crate::app::graphics::gpu_resources::GPU_Resources;
+use crate::error::Result;
 use crate::app::graphics::screen::Screen;
 use crate::physics::geometry::{Mesh, Point3};
 use crate::app::graphics::graphycs_geometry::GraphicsGeometry;
@@ -87,12 +88,13 @@ impl StateMovingBall {
         self.screen.configure_surface();
     }
 
-    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+    // буферы пересоздаются под новый размер
+    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) -> Result<()> {
         self.screen.resize(new_size);
-        self.init();
+        self.init()
     }
 
-    pub fn new(window: Arc<Window>, resources: Arc<GPU_Resources>) -> StateMovingBall {
+    pub fn new(window: Arc<Window>, resources: Arc<GPU_Resources>) -> Result<StateMovingBall> {
         let mut screen = Screen::new(window.clone(), resources.clone());
         screen.set_bg_color(wgpu::Color::BLACK);
         screen.configure_surface();
@@ -113,18 +115,17 @@ impl StateMovingBall {
             resources: resources.clone(), 
             gtools 
         };
-        state.init();
-        
-        state
+        state.init()?;
+        Ok(state)
     }
 
-    fn create_entity(&self, color: wgpu::Color) -> Entity {
-        let (vertices_s, indices_s) = self.get_vertices_indices_surface();
-        let (vertices_e, indices_e) = self.get_vertices_indices_edges();
+    fn create_entity(&self, color: wgpu::Color) -> Result<Entity> {
+        let (vertices_s, indices_s) = self.get_vertices_indices_surface()?;
+        let (vertices_e, indices_e) = self.get_vertices_indices_edges()?;
 
-        let length_s = vertices_s.len() as u16;
+        let length_s = vertices_s.len() as u32;
         let vertices: Vec<Vertex> = [vertices_s, vertices_e].concat();
-        let indices: Vec<u16> = indices_s
+        let indices: Vec<u32> = indices_s
             .iter()
             .cloned()
             .chain(indices_e.iter().map(|&x| x + length_s))
@@ -132,29 +133,29 @@ impl StateMovingBall {
         assert_eq!(indices_s.len() + indices_e.len(), indices.len(), "Lengthes must be the same!");
 
         let v_buf = self.resources.buffer_fabric.create_vertex_buffer_init(&vertices, None);
-        let i_buf = self.resources.buffer_fabric.create_index_buffer_init(&indices, None);
+        let (i_buf, index_format) = self.resources.buffer_fabric.create_mesh_index_buffer_init(&indices, vertices.len(), None);
         let mx_total = generate_transform(self.screen.get_ratio());
 
-        Entity {
+        Ok(Entity {
             mx_world: mx_total,
             color: color,
             vertex_buf: v_buf,
             index_buf: i_buf,
-            index_format: wgpu::IndexFormat::Uint16,
+            index_format,
             index_count: indices.len(),
             uniform_offset: 0
-        }
+        })
     }
 
-    pub fn init(&mut self) {
+    pub fn init(&mut self) -> Result<()> {
         self.gtools.init(self.resources.clone());
 
         // let (vertices, indices) = self.get_vertices_indices_surface();
         // let vertex_buf = self.resources.buffer_fabric.create_vertex_buffer(&vertices, None);
         // let index_buf = self.resources.buffer_fabric.create_index_buffer(&indices, None);
-        let entity = self.create_entity(wgpu::Color::WHITE);
+        let entity = self.create_entity(wgpu::Color::WHITE)?;
         self.gtools.push_entity(entity);
-        let entity = self.create_entity(wgpu::Color::WHITE);
+        let entity = self.create_entity(wgpu::Color::WHITE)?;
         self.gtools.entities[0] = entity;
 
         let bind_group_layout = self.resources.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -239,16 +240,17 @@ impl StateMovingBall {
         self.gtools.set_bind_group(bind_group);
         self.gtools.set_bind_group_layout(bind_group_layout);
         self.gtools.init_pipeline(shader, &vertex_buffers, &[Some(self.screen.surface.get_format().into())]);
+        Ok(())
     }
 
-    pub fn update(&mut self) {
+    pub fn update(&mut self) -> Result<()> {
         let mut coord = Coord::new_cartesian(self.ball.center.x, self.ball.center.y, self.ball.center.z);
-        coord.set_spherical(coord.r, coord.azimuth + PI / 100.0, coord.elevation);
+        coord.set_spherical(coord.r(), coord.azimuth() + PI / 100.0, coord.elevation());
         self.ball.center = Point3::new(coord.x, coord.y, coord.z);
-        self.init();
+        self.init()
     }
 
-    fn transform_mesh_to_vertices_indices(mesh: Mesh, color: wgpu::Color) -> (Vec<Vertex>, Vec<u16>) {
+    fn transform_mesh_to_vertices_indices(mesh: Mesh, color: wgpu::Color) -> (Vec<Vertex>, Vec<u32>) {
         let center = mesh.vertices.iter().fold([0.0; 3], |acc, v| {
             [acc[0] + v[0] as f32, acc[1] + v[1] as f32, acc[2] + v[2] as f32]
         });
@@ -271,25 +273,25 @@ impl StateMovingBall {
 
         let mut indices = Vec::new();
         for index_triplet in mesh.indices {
-            indices.push(index_triplet[0] as u16);
-            indices.push(index_triplet[1] as u16);
-            indices.push(index_triplet[2] as u16);
+            indices.push(index_triplet[0] as u32);
+            indices.push(index_triplet[1] as u32);
+            indices.push(index_triplet[2] as u32);
         }
 
         (vertices, indices)
     }
 
-    fn get_vertices_indices_surface(&self) -> (Vec<Vertex>, Vec<u16>) {
-        let mesh = self.ball.get_surface();
-        Self::transform_mesh_to_vertices_indices(mesh, wgpu::Color{r:0.0, g:1.0, b:1.0, a:1.0})
+    fn get_vertices_indices_surface(&self) -> Result<(Vec<Vertex>, Vec<u32>)> {
+        let mesh = self.ball.get_surface()?;
+        Ok(Self::transform_mesh_to_vertices_indices(mesh, wgpu::Color{r:0.0, g:1.0, b:1.0, a:1.0}))
     }
 
-    fn get_vertices_indices_edges(&self) -> (Vec<Vertex>, Vec<u16>) {
-        let mesh = self.ball.get_edges(0.01);
-        Self::transform_mesh_to_vertices_indices(mesh, wgpu::Color{r:1.0, g:0.0, b:1.0, a:1.0})
+    fn get_vertices_indices_edges(&self) -> Result<(Vec<Vertex>, Vec<u32>)> {
+        let mesh = self.ball.get_edges(0.01)?;
+        Ok(Self::transform_mesh_to_vertices_indices(mesh, wgpu::Color{r:1.0, g:0.0, b:1.0, a:1.0}))
     }
 
-    pub fn render(&mut self) {
+    pub fn render(&mut self) -> Result<()> {
         self.gtools.render(&self.screen)
     }
 }
@@ -353,12 +355,11 @@ impl GraphicsTools {
         self.pipeline = Some(pipeline);
     }
 
-    pub fn render(&self, screen: &Screen) {
+    pub fn render(&self, screen: &Screen) -> Result<()> {
         let mut encoder = self.resources.as_ref().unwrap().create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-        let surface_texture = screen
-            .surface
-            .get_current_texture()
-            .expect("failed to acquire next swapchain texture");
+        let Some(surface_texture) = screen.acquire_texture()? else {
+            return Ok(());
+        };
         let texture_view = surface_texture
             .texture
             .create_view(&wgpu::TextureViewDescriptor {
@@ -410,7 +411,7 @@ impl GraphicsTools {
             rpass.push_debug_group("Prepare data for draw.");
             rpass.set_pipeline(&self.pipeline.as_ref().unwrap());
             rpass.set_bind_group(0, &self.bind_group, &[]);
-            rpass.set_index_buffer(self.entities[0].index_buf.slice(..), wgpu::IndexFormat::Uint16);
+            rpass.set_index_buffer(self.entities[0].index_buf.slice(..), self.entities[0].index_format);
             rpass.set_vertex_buffer(0, self.entities[0].vertex_buf.slice(..));
             rpass.draw_indexed(0..self.entities[0].index_count as u32, 0, 0..1);
             rpass.pop_debug_group();
@@ -422,6 +423,7 @@ impl GraphicsTools {
             .submit_to_queue(Some(encoder.finish()));
         screen.window.pre_present_notify();
         surface_texture.present();
+        Ok(())
     }
 }
 
@@ -0,0 +1,200 @@
+use std::fs;
+use std::path::Path;
+
+use crate::error::{self, Result};
+
+pub(super) const CONFIG_PATH: &str = "src/app/states/configs/planets.json";
+
+// Кольца планеты: радиусы в радиусах планеты, текстуры - полосы от внутреннего края к внешнему.
+// Без текстуры прозрачности непрозрачность берётся из яркости цветной текстуры
+#[derive(Debug, serde::Deserialize)]
+pub(super) struct JsonRing {
+    pub(super) inner_radius: f64,
+    pub(super) outer_radius: f64,
+    pub(super) color_texture_path: String,
+    #[serde(default)]
+    pub(super) alpha_texture_path: Option<String>,
+}
+
+// Форма тела из файла (OBJ, glTF/GLB, STL, PLY) вместо сферы, например астероид 433 Эрос.
+// scale переводит единицы файла в радиусы тела; модель не обязана быть отцентрована
+#[derive(Debug, serde::Deserialize)]
+pub(super) struct JsonModel {
+    pub(super) path: String,
+    pub(super) scale: f64,
+}
+
+// Фрактальный шум рельефа; незаданные параметры берутся из FractalNoise::new
+#[derive(Debug, serde::Deserialize)]
+pub(super) struct JsonNoise {
+    pub(super) seed: u64,
+    #[serde(default)]
+    pub(super) octaves: Option<u32>,
+    #[serde(default)]
+    pub(super) frequency: Option<f64>,
+    #[serde(default)]
+    pub(super) lacunarity: Option<f64>,
+    #[serde(default)]
+    pub(super) gain: Option<f64>,
+    // горные хребты вместо холмов
+    #[serde(default)]
+    pub(super) ridged: bool,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(super) struct JsonCraters {
+    pub(super) seed: u64,
+    pub(super) count: usize,
+    pub(super) min_radius_deg: f64,
+    pub(super) max_radius_deg: f64,
+    pub(super) size_index: f64,
+}
+
+// Рельеф тела: сфера, вершины которой сдвинуты по нормали на amplitude * exaggeration
+// радиусов тела, умноженных на высоту. Заданные карта высот, шум и кратеры складываются
+#[derive(Debug, serde::Deserialize)]
+pub(super) struct JsonTerrain {
+    pub(super) amplitude: f64,
+    #[serde(default = "default_exaggeration")]
+    pub(super) exaggeration: f64,
+    #[serde(default)]
+    pub(super) heightmap: Option<String>,
+    #[serde(default)]
+    pub(super) noise: Option<JsonNoise>,
+    #[serde(default)]
+    pub(super) craters: Option<JsonCraters>,
+}
+
+pub(super) fn default_exaggeration() -> f64 {
+    1.0
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(super) struct JsonPlanet {
+    pub(super) name: String,
+    pub(super) year_dur_re: u32,
+    #[serde(rename = "R_au")]
+    pub(super) r_au: f64,
+    pub(super) radius_re: f64,
+    pub(super) texture_path: String,
+    pub(super) move_direction: String,
+    pub(super) is_giant: bool,
+    pub(super) mass_me: f64,
+    // наклон оси вращения к оси эклиптики, кольца лежат в плоскости экватора
+    #[serde(default)]
+    pub(super) axial_tilt_deg: f64,
+    #[serde(default)]
+    pub(super) ring: Option<JsonRing>,
+    #[serde(default)]
+    pub(super) model: Option<JsonModel>,
+    // рельеф вместо гладкой сферы, несовместим с model
+    #[serde(default)]
+    pub(super) terrain: Option<JsonTerrain>,
+    // спутник: планета выше по списку, R_au и year_dur_re тогда задают орбиту вокруг неё
+    #[serde(default)]
+    pub(super) parent: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(super) struct JsonBelt {
+    pub(super) name: String,
+    pub(super) count: usize,
+    pub(super) inner_au: f64,
+    pub(super) outer_au: f64,
+    pub(super) eccentricity_sigma: f64,
+    pub(super) max_eccentricity: f64,
+    pub(super) inclination_sigma_deg: f64,
+    pub(super) min_size_km: f64,
+    pub(super) max_size_km: f64,
+    pub(super) size_index: f64,
+    pub(super) seed: u64,
+    pub(super) color: [f32; 4],
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(super) struct JsonComet {
+    pub(super) name: String,
+    pub(super) perihelion_au: f64,
+    pub(super) eccentricity: f64,
+    pub(super) inclination_deg: f64,
+    pub(super) ascending_node_deg: f64,
+    pub(super) arg_perihelion_deg: f64,
+    pub(super) perihelion_time_years: f64,
+    pub(super) ion_tail_au: f64,
+    pub(super) tail_particles: usize,
+    pub(super) seed: u64,
+}
+
+// Именованный астероид: движется как частица пояса, но его можно указать в резонансе
+#[derive(Debug, serde::Deserialize)]
+pub(super) struct JsonAsteroid {
+    pub(super) name: String,
+    pub(super) semi_major_axis_au: f64,
+    pub(super) eccentricity: f64,
+    pub(super) inclination_deg: f64,
+    pub(super) ascending_node_deg: f64,
+    pub(super) arg_perihelion_deg: f64,
+    pub(super) mean_anomaly_deg: f64,
+    pub(super) size_km: f64,
+    pub(super) color: [f32; 4],
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(super) struct JsonResonance {
+    pub(super) name: String,
+    pub(super) bodies: Vec<String>,
+    pub(super) lambda: Vec<i32>,
+    pub(super) pericentre: Vec<i32>,
+}
+
+// Начальное состояние аппарата относительно центрального тела в момент t = 0
+#[derive(Debug, serde::Deserialize)]
+pub(super) struct JsonSpacecraft {
+    pub(super) name: String,
+    pub(super) central: String,
+    pub(super) offset_au: [f64; 3],
+    pub(super) velocity_au_yr: [f64; 3],
+}
+
+// Наблюдатель на поверхности тела: широта и долгота геодезические
+#[derive(Debug, Clone, serde::Deserialize)]
+pub(super) struct JsonObserver {
+    pub(super) body: String,
+    pub(super) latitude_deg: f64,
+    pub(super) longitude_deg: f64,
+    pub(super) altitude_km: f64,
+    pub(super) equatorial_radius_km: f64,
+    pub(super) polar_radius_km: f64,
+    pub(super) obliquity_deg: f64,
+    pub(super) sidereal_day_hours: f64,
+    // угол нулевого меридиана от точки равноденствия в момент t = 0
+    pub(super) rotation_at_epoch_deg: f64,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(super) struct JsonConfig {
+    pub(super) planets: Vec<JsonPlanet>,
+    #[serde(default)]
+    pub(super) belts: Vec<JsonBelt>,
+    #[serde(default)]
+    pub(super) comets: Vec<JsonComet>,
+    #[serde(default)]
+    pub(super) asteroids: Vec<JsonAsteroid>,
+    #[serde(default)]
+    pub(super) resonances: Vec<JsonResonance>,
+    #[serde(default)]
+    pub(super) spacecraft: Vec<JsonSpacecraft>,
+    #[serde(default)]
+    pub(super) observer: Option<JsonObserver>,
+}
+
+
+// Конфиг читается как дерево JSON: в таком виде он попадает в журнал прогона
+pub(super) fn load_config(path: &Path) -> Result<serde_json::Value> {
+    let contents = fs::read_to_string(path).map_err(|e| error::Error::io(path, e))?;
+    serde_json::from_str(&contents).map_err(|e| error::Error::json(path, e))
+}
+
+pub(super) fn parse_config(path: &Path, config: &serde_json::Value) -> Result<JsonConfig> {
+    serde_json::from_value(config.clone()).map_err(|e| error::Error::json(path, e))
+}
@@ -0,0 +1,1069 @@
+use std::sync::Arc;
+use winit::window::Window;
+use winit::keyboard::KeyCode;
+use bytemuck::{Pod, Zeroable};
+use rayon::prelude::*;
+use glam;
+
+use crate::app::graphics::gpu_resources::GPU_Resources;
+use crate::error::{self, Result};
+use crate::app::graphics::screen::Screen;
+use crate::physics::geometry::{Geometry, Mesh, PARALLEL_MIN_LEN, Point3};
+use crate::physics::ball::{Ball, sphere_uv};
+use crate::app::graphics::planet::{Planet, PlanetRing};
+use crate::app::graphics::camera::Camera;
+use crate::physics::comet::TailKind;
+use crate::physics::orbit::MU_SUN;
+use crate::app::graphics::points::{PointCloud, PointVertex};
+use crate::app::graphics::overlay::TranslucentMesh;
+use crate::app::graphics::wireframe::{Wireframe, WireframeMesh, WireframeStyle};
+use crate::app::graphics::mesh_cache::{GeometryCache, GpuMesh};
+use crate::app::graphics::instanced::{Instance, InstancedMeshes, create_texture_array};
+use crate::app::states::replay::{Input, ReplayLog, ReplayMode, Replayer};
+use std::path::{Path, PathBuf};
+use image;
+use image::GenericImageView;
+
+mod config;
+mod simulation;
+
+use config::{CONFIG_PATH, JsonConfig, JsonRing, load_config, parse_config};
+use simulation::{R, YEARS_PER_STEP};
+pub use simulation::PlanetsSimulation;
+
+fn load_image(path: &Path) -> Result<image::DynamicImage> {
+    let reader = image::ImageReader::open(path).map_err(|e| error::Error::io(path, e))?;
+    reader.decode().map_err(|e| error::Error::image(path, e))
+}
+
+fn upload_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    label: &str,
+    rgba: &image::RgbaImage,
+) -> wgpu::TextureView {
+    let dimensions = rgba.dimensions();
+
+    // Создаём описание текстуры
+    let texture_size = wgpu::Extent3d {
+        width: dimensions.0,
+        height: dimensions.1,
+        depth_or_array_layers: 1,
+    };
+
+    // Создаём текстуру на GPU
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(&format!("Texture: {}", label)),
+        size: texture_size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,  // Формат с гамма-коррекцией
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+
+    // Копируем данные изображения в текстуру GPU
+    queue.write_texture(
+        // Вместо wgpu::ImageCopyTexture используем wgpu::TexelCopyTextureInfo
+        wgpu::TexelCopyTextureInfo {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        rgba,
+        // Вместо wgpu::ImageDataLayout используем wgpu::TexelCopyBufferLayout
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            // 4 байта на пиксель (RGBA)
+            // ПРИМЕЧАНИЕ: bytes_per_row должен быть кратен 256 для оптимальной производительности[citation:1]
+            bytes_per_row: Some(4 * dimensions.0),
+            rows_per_image: Some(dimensions.1),
+        },
+        texture_size,
+    );
+
+    // Создаём "вид" (view) текстуры для использования в шейдере (без изменений)
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+// Цвет колец из одной текстуры, непрозрачность - из альфа-канала (или яркости) другой,
+// растянутой до размеров первой
+fn load_ring_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    ring: &JsonRing,
+) -> Result<wgpu::TextureView> {
+    let mut rgba = load_image(Path::new(&ring.color_texture_path))?.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    let alpha: image::GrayImage = match &ring.alpha_texture_path {
+        Some(path) => {
+            let img = load_image(Path::new(path))?;
+            let alpha = if img.color().has_alpha() {
+                image::GrayImage::from_fn(img.width(), img.height(), |x, y| image::Luma([img.get_pixel(x, y)[3]]))
+            } else {
+                img.to_luma8()
+            };
+            image::imageops::resize(&alpha, width, height, image::imageops::FilterType::Triangle)
+        }
+        None => image::DynamicImage::ImageRgba8(rgba.clone()).to_luma8(),
+    };
+    for (pixel, a) in rgba.pixels_mut().zip(alpha.pixels()) {
+        pixel[3] = a[0];
+    }
+    Ok(upload_texture(device, queue, &ring.color_texture_path, &rgba))
+}
+
+const CAMERA_EYE: [f64; 3] = [R as f64 * 6.0, R as f64 * 4.0, R as f64 * 6.0];
+// множитель расстояния до цели на одно нажатие клавиши приближения
+const ZOOM_STEP: f64 = 0.8;
+// наибольшее число шагов симуляции за кадр
+const MAX_SPEED: u32 = 64;
+// расстояние от камеры до выбранной планеты в её радиусах и до выбранного аппарата
+const TARGET_DISTANCE_RADII: f64 = 8.0;
+const SPACECRAFT_TARGET_DISTANCE: f64 = 5.0;
+const COMET_NUCLEUS_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const ION_TAIL_COLOR: [f32; 3] = [0.4, 0.6, 1.0];
+const DUST_TAIL_COLOR: [f32; 3] = [1.0, 0.9, 0.7];
+const SPACECRAFT_COLOR: [f32; 4] = [1.0, 0.2, 0.2, 1.0];
+const SOI_COLOR: [f32; 4] = [0.3, 0.9, 0.4, 0.15];
+// начальный вид каркаса; дальше его меняют клавиши, и значения хранит Wireframe::style
+const WIREFRAME_STYLE: WireframeStyle = WireframeStyle { color: [1.0, 0.0, 1.0, 1.0], thickness: 1.5 };
+const WIREFRAME_COLORS: [[f32; 4]; 4] = [[1.0, 0.0, 1.0, 1.0], [0.0, 1.0, 1.0, 1.0], [1.0, 1.0, 0.0, 1.0], [1.0, 1.0, 1.0, 1.0]];
+const WIREFRAME_THICKNESS_STEP: f32 = 0.5;
+const WIREFRAME_MAX_THICKNESS: f32 = 8.0;
+const RING_COLOR: wgpu::Color = wgpu::Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 };
+
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct Vertex {
+    _pos: [f32; 4],
+    _col: [f32; 4],
+    _normal: [f32; 3],
+    _uv: [f32; 2],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct Uniforms {
+    transform: [[f32; 4]; 4],
+    light_origin: [f32; 3],
+    _padding1: f32,  // Выравнивание до 16 байт
+
+    light_color: [f32; 3],
+    _padding2: f32,  // Выравнивание до 16 байт
+
+    ambient_strength: f32,
+    _padding3: [f32; 3],  // Добиваем до 16 байт
+}
+
+// Модельная матрица тела, у каждого тела свой участок буфера (динамическое смещение)
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct ModelUniforms {
+    model: [[f32; 4]; 4],
+}
+
+// Тело сцены: меш из GraphicsTools::meshes и модельная матрица относительно камеры
+struct Entity {
+    mesh: usize,
+    mx_world: glam::Mat4,
+    uniform_offset: wgpu::DynamicOffset,
+}
+
+#[derive(Default)]
+struct GraphicsTools {
+    resources: Option<Arc<GPU_Resources>>,
+
+    pipeline_layout: Option<wgpu::PipelineLayout>,
+    bind_group_layout: Option<wgpu::BindGroupLayout>,
+
+    uniform_buf: Option<wgpu::Buffer>,
+    model_buf: Option<wgpu::Buffer>,
+    // расстояние между матрицами в model_buf, кратно min_uniform_buffer_offset_alignment
+    model_stride: u64,
+
+    meshes: GeometryCache<GpuMesh>,
+    // планеты одной геометрии, каждая группа рисуется одним вызовом
+    planet_batches: GeometryCache<InstancedMeshes>,
+    // кольца с индексом текстуры, от дальних к ближним
+    rings: Vec<(Entity, usize)>,
+    ring_pipeline: Option<wgpu::RenderPipeline>,
+    ring_bind_groups: Vec<wgpu::BindGroup>,
+    point_cloud: Option<PointCloud>,
+    wireframe: Option<Wireframe>,
+    // рёбра каждой уникальной геометрии, загружаются один раз
+    wireframe_meshes: GeometryCache<WireframeMesh>,
+    soi_overlay: Option<TranslucentMesh>,
+}
+
+// Тело, за которым следует камера
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CameraTarget {
+    Sun,
+    Planet(usize),
+    Spacecraft(usize),
+}
+
+// Запись прогона: журнал сохраняется в path при закрытии окна
+struct Recorder {
+    path: PathBuf,
+    log: ReplayLog,
+}
+
+pub struct StatePlanets {
+    pub screen: Screen,
+    pub sim: PlanetsSimulation,
+    pub resources: Arc<GPU_Resources>,
+    gtools: GraphicsTools,
+    
+    // текстуры планет - слои одного массива, слой планеты в Planet::texture
+    pub planet_textures: wgpu::TextureView,
+    pub ring_textures: Vec<wgpu::TextureView>,
+    pub texture_sampler: Option<wgpu::Sampler>,
+    pub camera: Camera,
+    camera_target: CameraTarget,
+
+    pub show_soi: bool,
+    soi_sphere: Mesh,
+    pub show_wireframe: bool,
+
+    // пауза и число шагов симуляции за кадр
+    paused: bool,
+    speed: u32,
+    recorder: Option<Recorder>,
+    replayer: Option<Replayer>,
+}
+
+
+impl StatePlanets {
+    pub fn configure_surface(&self) {
+        self.screen.configure_surface();
+    }
+
+    // Буферы сцены пересоздаются под новый размер; ошибка переводит окно в State::Failed
+    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) -> Result<()> {
+        self.screen.resize(new_size);
+        self.init()
+    }
+
+    // Текстуры планет и колец; не загрузившаяся текстура - ошибка конфига
+    fn load_textures(resources: &GPU_Resources, config: &JsonConfig, planets: &mut [Planet]) -> Result<(wgpu::TextureView, Vec<wgpu::TextureView>)> {
+        let mut planet_images = Vec::new();
+        let mut ring_textures = Vec::new();
+        for (planet, json_planet) in planets.iter_mut().zip(&config.planets) {
+            planet_images.push(load_image(Path::new(&json_planet.texture_path))?.to_rgba8());
+
+            let Some(json_ring) = &json_planet.ring else {
+                continue;
+            };
+            if let Some(ring) = planet.ring.as_mut() {
+                ring.texture = ring_textures.len();
+            }
+            ring_textures.push(load_ring_texture(&resources.device, &resources.queue, json_ring)?);
+        }
+
+        let planet_textures = create_texture_array(resources, "Planet textures", &planet_images);
+        Ok((planet_textures, ring_textures))
+    }
+
+    fn comet_points(&self) -> Vec<PointVertex> {
+        let t = self.sim.time * YEARS_PER_STEP;
+        let mut points = Vec::new();
+        for comet in self.sim.comets.iter() {
+            for particle in comet.tail_at(MU_SUN, t) {
+                let base = match particle.kind {
+                    TailKind::Ion => ION_TAIL_COLOR,
+                    TailKind::Dust => DUST_TAIL_COLOR,
+                };
+                let k = particle.intensity as f32;
+                points.push(PointVertex {
+                    position: self.camera.to_relative(&(particle.position * R as f64)),
+                    color: [base[0] * k, base[1] * k, base[2] * k, 1.0],
+                });
+            }
+            points.push(PointVertex {
+                position: self.camera.to_relative(&(comet.position_at(MU_SUN, t) * R as f64)),
+                color: COMET_NUCLEUS_COLOR,
+            });
+        }
+        points
+    }
+
+    fn update_points(&mut self) {
+        let camera = &self.camera;
+        let mut points: Vec<PointVertex> = self.sim.belt_particles.par_iter()
+            .zip(self.sim.belt_colors.par_iter())
+            .with_min_len(PARALLEL_MIN_LEN)
+            .map(|(particle, color)| PointVertex {
+                position: camera.to_relative(&(particle.position * R as f64)),
+                color: *color,
+            })
+            .collect();
+        points.extend(self.comet_points());
+        let t = self.sim.time * YEARS_PER_STEP;
+        points.extend(self.sim.spacecraft.iter().map(|craft| PointVertex {
+            position: self.camera.to_relative(&(self.sim.propagator.state_of(craft, t).0 * R as f64)),
+            color: SPACECRAFT_COLOR,
+        }));
+        let transform = self.camera.relative_transform(self.screen.get_ratio());
+        if let Some(point_cloud) = self.gtools.point_cloud.as_mut() {
+            point_cloud.set_points(&self.resources, &points);
+            point_cloud.set_transform(&self.resources, transform);
+        }
+    }
+
+    // Рёбра загружены в init_meshes, каждый кадр обновляются только модельные матрицы
+    fn update_wireframe(&mut self) {
+        let Some(wireframe) = self.gtools.wireframe.as_ref() else {
+            return;
+        };
+        let meshes = &mut self.gtools.wireframe_meshes;
+        let mut instances: Vec<Vec<glam::Mat4>> = vec![Vec::new(); meshes.len()];
+        if self.show_wireframe {
+            for planet in &self.sim.planets {
+                if let Some(index) = meshes.index_of(&planet.geom_obj.geometry) {
+                    instances[index].push(planet.geom_obj.model_matrix(&self.camera.eye));
+                }
+            }
+        }
+        for (index, models) in instances.iter().enumerate() {
+            meshes.get_mut(index).set_instances(&self.resources, models);
+        }
+        let transform = self.camera.relative_transform(self.screen.get_ratio());
+        let viewport = (self.screen.get_width(), self.screen.get_height());
+        wireframe.set_transform(&self.resources, transform, viewport);
+    }
+
+    // Толщина не меньше 1 пикселя: тоньше аппаратные линии не рисуются
+    fn change_wireframe_style(&mut self, input: Input) {
+        let Some(wireframe) = self.gtools.wireframe.as_mut() else {
+            return;
+        };
+        let style = &mut wireframe.style;
+        match input {
+            Input::WireframeThicker => style.thickness = (style.thickness + WIREFRAME_THICKNESS_STEP).min(WIREFRAME_MAX_THICKNESS),
+            Input::WireframeThinner => style.thickness = (style.thickness - WIREFRAME_THICKNESS_STEP).max(1.0),
+            Input::WireframeColor => {
+                let next = WIREFRAME_COLORS.iter().position(|&c| c == style.color).map_or(0, |i| (i + 1) % WIREFRAME_COLORS.len());
+                style.color = WIREFRAME_COLORS[next];
+            }
+            _ => return,
+        }
+        log::info!("Wireframe: thickness {:.1} px, color {:?}", style.thickness, style.color);
+        self.update_wireframe();
+    }
+
+    // Сферы влияния планет, центры совпадают с отрисованными планетами
+    fn update_overlay(&mut self) {
+        let mut triangles = Vec::new();
+        if self.show_soi {
+            for (i, planet) in self.sim.planets.iter().enumerate() {
+                // тело 0 в propagator - Солнце
+                let radius = self.sim.propagator.bodies[i + 1].soi_radius * R as f64;
+                for face in &self.soi_sphere.indices {
+                    triangles.extend(face.iter().map(|&v| PointVertex {
+                        position: self.camera.to_relative(&(planet.geom_obj.center + self.soi_sphere.vertices[v] * radius)),
+                        color: SOI_COLOR,
+                    }));
+                }
+            }
+        }
+        let transform = self.camera.relative_transform(self.screen.get_ratio());
+        if let Some(overlay) = self.gtools.soi_overlay.as_mut() {
+            overlay.set_triangles(&self.resources, &triangles);
+            overlay.set_transform(&self.resources, transform);
+        }
+    }
+
+    // mode: обычный запуск, запись прогона в файл или воспроизведение записанного
+    pub fn new(window: Arc<Window>, resources: Arc<GPU_Resources>, mode: ReplayMode) -> Result<StatePlanets> {
+        let mut screen = Screen::new(window.clone(), resources.clone());
+        screen.set_bg_color(wgpu::Color::BLACK);
+        screen.configure_surface();
+
+
+        let gtools = GraphicsTools::default();
+
+        // при воспроизведении начальное состояние берётся из журнала, а не из текущего конфига
+        let (config_path, config_json, seed, replayer) = match &mode {
+            ReplayMode::Replay(path) => {
+                let log = ReplayLog::load(path)?;
+                (path.clone(), log.config.clone(), log.seed, Some(Replayer::new(log)))
+            }
+            ReplayMode::Live { seed } | ReplayMode::Record { seed, .. } => {
+                (PathBuf::from(CONFIG_PATH), load_config(Path::new(CONFIG_PATH))?, *seed, None)
+            }
+        };
+        let config = parse_config(&config_path, &config_json)?;
+        let mut sim = PlanetsSimulation::new(&config, seed)?;
+        let (planet_textures, ring_textures) = Self::load_textures(&resources, &config, &mut sim.planets)?;
+        let recorder = match mode {
+            ReplayMode::Record { path, .. } => Some(Recorder { path, log: ReplayLog::new(seed, config_json) }),
+            _ => None,
+        };
+        let texture_sampler = resources.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("texture_sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+
+        let mut state = StatePlanets { 
+            screen, 
+            sim,
+            resources: resources.clone(), 
+            gtools,
+            planet_textures,
+            ring_textures,
+            texture_sampler: Some(texture_sampler),
+            camera: Camera::new(Point3::from(CAMERA_EYE), Point3::zeros()),
+            camera_target: CameraTarget::Sun,
+            show_soi: false,
+            soi_sphere: Ball::icosphere(1.0, 2).get_surface_mesh()?,
+            show_wireframe: false,
+            paused: false,
+            speed: 1,
+            recorder,
+            replayer,
+        };
+        state.init()?;
+        Ok(state)
+    }
+
+    // Меши строятся в собственных координатах геометрии.
+    // Текстура колец - радиальная полоса: её u идёт по радиусу (v кольца)
+    fn ring_vertices_indices(geometry: &dyn Geometry) -> Result<(Vec<Vertex>, Vec<u32>)> {
+        let mut mesh = geometry.get_surface_mesh()?;
+        mesh.uvs = mesh.uvs.map(|uvs| uvs.iter().map(|uv| [uv[1], 0.5]).collect());
+        Ok(Self::transform_mesh_to_vertices_indices(mesh, RING_COLOR))
+    }
+
+    pub fn init(&mut self) -> Result<()> {
+        self.gtools.init(self.resources.clone());
+        self.init_meshes()?;
+
+
+        let bind_group_layout = self.resources.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                // Uniform буфер
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::all(),
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<Uniforms>() as u64),
+                    },
+                    count: None,
+                },
+                // Сэмплер
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                // Текстура
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                // Модельная матрица тела
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(size_of::<ModelUniforms>() as u64),
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = self.resources.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        
+        let uniforms = [self.uniforms()];
+        let uniform_buf = self.resources.buffer_fabric.create_buffer_init(
+            &uniforms, 
+            "Uniform Buffer", 
+            wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST
+        );
+        self.gtools.uniform_buf = Some(uniform_buf);
+
+        // по матрице на кольцо каждой планеты
+        let alignment = self.resources.device.limits().min_uniform_buffer_offset_alignment as u64;
+        self.gtools.model_stride = (size_of::<ModelUniforms>() as u64).div_ceil(alignment) * alignment;
+        self.gtools.model_buf = Some(self.resources.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Model uniform buffer"),
+            size: self.gtools.model_stride * self.sim.planets.len().max(1) as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }));
+        self.update_models();
+
+        let vertex_size = size_of::<Vertex>();
+        let vertex_buffers = [wgpu::VertexBufferLayout {
+            array_stride: vertex_size as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: 0,
+                    shader_location: 0,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x3,
+                    offset: (size_of::<[f32; 4]>()*2) as wgpu::BufferAddress,
+                    shader_location: 2,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x2,  // ← UV-координаты!
+                    offset: (size_of::<[f32; 4]>() * 2 + size_of::<[f32; 3]>()) as wgpu::BufferAddress,
+                    shader_location: 3,
+                },
+            ],
+        }];
+
+        self.gtools.set_pipeline_layout(pipeline_layout);
+        self.gtools.set_bind_group_layout(bind_group_layout);
+        self.gtools.ring_bind_groups = self.create_bind_groups(&self.ring_textures, "Ring");
+
+        let ring_shader = self.resources.device.create_shader_module(wgpu::include_wgsl!("../shaders/rings.wgsl"));
+        self.gtools.init_ring_pipeline(ring_shader, &vertex_buffers, &[Some(wgpu::ColorTargetState {
+            format: self.screen.surface.get_format(),
+            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+            write_mask: wgpu::ColorWrites::ALL,
+        })]);
+
+        let point_capacity = self.sim.belt_particles.len()
+            + self.sim.comets.iter().map(|c| c.tail_capacity() + 1).sum::<usize>()
+            + self.sim.spacecraft.len();
+        self.gtools.point_cloud = Some(PointCloud::new(&self.resources, self.screen.surface.get_format(), point_capacity));
+        self.update_points();
+
+        self.gtools.wireframe = Some(Wireframe::new(&self.resources, self.screen.surface.get_format(), WIREFRAME_STYLE));
+        self.update_wireframe();
+
+        let overlay_capacity = self.sim.planets.len() * self.soi_sphere.indices.len() * 3;
+        self.gtools.soi_overlay = Some(TranslucentMesh::new(&self.resources, self.screen.surface.get_format(), overlay_capacity));
+        self.update_overlay();
+        Ok(())
+    }
+
+    fn uniforms(&self) -> Uniforms {
+        let mx_total = self.camera.relative_transform(self.screen.get_ratio());
+        Uniforms {
+            transform: mx_total.to_cols_array_2d(),
+            // Солнце в начале мировых координат
+            light_origin: self.camera.to_relative(&Point3::zeros()),
+            _padding1: 0.0,
+            light_color: [1.0, 1.0, 1.0],
+            _padding2: 0.0,
+            ambient_strength: 1.0,
+            _padding3: [0.0; 3],
+        }
+    }
+
+    fn update_uniforms(&self) {
+        if let Some(uniform_buf) = &self.gtools.uniform_buf {
+            self.resources.queue.write_buffer(uniform_buf, 0, bytemuck::cast_slice(&[self.uniforms()]));
+        }
+    }
+
+    fn create_bind_groups(&self, textures: &[wgpu::TextureView], label: &str) -> Vec<wgpu::BindGroup> {
+        let bind_group_layout = self.gtools.bind_group_layout.as_ref().unwrap();
+        let mut bind_groups = Vec::new();
+        
+        for (i, texture_view) in textures.iter().enumerate() {
+            let bind_group = self.resources.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: self.gtools.uniform_buf.as_ref().unwrap().as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(
+                            self.texture_sampler.as_ref().unwrap()
+                        ),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::TextureView(texture_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                            buffer: self.gtools.model_buf.as_ref().unwrap(),
+                            offset: 0,
+                            size: wgpu::BufferSize::new(size_of::<ModelUniforms>() as u64),
+                        }),
+                    },
+                ],
+                label: Some(&format!("{} {} bind group", label, i)),
+            });
+            bind_groups.push(bind_group);
+        }
+        
+        bind_groups
+    }
+
+    // За кадр выполняется speed шагов, на паузе время стоит
+    pub fn update(&mut self) {
+        let mut stepped = false;
+        for _ in 0..self.speed {
+            self.apply_replayed_inputs();
+            // прогон воспроизведён, время останавливается
+            let finished = self.replayer.as_ref().is_some_and(|r| r.is_finished(self.sim.steps));
+            if self.paused || finished {
+                break;
+            }
+            self.sim.step();
+            stepped = true;
+        }
+        if stepped {
+            self.update_view();
+        }
+    }
+
+    // при воспроизведении записанные действия применяются перед тем же шагом, что и при записи
+    fn apply_replayed_inputs(&mut self) {
+        let Some(replayer) = self.replayer.as_mut() else {
+            return;
+        };
+        for input in replayer.inputs_at(self.sim.steps) {
+            self.apply_input(input);
+        }
+    }
+
+    fn target_position(&self) -> Point3 {
+        match self.camera_target {
+            CameraTarget::Sun => Point3::zeros(),
+            CameraTarget::Planet(i) => self.sim.planets[i].geom_obj.center,
+            CameraTarget::Spacecraft(i) => {
+                self.sim.propagator.state_of(&self.sim.spacecraft[i], self.sim.time * YEARS_PER_STEP).0 * R as f64
+            }
+        }
+    }
+
+    // Солнце, затем планеты, затем аппараты, и снова Солнце
+    fn next_camera_target(&mut self) {
+        let planets = self.sim.planets.len();
+        let spacecraft = self.sim.spacecraft.len();
+        self.camera_target = match self.camera_target {
+            CameraTarget::Sun if planets > 0 => CameraTarget::Planet(0),
+            CameraTarget::Planet(i) if i + 1 < planets => CameraTarget::Planet(i + 1),
+            CameraTarget::Sun | CameraTarget::Planet(_) if spacecraft > 0 => CameraTarget::Spacecraft(0),
+            CameraTarget::Spacecraft(i) if i + 1 < spacecraft => CameraTarget::Spacecraft(i + 1),
+            _ => CameraTarget::Sun,
+        };
+        let (name, distance) = match self.camera_target {
+            CameraTarget::Sun => ("Sun", Point3::from(CAMERA_EYE).magnitude()),
+            CameraTarget::Planet(i) => {
+                let planet = &self.sim.planets[i];
+                (planet.name.as_str(), planet.geom_obj.scale * TARGET_DISTANCE_RADII)
+            }
+            CameraTarget::Spacecraft(i) => (self.sim.spacecraft[i].name.as_str(), SPACECRAFT_TARGET_DISTANCE),
+        };
+        log::info!("Camera target: {}", name);
+        let position = self.target_position();
+        self.camera.retarget(position, distance);
+    }
+
+    fn update_view(&mut self) {
+        // камера движется вместе с выбранным телом
+        self.camera.follow(self.target_position());
+        self.update_uniforms();
+        self.update_points();
+        self.update_wireframe();
+        self.update_overlay();
+        self.update_models();
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) {
+        let Some(input) = Input::from_key(key) else {
+            return;
+        };
+        // во время воспроизведения симуляцией управляет журнал, с клавиатуры меняется только вид
+        if self.replayer.is_some() && !input.is_view() {
+            return;
+        }
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.log.record(self.sim.steps, input);
+        }
+        self.apply_input(input);
+    }
+
+    fn apply_input(&mut self, input: Input) {
+        match input {
+            Input::Pause => {
+                self.paused = !self.paused;
+                log::info!("{}", if self.paused { "Paused" } else { "Resumed" });
+            }
+            Input::Faster => {
+                self.speed = (self.speed * 2).min(MAX_SPEED);
+                log::info!("{} steps per frame", self.speed);
+            }
+            Input::Slower => {
+                self.speed = (self.speed / 2).max(1);
+                log::info!("{} steps per frame", self.speed);
+            }
+            // показать / скрыть сферы влияния
+            Input::ToggleSoi => {
+                self.show_soi = !self.show_soi;
+                if self.show_soi {
+                    for body in self.sim.propagator.bodies.iter().skip(1) {
+                        log::info!("{}: Hill sphere {:.5} AU, SOI {:.5} AU", body.name, body.hill_radius, body.soi_radius);
+                    }
+                }
+                self.update_overlay();
+            }
+            // показать / скрыть каркас планет
+            Input::ToggleWireframe => {
+                self.show_wireframe = !self.show_wireframe;
+                self.update_wireframe();
+            }
+            Input::WireframeThicker | Input::WireframeThinner | Input::WireframeColor => self.change_wireframe_style(input),
+            // приближение и отдаление камеры
+            Input::ZoomIn => {
+                self.camera.zoom(ZOOM_STEP);
+                self.update_view();
+            }
+            Input::ZoomOut => {
+                self.camera.zoom(1.0 / ZOOM_STEP);
+                self.update_view();
+            }
+            Input::NextTarget => {
+                self.next_camera_target();
+                self.update_view();
+            }
+            _ => {
+                self.sim.apply(input);
+                self.update_view();
+            }
+        }
+    }
+
+    // Записанный прогон сохраняется при закрытии окна
+    pub fn close(&mut self) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.log.steps = self.sim.steps;
+            if let Err(e) = recorder.log.save(&recorder.path) {
+                log::error!("Failed to save the replay: {}", e);
+            }
+        }
+    }
+
+    // Каждая уникальная геометрия строится и загружается на GPU один раз.
+    // Планеты с общей геометрией рисуются экземплярами одного InstancedMeshes,
+    // их каркас - экземплярами одного WireframeMesh
+    fn init_meshes(&mut self) -> Result<()> {
+        let resources = &self.resources;
+        let color_format = self.screen.surface.get_format();
+        let planet_textures = &self.planet_textures;
+        let gtools = &mut self.gtools;
+        gtools.meshes.clear();
+        gtools.planet_batches.clear();
+        gtools.wireframe_meshes.clear();
+        for planet in &self.sim.planets {
+            gtools.planet_batches.try_get_or_insert_with(&planet.geom_obj.geometry, |geometry| {
+                Ok::<_, error::Error>(InstancedMeshes::new(resources, color_format, &geometry.get_surface_mesh()?, planet_textures, 1))
+            })?;
+            gtools.wireframe_meshes.try_get_or_insert_with(&planet.geom_obj.geometry, |geometry| {
+                Ok::<_, error::Error>(WireframeMesh::new(resources, &geometry.get_wireframe()?))
+            })?;
+            if let Some(ring) = &planet.ring {
+                gtools.meshes.try_get_or_insert_with(&ring.geom_obj.geometry, |geometry| {
+                    let (vertices, indices) = Self::ring_vertices_indices(geometry)?;
+                    Ok::<_, error::Error>(GpuMesh::new(resources, &vertices, &indices, Some("Ring mesh")))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    // Каждый кадр обновляются только модельные матрицы: экземпляры планет и по слоту на кольцо
+    fn update_models(&mut self) {
+        let camera_eye = self.camera.eye;
+        let transform = self.camera.relative_transform(self.screen.get_ratio());
+        let light_origin = self.camera.to_relative(&Point3::zeros());
+
+        let batches = &mut self.gtools.planet_batches;
+        let mut instances: Vec<Vec<Instance>> = vec![Vec::new(); batches.len()];
+        for planet in &self.sim.planets {
+            if let Some(batch) = batches.index_of(&planet.geom_obj.geometry) {
+                let model = planet.geom_obj.model_matrix(&camera_eye);
+                instances[batch].push(Instance::new(model, planet.texture as u32, [1.0; 4]));
+            }
+        }
+        for (batch, instances) in instances.iter().enumerate() {
+            let batch = batches.get_mut(batch);
+            batch.set_instances(&self.resources, instances);
+            batch.set_transform(&self.resources, transform, light_origin);
+        }
+
+        // полупрозрачные кольца рисуются от дальних к ближним; кольцо планеты i - в слоте i
+        let stride = self.gtools.model_stride;
+        let meshes = &self.gtools.meshes;
+        let mut rings: Vec<(usize, &PlanetRing)> = self.sim.planets.iter().enumerate()
+            .filter_map(|(i, p)| p.ring.as_ref().map(|ring| (i, ring)))
+            .collect();
+        let distance = |ring: &PlanetRing| (ring.geom_obj.center - camera_eye).magnitude();
+        rings.sort_by(|a, b| distance(b.1).total_cmp(&distance(a.1)));
+        let rings = rings.into_iter()
+            .filter_map(|(i, ring)| {
+                let entity = Entity {
+                    mesh: meshes.index_of(&ring.geom_obj.geometry)?,
+                    mx_world: ring.geom_obj.model_matrix(&camera_eye),
+                    uniform_offset: (i as u64 * stride) as wgpu::DynamicOffset,
+                };
+                Some((entity, ring.texture))
+            })
+            .collect();
+        self.gtools.rings = rings;
+
+        if let Some(model_buf) = &self.gtools.model_buf {
+            let mut data = vec![0u8; (stride as usize) * self.sim.planets.len().max(1)];
+            for (entity, _) in &self.gtools.rings {
+                let offset = entity.uniform_offset as usize;
+                let model = ModelUniforms { model: entity.mx_world.to_cols_array_2d() };
+                data[offset..offset + size_of::<ModelUniforms>()].copy_from_slice(bytemuck::bytes_of(&model));
+            }
+            self.resources.queue.write_buffer(model_buf, 0, &data);
+        }
+    }
+
+    fn transform_mesh_to_vertices_indices(mesh: Mesh, color: wgpu::Color) -> (Vec<Vertex>, Vec<u32>) {
+        let center = mesh.vertices.iter().fold(Point3::zeros(), |acc, v| acc + v) / mesh.vertices.len() as f64;
+
+        let normals = mesh.normals.clone().unwrap_or_else(|| mesh.smooth_normals());
+
+        let vertices = mesh.vertices.iter().enumerate().map(|(i, ver)| {
+            let normal = normals[i];
+            // без UV меш проецируется на сферу вокруг своего центра
+            let uv = match &mesh.uvs {
+                Some(uvs) => uvs[i],
+                None => sphere_uv(&(ver - center).normalize()),
+            };
+
+            Vertex {
+                _pos: [ver.x as f32, ver.y as f32, ver.z as f32, 1.0],
+                _col: [color.r as f32, color.g as f32, color.b as f32, color.a as f32],
+                _normal: [normal.x as f32, normal.y as f32, normal.z as f32],
+                _uv: [uv[0] as f32, uv[1] as f32],
+            }
+        }).collect();
+
+        let mut indices = Vec::new();
+        for index_triplet in mesh.indices {
+            indices.push(index_triplet[0] as u32);
+            indices.push(index_triplet[1] as u32);
+            indices.push(index_triplet[2] as u32);
+        }
+
+        (vertices, indices)
+    }
+
+    pub fn render(&mut self) -> Result<()> {
+        self.gtools.render(&self.screen)
+    }
+}
+
+impl GraphicsTools {
+    pub fn init(&mut self, resources: Arc<GPU_Resources>) {
+        self.resources = Some(resources.clone());
+    }
+
+    pub fn set_pipeline_layout(&mut self, pipeline_layout: wgpu::PipelineLayout) {
+        self.pipeline_layout = Some(pipeline_layout);
+    }
+    pub fn set_bind_group_layout(&mut self, bind_group_layout: wgpu::BindGroupLayout) {
+        self.bind_group_layout = Some(bind_group_layout);
+    }
+    // Полупрозрачные поверхности проверяют глубину, но не пишут её: закрытое планетой
+    // отсекается, а лежащее за ними остаётся видно
+    pub fn init_ring_pipeline(&mut self,
+                              shader: wgpu::ShaderModule,
+                              vertex_buffers: &[wgpu::VertexBufferLayout],
+                              fragment_target: &[Option<wgpu::ColorTargetState>])
+    {
+        self.ring_pipeline = Some(self.create_pipeline(shader, vertex_buffers, fragment_target, false));
+    }
+
+    fn create_pipeline(&self,
+                       shader: wgpu::ShaderModule,
+                       vertex_buffers: &[wgpu::VertexBufferLayout],
+                       fragment_target: &[Option<wgpu::ColorTargetState>],
+                       depth_write_enabled: bool) -> wgpu::RenderPipeline
+    {
+        self.resources.as_ref().unwrap().device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor 
+        {
+                label: None,
+                layout: Some(self.pipeline_layout.as_ref().unwrap()),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    compilation_options: Default::default(),
+                    buffers: vertex_buffers,
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_main"),
+                    compilation_options: Default::default(),
+                    targets: fragment_target,
+                }),
+                primitive: wgpu::PrimitiveState {
+                    cull_mode: None, // Some(wgpu::Face::Back),
+                    ..Default::default()
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: wgpu::TextureFormat::Depth32Float,
+                    depth_write_enabled,
+                    // reverse-Z: ближе к камере = больше глубина
+                    depth_compare: wgpu::CompareFunction::Greater,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                // depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+        })
+    }
+
+    pub fn render(&self, screen: &Screen) -> Result<()> {
+        let mut encoder = self.resources.as_ref().unwrap().create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let Some(surface_texture) = screen.acquire_texture()? else {
+            return Ok(());
+        };
+        let texture_view = surface_texture
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor {
+                format: Some(screen.surface.get_format().add_srgb_suffix()),
+                // format: Some(wgpu::TextureFormat::Depth32Float),
+                ..Default::default()
+            });
+
+        let depth_texture = self.resources.clone().unwrap().device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("depth_texture"),
+            size: wgpu::Extent3d {
+                width: screen.get_width(),
+                height: screen.get_height(),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Depth32Float,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &texture_view,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(screen.get_bg_color()),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(0.0), // reverse-Z: дальняя плоскость имеет глубину 0
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            rpass.push_debug_group("Prepare data for draw.");
+            for batch in self.planet_batches.iter() {
+                batch.draw(&mut rpass);
+            }
+
+            if let Some(wireframe) = &self.wireframe {
+                wireframe.draw(&mut rpass, self.wireframe_meshes.iter());
+            }
+            if let Some(point_cloud) = &self.point_cloud {
+                point_cloud.draw(&mut rpass);
+            }
+            // полупрозрачные объекты последними
+            if let Some(ring_pipeline) = &self.ring_pipeline {
+                rpass.set_pipeline(ring_pipeline);
+                for (entity, texture) in &self.rings {
+                    if let Some(bind_group) = self.ring_bind_groups.get(*texture) {
+                        rpass.set_bind_group(0, bind_group, &[entity.uniform_offset]);
+                        self.meshes.get(entity.mesh).draw(&mut rpass);
+                    }
+                }
+            }
+            if let Some(overlay) = &self.soi_overlay {
+                overlay.draw(&mut rpass);
+            }
+
+            rpass.pop_debug_group();
+            rpass.insert_debug_marker("Draw!");
+        }
+
+        self.resources
+            .as_ref().unwrap()
+            .submit_to_queue(Some(encoder.finish()));
+        screen.window.pre_present_notify();
+        surface_texture.present();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // нет файла - Io, файл не декодируется - Parse; в обоих случаях с путём
+    #[test]
+    fn texture_errors_carry_the_path() {
+        let missing = std::env::temp_dir().join(format!("planets_simulator_{}_missing.png", std::process::id()));
+        assert!(matches!(load_image(&missing), Err(error::Error::Io { path, .. }) if path == missing));
+
+        let broken = std::env::temp_dir().join(format!("planets_simulator_{}_broken.png", std::process::id()));
+        std::fs::write(&broken, b"\x89PNG\r\n\x1a\nnot really").unwrap();
+        let result = load_image(&broken);
+        std::fs::remove_file(&broken).unwrap();
+        assert!(matches!(result, Err(error::Error::Parse { path, line: None, .. }) if path == broken));
+    }
+}
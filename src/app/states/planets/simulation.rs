@@ -0,0 +1,797 @@
+use std::sync::Arc;
+use rayon::prelude::*;
+use std::f64::consts::PI;
+
+use crate::error::{self, Result};
+use crate::app::graphics::graphycs_geometry::GraphicsGeometry;
+use crate::physics::geometry::{Geometry, PARALLEL_MIN_LEN, Point3};
+use crate::physics::ball::Ball;
+use crate::physics::annulus::Annulus;
+use crate::physics::model::Model;
+use crate::physics::noise::FractalNoise;
+use crate::physics::terrain::{CraterField, DisplacedSphere, Heightmap, Relief};
+use crate::physics::coords::{Coord, Ellipsoid, Frame, hour_angle};
+use crate::app::graphics::planet::{ParentOrbit, Planet, PlanetRing, orbit_position};
+use crate::physics::events::{AstroEvent, EventBody, EventFinder, EventLog};
+use crate::physics::belt::{BeltConfig, SmallBody, generate_belt};
+use crate::physics::gravity::{Attractor, TestParticle, step_test_particles};
+use crate::physics::orbit::{EARTH_MASS_SUN, MU_SUN, OrbitalElements};
+use crate::physics::comet::Comet;
+use crate::physics::random::derive_seed;
+use crate::physics::resonance::ResonanceAnalyser;
+use crate::physics::soi::{ConicBody, PatchedConicPropagator, Spacecraft, hill_radius, laplace_radius};
+use crate::app::states::replay::Input;
+use std::path::Path;
+
+use super::config::{JsonConfig, JsonObserver, JsonTerrain, parse_config};
+
+const ORIGIN_POS: [f32; 3] = [0.0, 0.0, 0.0];
+const BASE_ANGLE_SPEED: f32 = PI as f32 / 40.0;
+pub(super) const R: f32 = 100.0;
+const PLANET_RADIUS: f64 = 2.0;
+// разрешение UV-сферы планет по долготе и широте
+const PLANET_SEGMENTS: usize = 64;
+const PLANET_RINGS: usize = 32;
+// радиус Солнца в масштабе орбит (0.00465 а.е.)
+const SUN_RADIUS: f64 = 0.00465 * R as f64;
+const EARTH_RADIUS_AU: f64 = 6371.0 / KM_PER_AU;
+// сближения тел дальше 0.1 а.е. не попадают в журнал событий
+const ENCOUNTER_DISTANCE: f64 = 0.1 * R as f64;
+// на сколько шагов вперёд ищутся события по клавише F
+const EVENT_LOOKAHEAD: f64 = 400.0;
+// сколько строк списка событий выводится по клавише L до и после выбранной
+const EVENT_LIST_RADIUS: usize = 10;
+// один шаг симуляции в годах: Земля проходит BASE_ANGLE_SPEED за шаг
+pub(super) const YEARS_PER_STEP: f64 = BASE_ANGLE_SPEED as f64 / (2.0 * PI);
+const BELT_SUBSTEPS: usize = 2;
+const BELT_SOFTENING: f64 = 1e-3;
+// шаг численного дифференцирования орбит планет (в шагах симуляции)
+const VELOCITY_STEP: f64 = 1e-3;
+// шаг поиска пересечений сфер влияния в годах
+const CONIC_STEP: f64 = YEARS_PER_STEP / 8.0;
+const KM_PER_AU: f64 = 1.495978707e8;
+const HOURS_PER_YEAR: f64 = 365.25 * 24.0;
+
+// Состояние симуляции без графики: его можно прогнать без окна, см. replay::replay_headless
+pub struct PlanetsSimulation {
+    // seed прогона, из него и seed конфига выводятся seed поясов и комет
+    pub seed: u64,
+    pub planets: Vec<Planet>,
+
+    // время симуляции в шагах; steps - число выполненных шагов, прыжки по времени его не меняют
+    pub time: f64,
+    pub steps: u64,
+    pub event_finder: EventFinder,
+    pub event_log: EventLog,
+
+    // малые тела поясов: безмассовые частицы в а.е. и а.е./год
+    pub belt_bodies: Vec<SmallBody>,
+    pub belt_colors: Vec<[f32; 4]>,
+    pub belt_particles: Vec<TestParticle>,
+    // именованные астероиды: имя и индекс частицы в belt_particles
+    pub asteroids: Vec<(String, usize)>,
+    pub comets: Arc<Vec<Comet>>,
+    pub resonances: Vec<ResonanceAnalyser>,
+
+    // аппараты в методе сопряжённых конических сечений; начальные состояния нужны для прыжков во времени
+    pub propagator: PatchedConicPropagator,
+    pub spacecraft: Vec<Spacecraft>,
+    pub spacecraft_start: Vec<Spacecraft>,
+
+    // наземный наблюдатель и индекс планеты, на которой он стоит
+    observer: Option<(JsonObserver, usize)>,
+}
+
+impl PlanetsSimulation {
+    pub(super) fn new(config: &JsonConfig, seed: u64) -> Result<Self> {
+        let planets = Self::load_planets(config)?;
+        let (mut belt_bodies, mut belt_colors) = Self::load_belts(config, seed)?;
+        let asteroids = Self::load_asteroids(config, &mut belt_bodies, &mut belt_colors);
+        let belt_particles = Self::belt_particles_at(&belt_bodies, 0.0);
+        let comets = Arc::new(Self::load_comets(config, seed)?);
+        let event_finder = Self::create_event_finder(&planets, &comets);
+        let resonances = Self::load_resonances(config)?;
+        let propagator = Self::create_propagator(&planets);
+        let spacecraft = Self::load_spacecraft(config, &propagator)?;
+        let observer = Self::load_observer(config, &planets)?;
+        let mut sim = PlanetsSimulation {
+            seed,
+            planets,
+            time: 0.0,
+            steps: 0,
+            event_finder,
+            event_log: EventLog::default(),
+            belt_bodies,
+            belt_colors,
+            belt_particles,
+            asteroids,
+            comets,
+            resonances,
+            propagator,
+            spacecraft_start: spacecraft.clone(),
+            spacecraft,
+            observer,
+        };
+        sim.set_centers();
+        Ok(sim)
+    }
+
+    // config - дерево JSON конфига; path нужен только для сообщений об ошибках
+    pub fn from_json(path: &Path, config: &serde_json::Value, seed: u64) -> Result<Self> {
+        Self::new(&parse_config(path, config)?, seed)
+    }
+
+    fn load_planets(config: &JsonConfig) -> Result<Vec<Planet>> {
+        let mut planets = Vec::new();
+        // все планеты без модели - одна единичная сфера, размер и положение задаёт модельная матрица
+        let sphere: Arc<dyn Geometry> = Arc::new(Ball::uv_sphere(1.0, PLANET_SEGMENTS, PLANET_RINGS));
+        for (i, json_planet) in config.planets.iter().enumerate() {
+            let mut scale = json_planet.radius_re;
+            if json_planet.is_giant {
+                scale = scale.sqrt();
+            }
+            scale *=  PLANET_RADIUS;
+            let center = Point3::new((ORIGIN_POS[0] + (R*json_planet.r_au as f32)) as f64, ORIGIN_POS[1] as f64, ORIGIN_POS[2] as f64);
+            let tilt = (json_planet.axial_tilt_deg.to_radians(), 0.0, 0.0);
+            let planet = match (&json_planet.model, &json_planet.terrain) {
+                (Some(_), Some(_)) => {
+                    return Err(error::Error::Config {
+                        entry: format!("planet {}", json_planet.name),
+                        reason: "model and terrain cannot be used together".to_string(),
+                    });
+                }
+                (None, Some(json_terrain)) => GraphicsGeometry::new(
+                    Box::new(Self::load_terrain(&json_planet.name, json_terrain)?),
+                    tilt,
+                    scale,
+                    center
+                ),
+                (model, None) => {
+                    let geometry: Arc<dyn Geometry> = match model {
+                        Some(json_model) => Arc::new(Model::load(&json_model.path, json_model.scale)?),
+                        None => sphere.clone(),
+                    };
+                    GraphicsGeometry::shared(
+                        geometry,
+                        tilt,
+                        scale, 
+                        center
+                    )
+                }
+            };
+            // индекс текстуры колец назначает StatePlanets::load_textures
+            let ring = json_planet.ring.as_ref().map(|json_ring| {
+                let annulus = Annulus::new(json_ring.inner_radius, json_ring.outer_radius);
+                PlanetRing {
+                    geom_obj: GraphicsGeometry::new(Box::new(annulus), tilt, scale, center),
+                    texture: 0,
+                }
+            });
+            // родитель должен стоять в конфиге раньше спутника
+            let parent = match &json_planet.parent {
+                Some(name) => {
+                    let index = planets.iter().position(|p: &Planet| &p.name == name).ok_or_else(|| error::Error::Config {
+                        entry: format!("planet {}", json_planet.name),
+                        reason: format!("unknown parent {}", name),
+                    })?;
+                    Some(ParentOrbit {
+                        index,
+                        orbit_start: planets[index].orbit_start.clone(),
+                        angle_speed: planets[index].angle_speed,
+                    })
+                }
+                None => None,
+            };
+            let mut planet = Planet { 
+                name: json_planet.name.clone(),
+                geom_obj: planet, 
+                texture: i as i32,
+                angle_speed: BASE_ANGLE_SPEED * 365.0 / json_planet.year_dur_re as f32,
+                orbit_start: Coord::from(center),
+                mass_me: json_planet.mass_me,
+                radius_re: json_planet.radius_re,
+                ring,
+                parent,
+            };
+            if json_planet.move_direction == "ccw" {
+                planet.angle_speed *= -1.0;
+            }
+            planets.push(planet);
+        }
+        Ok(planets)
+    }
+
+    // Рельеф единичной сферы: слои карты высот, шума и кратеров с равными весами
+    fn load_terrain(name: &str, json_terrain: &JsonTerrain) -> Result<DisplacedSphere> {
+        let mut layers = Vec::new();
+        if let Some(path) = &json_terrain.heightmap {
+            layers.push((1.0, Relief::Heightmap(Heightmap::load(path)?)));
+        }
+        if let Some(json_noise) = &json_terrain.noise {
+            let mut noise = FractalNoise::new(json_noise.seed);
+            noise.octaves = json_noise.octaves.unwrap_or(noise.octaves);
+            noise.frequency = json_noise.frequency.unwrap_or(noise.frequency);
+            noise.lacunarity = json_noise.lacunarity.unwrap_or(noise.lacunarity);
+            noise.gain = json_noise.gain.unwrap_or(noise.gain);
+            layers.push((1.0, if json_noise.ridged { Relief::Ridged(noise) } else { Relief::Fbm(noise) }));
+        }
+        if let Some(json_craters) = &json_terrain.craters {
+            let field = CraterField::generate(
+                json_craters.seed,
+                json_craters.count,
+                json_craters.min_radius_deg.to_radians(),
+                json_craters.max_radius_deg.to_radians(),
+                json_craters.size_index,
+            );
+            layers.push((1.0, Relief::Craters(field)));
+        }
+        let relief = match layers.len() {
+            0 => {
+                return Err(error::Error::Config {
+                    entry: format!("planet {}", name),
+                    reason: "terrain needs a heightmap, noise or craters".to_string(),
+                });
+            }
+            1 => layers.pop().unwrap().1,
+            _ => Relief::Layers(layers),
+        };
+        DisplacedSphere::new(1.0, json_terrain.amplitude * json_terrain.exaggeration, &relief)
+    }
+
+    // Параметры поясов проверяет generate_belt: ошибка в конфиге не даёт загрузить сцену
+    fn load_belts(config: &JsonConfig, seed: u64) -> Result<(Vec<SmallBody>, Vec<[f32; 4]>)> {
+        let mut bodies = Vec::new();
+        let mut colors = Vec::new();
+        for json_belt in &config.belts {
+            let belt = generate_belt(&BeltConfig {
+                count: json_belt.count,
+                inner_radius: json_belt.inner_au,
+                outer_radius: json_belt.outer_au,
+                eccentricity_sigma: json_belt.eccentricity_sigma,
+                max_eccentricity: json_belt.max_eccentricity,
+                inclination_sigma: json_belt.inclination_sigma_deg.to_radians(),
+                min_size: json_belt.min_size_km,
+                max_size: json_belt.max_size_km,
+                size_index: json_belt.size_index,
+                seed: derive_seed(seed, json_belt.seed),
+            })?;
+            log::debug!("{}: {} bodies", json_belt.name, belt.len());
+
+            // крупные тела ярче мелких
+            let log_range = (json_belt.max_size_km / json_belt.min_size_km).ln().max(f64::EPSILON);
+            for body in belt {
+                let brightness = 0.4 + 0.6 * ((body.size / json_belt.min_size_km).ln() / log_range) as f32;
+                let c = json_belt.color;
+                colors.push([c[0] * brightness, c[1] * brightness, c[2] * brightness, c[3]]);
+                bodies.push(body);
+            }
+        }
+        Ok((bodies, colors))
+    }
+
+    // Астероиды добавляются в конец пояса: их интегрирует тот же шаг, что и частицы поясов
+    fn load_asteroids(config: &JsonConfig, bodies: &mut Vec<SmallBody>, colors: &mut Vec<[f32; 4]>) -> Vec<(String, usize)> {
+        config.asteroids.iter().map(|json_asteroid| {
+            bodies.push(SmallBody {
+                elements: OrbitalElements {
+                    semi_major_axis: json_asteroid.semi_major_axis_au,
+                    eccentricity: json_asteroid.eccentricity,
+                    inclination: json_asteroid.inclination_deg.to_radians(),
+                    ascending_node: json_asteroid.ascending_node_deg.to_radians(),
+                    arg_periapsis: json_asteroid.arg_perihelion_deg.to_radians(),
+                    mean_anomaly: json_asteroid.mean_anomaly_deg.to_radians(),
+                },
+                size: json_asteroid.size_km,
+            });
+            colors.push(json_asteroid.color);
+            (json_asteroid.name.clone(), bodies.len() - 1)
+        }).collect()
+    }
+
+    fn load_comets(config: &JsonConfig, seed: u64) -> Result<Vec<Comet>> {
+        config.comets.iter().map(|json_comet| {
+            // параболические орбиты (e = 1) не поддерживаются уравнением Кеплера
+            if json_comet.eccentricity == 1.0 {
+                return Err(error::Error::Config {
+                    entry: format!("comet {}", json_comet.name),
+                    reason: "parabolic orbits are not supported".to_string(),
+                });
+            }
+            let elements = OrbitalElements::from_perihelion(
+                json_comet.perihelion_au,
+                json_comet.eccentricity,
+                json_comet.inclination_deg.to_radians(),
+                json_comet.ascending_node_deg.to_radians(),
+                json_comet.arg_perihelion_deg.to_radians(),
+                json_comet.perihelion_time_years,
+                MU_SUN,
+            );
+            Ok(Comet::new(
+                json_comet.name.clone(),
+                elements,
+                json_comet.ion_tail_au,
+                json_comet.tail_particles,
+                derive_seed(seed, json_comet.seed),
+            ))
+        }).collect()
+    }
+
+    fn load_resonances(config: &JsonConfig) -> Result<Vec<ResonanceAnalyser>> {
+        config.resonances.iter().map(|json_res| {
+            ResonanceAnalyser::new(
+                json_res.name.clone(),
+                json_res.bodies.clone(),
+                json_res.lambda.clone(),
+                json_res.pericentre.clone(),
+            )
+        }).collect()
+    }
+
+    // Положение (а.е.) и скорость (а.е./год) планеты на круговой орбите в момент t (в годах)
+    fn planet_state(start: &Coord, angle_speed: f32, t: f64) -> (Point3, Point3) {
+        let scale = R as f64;
+        let time = t / YEARS_PER_STEP;
+        let position = orbit_position(start, angle_speed, time) / scale;
+        let velocity = (orbit_position(start, angle_speed, time + VELOCITY_STEP) - orbit_position(start, angle_speed, time - VELOCITY_STEP))
+            / (2.0 * VELOCITY_STEP * YEARS_PER_STEP * scale);
+        (position, velocity)
+    }
+
+    // Положение и скорость планеты в системе Солнца: спутник движется вместе со своей планетой
+    fn heliocentric_state(planet: &Planet) -> impl Fn(f64) -> (Point3, Point3) + Send + Sync + 'static {
+        let start = planet.orbit_start.clone();
+        let angle_speed = planet.angle_speed;
+        let parent = planet.parent.clone();
+        move |t| {
+            let (position, velocity) = Self::planet_state(&start, angle_speed, t);
+            match &parent {
+                Some(parent) => {
+                    let (parent_position, parent_velocity) = Self::planet_state(&parent.orbit_start, parent.angle_speed, t);
+                    (position + parent_position, velocity + parent_velocity)
+                }
+                None => (position, velocity),
+            }
+        }
+    }
+
+    // Оскулирующие элементы планеты, астероида или кометы в момент time (в шагах), в а.е. и годах.
+    // Астероид берётся в текущем состоянии интегрирования, time для него не используется
+    fn body_elements(&self, name: &str, time: f64) -> Option<OrbitalElements> {
+        if let Some(planet) = self.planets.iter().find(|p| p.name == name) {
+            let (position, velocity) = Self::heliocentric_state(planet)(time * YEARS_PER_STEP);
+            return Some(OrbitalElements::from_state(&position, &velocity, MU_SUN));
+        }
+        if let Some(&(_, i)) = self.asteroids.iter().find(|(asteroid, _)| asteroid == name) {
+            let particle = &self.belt_particles[i];
+            return Some(OrbitalElements::from_state(&particle.position, &particle.velocity, MU_SUN));
+        }
+        self.comets.iter().find(|c| c.name == name).map(|comet| {
+            let (position, velocity) = comet.elements.state_at(MU_SUN, time * YEARS_PER_STEP);
+            OrbitalElements::from_state(&position, &velocity, MU_SUN)
+        })
+    }
+
+    fn sample_resonances(&mut self) {
+        let time = self.time;
+        let mut resonances = std::mem::take(&mut self.resonances);
+        for resonance in &mut resonances {
+            let elements: Option<Vec<OrbitalElements>> = resonance.bodies.iter()
+                .map(|name| self.body_elements(name, time))
+                .collect();
+            if let Some(elements) = elements {
+                resonance.add_sample(time * YEARS_PER_STEP, &elements, MU_SUN);
+            }
+        }
+        self.resonances = resonances;
+    }
+
+    // Солнце и планеты со сферами влияния Лапласа; время в годах, координаты в а.е.
+    fn create_propagator(planets: &[Planet]) -> PatchedConicPropagator {
+        let mut propagator = PatchedConicPropagator::new(CONIC_STEP);
+        let sun = propagator.add_body(ConicBody {
+            name: "Sun".to_string(),
+            mu: MU_SUN,
+            soi_radius: f64::INFINITY,
+            hill_radius: f64::INFINITY,
+            parent: None,
+            state: Box::new(|_| (Point3::zeros(), Point3::zeros())),
+        });
+        for planet in planets {
+            // сфера влияния спутника считается относительно его планеты; тело i в propagator - планета i - 1
+            let semi_major_axis = planet.orbit_start.r() / R as f64;
+            let mass_ratio = EARTH_MASS_SUN * planet.mass_me;
+            let (parent, central_ratio) = match &planet.parent {
+                Some(parent) => (parent.index + 1, planet.mass_me / planets[parent.index].mass_me),
+                None => (sun, mass_ratio),
+            };
+            propagator.add_body(ConicBody {
+                name: planet.name.clone(),
+                mu: MU_SUN * mass_ratio,
+                soi_radius: laplace_radius(semi_major_axis, central_ratio),
+                hill_radius: hill_radius(semi_major_axis, 0.0, central_ratio),
+                parent: Some(parent),
+                state: Box::new(Self::heliocentric_state(planet)),
+            });
+        }
+        propagator
+    }
+
+    fn load_spacecraft(config: &JsonConfig, propagator: &PatchedConicPropagator) -> Result<Vec<Spacecraft>> {
+        config.spacecraft.iter().map(|json_craft| {
+            let central = propagator.bodies.iter().position(|b| b.name == json_craft.central).ok_or_else(|| error::Error::Config {
+                entry: format!("spacecraft {}", json_craft.name),
+                reason: format!("unknown central body {}", json_craft.central),
+            })?;
+            Ok(propagator.spacecraft(
+                json_craft.name.clone(),
+                central,
+                &Point3::from(json_craft.offset_au),
+                &Point3::from(json_craft.velocity_au_yr),
+                0.0,
+            ))
+        }).collect()
+    }
+
+    fn step_spacecraft(&mut self, t_prev: f64) {
+        let mut spacecraft = std::mem::take(&mut self.spacecraft);
+        for craft in &mut spacecraft {
+            match self.propagator.propagate(craft, t_prev * YEARS_PER_STEP, self.time * YEARS_PER_STEP) {
+                Ok(transitions) => transitions.iter().for_each(|transition| log::info!("{}", transition)),
+                Err(e) => log::error!("{}: {}", craft.name, e),
+            }
+        }
+        self.spacecraft = spacecraft;
+    }
+
+    // Частицы на невозмущённых кеплеровых орбитах в момент time (в шагах)
+    fn belt_particles_at(bodies: &[SmallBody], time: f64) -> Vec<TestParticle> {
+        bodies.par_iter().with_min_len(PARALLEL_MIN_LEN).map(|body| {
+            let (position, velocity) = body.elements.state_at(MU_SUN, time * YEARS_PER_STEP);
+            TestParticle { position, velocity }
+        }).collect()
+    }
+
+    // Солнце (центральное тело) и планеты как притягивающие тела; t в годах, координаты в а.е.
+    fn attractors_at(&self, t: f64) -> Vec<Attractor> {
+        let mut attractors = vec![Attractor { position: Point3::zeros(), mu: MU_SUN }];
+        attractors.extend(self.planets.iter().map(|planet| Attractor {
+            position: planet.position_at(t / YEARS_PER_STEP) / R as f64,
+            mu: MU_SUN * EARTH_MASS_SUN * planet.mass_me,
+        }));
+        attractors
+    }
+
+    fn step_belts(&mut self, t_prev: f64) {
+        let dt = YEARS_PER_STEP / BELT_SUBSTEPS as f64;
+        let mut particles = std::mem::take(&mut self.belt_particles);
+        for i in 0..BELT_SUBSTEPS {
+            let t = t_prev * YEARS_PER_STEP + i as f64 * dt;
+            step_test_particles(&mut particles, |t| self.attractors_at(t), t, dt, BELT_SOFTENING);
+        }
+        self.belt_particles = particles;
+    }
+
+    fn load_observer(config: &JsonConfig, planets: &[Planet]) -> Result<Option<(JsonObserver, usize)>> {
+        let Some(observer) = &config.observer else {
+            return Ok(None);
+        };
+        let home = planets.iter().position(|p| p.name == observer.body).ok_or_else(|| error::Error::Config {
+            entry: "observer".to_string(),
+            reason: format!("unknown body {}", observer.body),
+        })?;
+        Ok(Some((observer.clone(), home)))
+    }
+
+    // Экваториальные и горизонтальные координаты Солнца и планет для наземного наблюдателя
+    fn log_sky(&self) {
+        let Some((observer, home)) = &self.observer else {
+            log::warn!("No ground observer in the config");
+            return;
+        };
+        let home = &self.planets[*home];
+        let t = self.time * YEARS_PER_STEP;
+        let scale = R as f64;
+        let obliquity = observer.obliquity_deg.to_radians();
+        let rotation = observer.rotation_at_epoch_deg.to_radians()
+            + 2.0 * PI * t * HOURS_PER_YEAR / observer.sidereal_day_hours;
+        let latitude = observer.latitude_deg.to_radians();
+        let longitude = observer.longitude_deg.to_radians();
+        let local_sidereal_time = rotation + longitude;
+
+        let ellipsoid = Ellipsoid::new(observer.equatorial_radius_km / KM_PER_AU, observer.polar_radius_km / KM_PER_AU);
+        let site = Coord::new_geodetic(&ellipsoid, latitude, longitude, observer.altitude_km / KM_PER_AU);
+        let site = Frame::BodyFixed { obliquity, rotation }.to_ecliptic(&site);
+        let origin = Coord::new_cartesian(0.0, 0.0, 0.0);
+        let observer_position = Coord::from(home.position_at(self.time) / scale) + site;
+
+        let equatorial = Frame::Equatorial { obliquity };
+        let horizontal = Frame::Horizontal { obliquity, latitude, local_sidereal_time };
+        log::info!("Sky from {} (lat {:.2}, lon {:.2}) at t={:.4} yr:", observer.body, observer.latitude_deg, observer.longitude_deg, t);
+        let targets = std::iter::once(("Sun".to_string(), origin))
+            .chain(self.planets.iter().filter(|p| p.name != observer.body).map(|p| {
+                (p.name.clone(), Coord::from(p.position_at(self.time) / scale))
+            }));
+        for (name, position) in targets {
+            let direction = position - observer_position.clone();
+            let (right_ascension, declination) = direction.to_frame(&Frame::Ecliptic, &equatorial).ra_dec();
+            let (altitude, azimuth) = direction.to_frame(&Frame::Ecliptic, &horizontal).alt_az();
+            log::info!(
+                "  {}: RA {:.2} h, Dec {:.2} deg, HA {:.2} h, Alt {:.2} deg, Az {:.2} deg",
+                name,
+                right_ascension.to_degrees() / 15.0,
+                declination.to_degrees(),
+                hour_angle(local_sidereal_time, right_ascension).to_degrees() / 15.0,
+                altitude.to_degrees(),
+                azimuth.to_degrees(),
+            );
+        }
+    }
+
+    fn create_event_finder(planets: &[Planet], comets: &Arc<Vec<Comet>>) -> EventFinder {
+        let observer = planets.iter().position(|p| p.name == "Earth").unwrap_or(0);
+        let mut finder = EventFinder::new(observer, SUN_RADIUS, 1.0);
+        finder.max_encounter_distance = Some(ENCOUNTER_DISTANCE);
+        // тело i в finder - планета i, поэтому индексы родителей совпадают.
+        // Прохождения и затмения считаются по физическим радиусам, а не по размерам на экране
+        for planet in planets {
+            let start = planet.orbit_start.clone();
+            let angle_speed = planet.angle_speed;
+            let parent = planet.parent.clone();
+            finder.add_body(EventBody {
+                name: planet.name.clone(),
+                radius: planet.radius_re * EARTH_RADIUS_AU * R as f64,
+                parent: planet.parent.as_ref().map(|p| p.index),
+                position: Box::new(move |t| {
+                    let position = orbit_position(&start, angle_speed, t);
+                    parent.as_ref().map_or(position, |p| position + p.position_at(t))
+                }),
+            });
+        }
+        for (i, comet) in comets.iter().enumerate() {
+            let comets = comets.clone();
+            finder.add_body(EventBody {
+                name: comet.name.clone(),
+                radius: 0.0,
+                parent: None,
+                position: Box::new(move |t| comets[i].position_at(MU_SUN, t * YEARS_PER_STEP) * R as f64),
+            });
+        }
+        finder
+    }
+
+    pub fn step(&mut self) {
+        let t_prev = self.time;
+        self.time += 1.0;
+        self.steps += 1;
+        for event in self.event_finder.scan(t_prev, self.time) {
+            self.log_event(event);
+        }
+        self.step_belts(t_prev);
+        self.step_spacecraft(t_prev);
+        self.sample_resonances();
+        self.set_centers();
+    }
+
+    // планет единицы, поэтому без rayon
+    fn set_centers(&mut self) {
+        for planet in &mut self.planets {
+            let center = planet.position_at(self.time);
+            planet.set_center(center);
+        }
+    }
+
+    fn log_event(&mut self, event: AstroEvent) {
+        let text = event.to_string();
+        if self.event_log.insert(event) {
+            log::info!("{}", text);
+        }
+    }
+
+    // Строки журнала вокруг выбранного события (или вокруг текущего времени), выбранная отмечена
+    fn log_event_list(&self) {
+        if self.event_log.is_empty() {
+            log::info!("The event log is empty, press F to scan for events");
+            return;
+        }
+        let selected = self.event_log.selected().map(|(i, _)| i);
+        let center = selected.unwrap_or_else(|| self.event_log.iter().take_while(|e| e.time < self.time).count());
+        let first = center.saturating_sub(EVENT_LIST_RADIUS);
+        for (i, event) in self.event_log.iter().enumerate().skip(first).take(2 * EVENT_LIST_RADIUS + 1) {
+            let marker = if Some(i) == selected { '>' } else { ' ' };
+            log::info!("{} {:>5}: {}", marker, i + 1, event);
+        }
+    }
+
+    pub fn jump_to(&mut self, time: f64) {
+        self.time = time;
+        // возмущения от планет при прыжке не интегрируются: пояса возвращаются на кеплеровы орбиты
+        self.belt_particles = Self::belt_particles_at(&self.belt_bodies, time);
+        self.resonances.iter_mut().for_each(|r| r.reset());
+        // конические сечения дают положение аппарата в любой момент: переходы пересчитываются от старта
+        self.spacecraft = self.spacecraft_start.clone();
+        for craft in &mut self.spacecraft {
+            if let Err(e) = self.propagator.propagate(craft, 0.0, time * YEARS_PER_STEP) {
+                log::error!("{}: {}", craft.name, e);
+            }
+        }
+        self.set_centers();
+    }
+
+    // Действия, которые меняют симуляцию или выводят её состояние; остальные относятся к виду
+    pub fn apply(&mut self, input: Input) {
+        match input {
+            Input::ScanEvents => {
+                for event in self.event_finder.scan(self.time, self.time + EVENT_LOOKAHEAD) {
+                    self.log_event(event);
+                }
+            }
+            Input::NextEvent => {
+                if let Some(event) = self.event_log.next_after(self.time) {
+                    log::info!("Jump to {}", event);
+                    self.jump_to(event.time);
+                }
+            }
+            Input::PrevEvent => {
+                if let Some(event) = self.event_log.prev_before(self.time) {
+                    log::info!("Jump to {}", event);
+                    self.jump_to(event.time);
+                }
+            }
+            Input::ListEvents => self.log_event_list(),
+            Input::SelectNextEvent | Input::SelectPrevEvent => {
+                let total = self.event_log.len();
+                let selected = if input == Input::SelectNextEvent {
+                    self.event_log.select_next(self.time)
+                } else {
+                    self.event_log.select_prev(self.time)
+                };
+                if let Some((index, event)) = selected {
+                    log::info!("Selected {}/{}: {}", index + 1, total, event);
+                }
+            }
+            Input::JumpToSelected => {
+                if let Some((_, event)) = self.event_log.selected() {
+                    log::info!("Jump to {}", event);
+                    self.jump_to(event.time);
+                }
+            }
+            Input::ResonanceReport => {
+                for resonance in &self.resonances {
+                    log::info!("{}", resonance.report());
+                }
+            }
+            Input::Sky => self.log_sky(),
+            _ => (),
+        }
+    }
+
+    // Положения планет, частиц поясов и аппаратов в а.е.: по ним сравниваются прогоны
+    pub fn positions(&self) -> Vec<Point3> {
+        let t = self.time * YEARS_PER_STEP;
+        let mut positions: Vec<Point3> = self.planets.iter().map(|p| p.position_at(self.time) / R as f64).collect();
+        positions.extend(self.belt_particles.iter().map(|p| p.position));
+        positions.extend(self.spacecraft.iter().map(|craft| self.propagator.state_of(craft, t).0));
+        positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::states::planets::config::{CONFIG_PATH, load_config};
+    use crate::app::states::replay::DEFAULT_SEED;
+    use crate::physics::events::EventKind;
+
+    fn simulation() -> PlanetsSimulation {
+        let path = Path::new(CONFIG_PATH);
+        PlanetsSimulation::from_json(path, &load_config(path).unwrap(), DEFAULT_SEED).unwrap()
+    }
+
+    #[test]
+    fn invalid_belt_fails_to_load() {
+        let path = Path::new(CONFIG_PATH);
+        let mut config = load_config(path).unwrap();
+        config["belts"][0]["max_eccentricity"] = serde_json::json!(0.0);
+        let result = PlanetsSimulation::from_json(path, &config, DEFAULT_SEED);
+        assert!(matches!(result, Err(error::Error::InvalidParameter { name: "belt max eccentricity", .. })));
+    }
+
+    // записи, ссылающиеся на несуществующие тела, и параболическая комета не дают загрузить сцену
+    #[test]
+    fn invalid_config_entries_fail_to_load() {
+        let path = Path::new(CONFIG_PATH);
+        let cases = [
+            ("/planets/3/parent", serde_json::json!("Terra"), "planet Moon"),
+            ("/comets/0/eccentricity", serde_json::json!(1.0), "comet Halley"),
+            ("/spacecraft/0/central", serde_json::json!("Vulcan"), "spacecraft Mars transfer"),
+            ("/observer/body", serde_json::json!("Vulcan"), "observer"),
+        ];
+        for (pointer, value, expected_entry) in cases {
+            let mut config = load_config(path).unwrap();
+            *config.pointer_mut(pointer).unwrap() = value;
+            match PlanetsSimulation::from_json(path, &config, DEFAULT_SEED) {
+                Err(error::Error::Config { entry, .. }) => assert_eq!(entry, expected_entry),
+                Err(e) => panic!("{}: unexpected error {}", pointer, e),
+                Ok(_) => panic!("{}: invalid config loaded", pointer),
+            }
+        }
+    }
+
+    #[test]
+    fn resonance_with_missing_coefficients_fails_to_load() {
+        let path = Path::new(CONFIG_PATH);
+        let mut config = load_config(path).unwrap();
+        config["resonances"][0]["pericentre"] = serde_json::json!([0]);
+        let result = PlanetsSimulation::from_json(path, &config, DEFAULT_SEED);
+        assert!(matches!(result, Err(error::Error::InvalidParameter { name: "number of pericentre coefficients", .. })));
+    }
+
+    // Луна из конфига обращается вокруг Земли в плоскости эклиптики и стартует в полнолуние:
+    // солнечное затмение через половину синодического месяца, лунное - через целый
+    #[test]
+    fn finds_moon_eclipses() {
+        let sim = simulation();
+        let angle_speed = |name: &str| sim.planets.iter().find(|p| p.name == name).unwrap().angle_speed as f64;
+        let month = 2.0 * PI / (angle_speed("Moon") - angle_speed("Earth"));
+        let events = sim.event_finder.scan(0.5, month + 0.5);
+
+        let find = |kind: EventKind| events.iter().find(|e| e.kind == kind).unwrap_or_else(|| panic!("no {:?}", kind));
+        let solar = find(EventKind::SolarEclipse);
+        assert_eq!(solar.bodies, ("Earth".to_string(), "Moon".to_string()));
+        assert!((solar.time - month / 2.0).abs() < 1e-3);
+        let lunar = find(EventKind::LunarEclipse);
+        assert!((lunar.time - month).abs() < 1e-3);
+    }
+
+    // Тела в поиске событий имеют физические радиусы, а сближения дальше ENCOUNTER_DISTANCE отброшены
+    #[test]
+    fn event_finder_uses_physical_radii_and_near_encounters() {
+        let sim = simulation();
+        let earth = sim.event_finder.bodies.iter().find(|b| b.name == "Earth").unwrap();
+        assert!((earth.radius - EARTH_RADIUS_AU * R as f64).abs() < 1e-12);
+
+        let year = 2.0 * PI / BASE_ANGLE_SPEED as f64;
+        let encounters: Vec<_> = sim.event_finder.scan(0.5, year + 0.5).into_iter()
+            .filter(|e| e.kind == EventKind::ClosestApproach)
+            .collect();
+        assert!(!encounters.is_empty());
+        assert!(encounters.iter().all(|e| e.value <= ENCOUNTER_DISTANCE));
+    }
+
+    // Тело с формой из файла получает свою геометрию, scale переводит единицы файла в радиусы тела
+    #[test]
+    fn loads_a_shape_model_from_the_config() {
+        let model_path = std::env::temp_dir().join(format!("planets_simulator_{}_model.obj", std::process::id()));
+        crate::physics::mesh_io::save_geometry(&Ball::uv_sphere(2.0, 16, 8), &model_path).unwrap();
+
+        let path = Path::new(CONFIG_PATH);
+        let mut config = load_config(path).unwrap();
+        config["planets"][0]["model"] = serde_json::json!({ "path": model_path, "scale": 0.5 });
+        let sim = PlanetsSimulation::from_json(path, &config, DEFAULT_SEED).unwrap();
+        std::fs::remove_file(&model_path).unwrap();
+
+        let (model, sphere) = (&sim.planets[0].geom_obj.geometry, &sim.planets[1].geom_obj.geometry);
+        assert!(!Arc::ptr_eq(model, sphere));
+        assert!((model.bounding_sphere().radius - 1.0).abs() < 1e-9);
+
+        // файла модели больше нет
+        assert!(matches!(PlanetsSimulation::from_json(path, &config, DEFAULT_SEED), Err(error::Error::Io { .. })));
+    }
+
+    // Луна в конфиге с рельефом: своя геометрия, поверхность не лежит на единичной сфере
+    #[test]
+    fn loads_terrain_from_the_config() {
+        let sim = simulation();
+        let geometry = |name: &str| sim.planets.iter().find(|p| p.name == name).unwrap().geom_obj.geometry.clone();
+        let moon = geometry("Moon").get_surface_mesh().unwrap();
+        assert!(!Arc::ptr_eq(&geometry("Moon"), &geometry("Earth")));
+        let (min, max) = moon.vertices.iter().fold((f64::MAX, 0.0_f64), |(min, max), v| (min.min(v.magnitude()), max.max(v.magnitude())));
+        assert!(min < 1.0 && max > 1.0);
+
+        let path = Path::new(CONFIG_PATH);
+        let mut config = load_config(path).unwrap();
+        config["planets"][3]["model"] = serde_json::json!({ "path": "moon.obj", "scale": 1.0 });
+        let result = PlanetsSimulation::from_json(path, &config, DEFAULT_SEED);
+        assert!(matches!(result, Err(error::Error::Config { .. })));
+    }
+}
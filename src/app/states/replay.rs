@@ -0,0 +1,315 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use winit::keyboard::KeyCode;
+
+use crate::app::states::planets::PlanetsSimulation;
+use crate::error::{Error, Result};
+
+// seed прогона без `--seed`
+pub const DEFAULT_SEED: u64 = 0;
+
+// Действие пользователя. Клавиши переводятся в действия до обработки,
+// в журнал попадают действия, а не коды клавиш
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Input {
+    // управление временем: пауза и число шагов за кадр
+    Pause,
+    Faster,
+    Slower,
+    // поиск событий наперёд и прыжки по журналу событий
+    ScanEvents,
+    NextEvent,
+    PrevEvent,
+    // список событий: вывод, выбор строки и прыжок к выбранному событию
+    ListEvents,
+    SelectNextEvent,
+    SelectPrevEvent,
+    JumpToSelected,
+    ResonanceReport,
+    // небо для наземного наблюдателя
+    Sky,
+    ToggleSoi,
+    ToggleWireframe,
+    // толщина и цвет каркаса
+    WireframeThicker,
+    WireframeThinner,
+    WireframeColor,
+    ZoomIn,
+    ZoomOut,
+    // камера переходит к следующему телу: Солнце, планеты, аппараты
+    NextTarget,
+}
+
+impl Input {
+    pub fn from_key(key: KeyCode) -> Option<Self> {
+        match key {
+            KeyCode::Space => Some(Input::Pause),
+            KeyCode::Period => Some(Input::Faster),
+            KeyCode::Comma => Some(Input::Slower),
+            KeyCode::KeyF => Some(Input::ScanEvents),
+            KeyCode::KeyN => Some(Input::NextEvent),
+            KeyCode::KeyB => Some(Input::PrevEvent),
+            KeyCode::KeyL => Some(Input::ListEvents),
+            KeyCode::ArrowDown => Some(Input::SelectNextEvent),
+            KeyCode::ArrowUp => Some(Input::SelectPrevEvent),
+            KeyCode::Enter => Some(Input::JumpToSelected),
+            KeyCode::KeyR => Some(Input::ResonanceReport),
+            KeyCode::KeyG => Some(Input::Sky),
+            KeyCode::KeyO => Some(Input::ToggleSoi),
+            KeyCode::KeyW => Some(Input::ToggleWireframe),
+            KeyCode::BracketRight => Some(Input::WireframeThicker),
+            KeyCode::BracketLeft => Some(Input::WireframeThinner),
+            KeyCode::KeyC => Some(Input::WireframeColor),
+            KeyCode::Equal => Some(Input::ZoomIn),
+            KeyCode::Minus => Some(Input::ZoomOut),
+            KeyCode::KeyT => Some(Input::NextTarget),
+            _ => None,
+        }
+    }
+
+    // действие меняет только изображение или темп показа, но не состояние симуляции
+    // после данного числа шагов и не её вывод
+    pub fn is_view(&self) -> bool {
+        matches!(
+            self,
+            Input::Pause
+                | Input::Faster
+                | Input::Slower
+                | Input::ToggleSoi
+                | Input::ToggleWireframe
+                | Input::WireframeThicker
+                | Input::WireframeThinner
+                | Input::WireframeColor
+                | Input::ZoomIn
+                | Input::ZoomOut
+                | Input::NextTarget
+        )
+    }
+}
+
+// Действие и число шагов, выполненных до него
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RecordedInput {
+    pub step: u64,
+    pub input: Input,
+}
+
+// Журнал прогона: seed прогона, начальное состояние (конфиг целиком), действия по шагам
+// и общее число шагов. Симуляция детерминирована, в том числе параллельные её части,
+// поэтому тот же журнал всегда даёт тот же прогон
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ReplayLog {
+    pub seed: u64,
+    pub config: serde_json::Value,
+    pub steps: u64,
+    pub inputs: Vec<RecordedInput>,
+}
+
+impl ReplayLog {
+    pub fn new(seed: u64, config: serde_json::Value) -> Self {
+        Self { seed, config, steps: 0, inputs: Vec::new() }
+    }
+
+    pub fn record(&mut self, step: u64, input: Input) {
+        self.inputs.push(RecordedInput { step, input });
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        serde_json::from_str(&contents).map_err(|e| Error::json(path, e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self).map_err(|e| Error::json(path, e))?;
+        fs::write(path, contents).map_err(|e| Error::io(path, e))
+    }
+}
+
+// Выдаёт записанные действия по мере того, как симуляция доходит до их шагов
+pub struct Replayer {
+    log: ReplayLog,
+    next: usize,
+}
+
+impl Replayer {
+    pub fn new(log: ReplayLog) -> Self {
+        Self { log, next: 0 }
+    }
+
+    // действия, записанные после step шагов
+    pub fn inputs_at(&mut self, step: u64) -> Vec<Input> {
+        let mut inputs = Vec::new();
+        while let Some(recorded) = self.log.inputs.get(self.next).filter(|r| r.step <= step) {
+            inputs.push(recorded.input);
+            self.next += 1;
+        }
+        inputs
+    }
+
+    pub fn is_finished(&self, step: u64) -> bool {
+        step >= self.log.steps && self.next >= self.log.inputs.len()
+    }
+}
+
+// Режим сцены планет, задаётся после имени сцены: `--seed <число>`, `--record <файл>`
+// или `--replay <файл>`. Новый прогон получает seed из аргументов, воспроизводимый - из журнала
+#[derive(Debug, PartialEq)]
+pub enum ReplayMode {
+    Live { seed: u64 },
+    Record { seed: u64, path: PathBuf },
+    Replay(PathBuf),
+}
+
+impl ReplayMode {
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let value = |flag: &str| args.iter().position(|arg| arg == flag).and_then(|i| args.get(i + 1));
+        if let Some(path) = value("--replay") {
+            return Ok(ReplayMode::Replay(PathBuf::from(path)));
+        }
+        let seed = match value("--seed") {
+            Some(seed) => seed.parse().map_err(|_| Error::InvalidArgument {
+                name: "--seed",
+                value: seed.clone(),
+                expected: "an unsigned integer",
+            })?,
+            None => DEFAULT_SEED,
+        };
+        Ok(match value("--record") {
+            Some(path) => ReplayMode::Record { seed, path: PathBuf::from(path) },
+            None => ReplayMode::Live { seed },
+        })
+    }
+}
+
+// Прогон журнала без окна: действия вида пропускаются, остальные применяются перед теми же шагами
+pub fn replay_headless(path: &Path, log: &ReplayLog) -> Result<PlanetsSimulation> {
+    let mut sim = PlanetsSimulation::from_json(path, &log.config, log.seed)?;
+    let mut replayer = Replayer::new(log.clone());
+    loop {
+        for input in replayer.inputs_at(sim.steps) {
+            sim.apply(input);
+        }
+        if replayer.is_finished(sim.steps) {
+            return Ok(sim);
+        }
+        sim.step();
+    }
+}
+
+// `planets_simulator replay <файл>`: воспроизводит прогон и печатает конечные положения тел
+pub fn run_headless(path: &Path) -> Result<()> {
+    let log = ReplayLog::load(path)?;
+    let sim = replay_headless(path, &log)?;
+    println!("seed {}: t={:.4} after {} steps", log.seed, sim.time, sim.steps);
+    for planet in &sim.planets {
+        let p = planet.geom_obj.center;
+        println!("  {}: ({:.6}, {:.6}, {:.6})", planet.name, p.x, p.y, p.z);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::geometry::Point3;
+
+    const CONFIG: &str = "src/app/states/configs/planets.json";
+
+    fn config() -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(CONFIG).unwrap()).unwrap()
+    }
+
+    // Прогон с записью: действие input применяется после step шагов
+    fn record_run(seed: u64, steps: u64, inputs: &[(u64, Input)]) -> (PlanetsSimulation, ReplayLog) {
+        let mut sim = PlanetsSimulation::from_json(Path::new(CONFIG), &config(), seed).unwrap();
+        let mut log = ReplayLog::new(seed, config());
+        for step in 0..=steps {
+            for &(_, input) in inputs.iter().filter(|(s, _)| *s == step) {
+                log.record(sim.steps, input);
+                sim.apply(input);
+            }
+            if step < steps {
+                sim.step();
+            }
+        }
+        log.steps = sim.steps;
+        (sim, log)
+    }
+
+    #[test]
+    fn replay_reproduces_positions_after_n_steps() {
+        let inputs = [
+            (3, Input::Pause), (3, Input::Pause), (4, Input::Faster),
+            (5, Input::ScanEvents), (12, Input::NextEvent), (12, Input::ZoomIn), (20, Input::PrevEvent),
+        ];
+        let (sim, log) = record_run(7, 30, &inputs);
+
+        // журнал проходит через JSON, как при сохранении в файл
+        let log: ReplayLog = serde_json::from_str(&serde_json::to_string(&log).unwrap()).unwrap();
+        let replayed = replay_headless(Path::new(CONFIG), &log).unwrap();
+
+        assert_eq!(replayed.steps, 30);
+        assert_eq!(log.seed, 7);
+        assert_eq!(replayed.time, sim.time);
+        assert_eq!(replayed.positions(), sim.positions());
+        assert_eq!(replayed.event_log.len(), sim.event_log.len());
+    }
+
+    #[test]
+    fn recorded_inputs_change_the_run() {
+        let (plain, _) = record_run(DEFAULT_SEED, 20, &[]);
+        let (jumped, _) = record_run(DEFAULT_SEED, 20, &[(5, Input::ScanEvents), (10, Input::NextEvent)]);
+        assert_ne!(plain.time, jumped.time);
+        assert_ne!(plain.positions(), jumped.positions());
+    }
+
+    // прыжок к событию, выбранному в списке, а не только к соседнему
+    #[test]
+    fn jumps_to_the_selected_event() {
+        let inputs = [
+            (5, Input::ScanEvents), (5, Input::SelectNextEvent), (5, Input::SelectNextEvent),
+            (5, Input::SelectNextEvent), (5, Input::ListEvents), (5, Input::JumpToSelected),
+        ];
+        let (sim, log) = record_run(DEFAULT_SEED, 5, &inputs);
+        let (index, event) = sim.event_log.selected().expect("selected event");
+        assert_eq!(sim.time, event.time);
+        // третье событие после текущего времени, а не ближайшее
+        let (next, _) = record_run(DEFAULT_SEED, 5, &[(5, Input::ScanEvents), (5, Input::NextEvent)]);
+        assert_eq!(next.time, sim.event_log.get(index - 2).unwrap().time);
+
+        let replayed = replay_headless(Path::new(CONFIG), &log).unwrap();
+        assert_eq!(replayed.time, sim.time);
+        assert_eq!(replayed.event_log.selected().map(|(i, _)| i), Some(index));
+    }
+
+    // один шаг - 1/80 года: через 80 шагов планета на круговой орбите возвращается в начало
+    // с точностью угловой скорости в f32
+    #[test]
+    fn earth_returns_after_a_year() {
+        let (sim, _) = record_run(DEFAULT_SEED, 80, &[]);
+        let earth = sim.planets.iter().find(|p| p.name == "Earth").unwrap();
+        let start = Point3::from(earth.orbit_start.clone());
+        assert!((earth.position_at(sim.time) - start).magnitude() < 1e-5 * start.magnitude());
+    }
+
+    #[test]
+    fn the_seed_changes_the_belts() {
+        let (a, _) = record_run(1, 0, &[]);
+        let (b, _) = record_run(2, 0, &[]);
+        assert_eq!(a.belt_particles.len(), b.belt_particles.len());
+        assert_ne!(a.positions(), b.positions());
+    }
+
+    #[test]
+    fn parses_the_mode_from_args() {
+        let args = |line: &str| line.split_whitespace().map(String::from).collect::<Vec<_>>();
+        assert_eq!(ReplayMode::from_args(&[]).unwrap(), ReplayMode::Live { seed: DEFAULT_SEED });
+        assert_eq!(
+            ReplayMode::from_args(&args("--seed 42 --record run.json")).unwrap(),
+            ReplayMode::Record { seed: 42, path: PathBuf::from("run.json") },
+        );
+        assert_eq!(ReplayMode::from_args(&args("--replay run.json --seed 1")).unwrap(), ReplayMode::Replay(PathBuf::from("run.json")));
+        assert!(ReplayMode::from_args(&args("--seed -1")).is_err());
+    }
+}
@@ -0,0 +1,57 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) normal: vec3<f32>,
+    @location(3) world_pos: vec3<f32>,
+    @location(4) uv: vec2<f32>,
+};
+
+struct Uniforms {
+    transform: mat4x4<f32>,
+    light_origin: vec3<f32>,
+    light_color: vec3<f32>,
+    ambient_strength: f32,
+};
+
+@group(0)
+@binding(0)
+var<uniform> uniforms: Uniforms;
+@group(0) @binding(1) var texture_sampler: sampler;
+@group(0) @binding(2) var ring_texture: texture_2d<f32>;
+// модельная матрица тела: вершины в собственных координатах геометрии
+@group(0) @binding(3) var<uniform> model: mat4x4<f32>;
+
+@vertex
+fn vs_main(
+    @location(0) position: vec4<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) normal: vec3<f32>,
+    @location(3) uv: vec2<f32>
+) -> VertexOutput {
+    var result: VertexOutput;
+    let world_pos = model * position;
+    result.position = uniforms.transform * world_pos;
+    result.color = color;
+    // масштаб равномерный, поэтому нормали поворачиваются той же матрицей
+    result.normal = normalize((model * vec4<f32>(normal, 0.0)).xyz);
+    result.world_pos = world_pos.xyz;
+    result.uv = uv;
+
+    return result;
+}
+
+// Кольцо видно с обеих сторон: освещённость зависит от угла падения света, но не от стороны
+@fragment
+fn fs_main(vertex: VertexOutput) -> @location(0) vec4<f32> {
+    let texture_color = textureSample(ring_texture, texture_sampler, vertex.uv);
+
+    let normal = normalize(vertex.normal);
+    let light_dir = normalize(uniforms.light_origin - vertex.world_pos);
+
+    let ambient = uniforms.ambient_strength;
+    let intensity = (abs(dot(normal, light_dir)) + ambient) / (1.0 + ambient);
+
+    let lit_color = texture_color.rgb * intensity * uniforms.light_color * vertex.color.rgb;
+
+    return vec4<f32>(lit_color, texture_color.a * vertex.color.a);
+}
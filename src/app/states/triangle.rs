@@ -2,6 +2,7 @@ use std::sync::Arc;
 use winit::window::Window;
 use crate::app::graphics::gpu_resources::GPU_Resources;
 use crate::app::graphics::screen::Screen;
+use crate::error::Result;
 use std::borrow::Cow;
 
 pub struct StateTriangle {
@@ -28,7 +29,7 @@ impl StateTriangle {
     pub fn init() {
     }
 
-    pub fn render(&mut self) {
+    pub fn render(&mut self) -> Result<()> {
         let pipeline_layout = self.screen.resources.create_pipeline_layout();
         let shader = self.screen.resources.device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: None,
@@ -40,6 +41,6 @@ impl StateTriangle {
             rpass.set_pipeline(&render_pipeline);
             rpass.draw(0..3, 0..1);
 
-        }, Some(render_pipeline));
+        }, Some(render_pipeline))
     }
 }
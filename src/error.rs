@@ -0,0 +1,136 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::physics::bounds::Aabb;
+
+// Ошибки загрузки конфигов и построения геометрии. Несут контекст для диагностики:
+// путь к файлу, позицию в JSON, размеры вырожденного набора точек
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    // line и column считаются с 1, как в serde_json
+    Json {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        source: serde_json::Error,
+    },
+    // содержимое файла не разобрать (меш, изображение); line считается с 1,
+    // у двоичных форматов её нет
+    Parse {
+        path: PathBuf,
+        line: Option<usize>,
+        message: String,
+    },
+    // выпуклую оболочку не построить: точек меньше 4 или все они в одной плоскости.
+    // bounds - габариты набора, по ним видно, вдоль какой оси он сплющен
+    DegenerateHull {
+        points: usize,
+        bounds: Option<Aabb>,
+        reason: String,
+    },
+    // у меша нет вершин
+    EmptyMesh,
+    InvalidParameter {
+        name: &'static str,
+        value: f64,
+        expected: &'static str,
+    },
+    // запись конфига сцены ссылается на несуществующее тело или задаёт неподдерживаемый случай;
+    // entry - вид и имя записи, например `comet Halley`
+    Config {
+        entry: String,
+        reason: String,
+    },
+    // бисекция потеряла смену центрального тела, найденную на шаге поиска:
+    // аппарат скользит по границе сферы влияния
+    SoiCrossing {
+        spacecraft: String,
+        time: f64,
+    },
+    // кадр не получить даже после перенастройки поверхности
+    Surface(wgpu::SurfaceError),
+    // окно или цикл событий не создать
+    Window(winit::error::OsError),
+    EventLoop(winit::error::EventLoopError),
+    // неверное значение аргумента командной строки
+    InvalidArgument {
+        name: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    pub fn io(path: &Path, source: std::io::Error) -> Self {
+        Error::Io { path: path.to_path_buf(), source }
+    }
+
+    pub fn json(path: &Path, source: serde_json::Error) -> Self {
+        Error::Json { path: path.to_path_buf(), line: source.line(), column: source.column(), source }
+    }
+
+    // ошибка чтения файла остаётся Io, ошибка декодирования становится Parse
+    pub fn image(path: &Path, source: image::ImageError) -> Self {
+        match source {
+            image::ImageError::IoError(source) => Error::io(path, source),
+            source => Error::Parse { path: path.to_path_buf(), line: None, message: source.to_string() },
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::Json { path, line, column, source } => {
+                // позиция уже выведена отдельно, из сообщения serde_json она убирается
+                let message = source.to_string();
+                let message = message.split(" at line ").next().unwrap_or(&message);
+                write!(f, "{}:{}:{}: {}", path.display(), line, column, message)
+            }
+            Error::Parse { path, line: Some(line), message } => write!(f, "{}:{}: {}", path.display(), line, message),
+            Error::Parse { path, line: None, message } => write!(f, "{}: {}", path.display(), message),
+            Error::DegenerateHull { points, bounds, reason } => {
+                write!(f, "cannot build a convex hull of {} points: {}", points, reason)?;
+                if let Some(bounds) = bounds {
+                    let size = bounds.size();
+                    write!(f, " (extent {:.3e} x {:.3e} x {:.3e})", size.x, size.y, size.z)?;
+                }
+                Ok(())
+            }
+            Error::EmptyMesh => write!(f, "mesh has no vertices"),
+            Error::InvalidParameter { name, value, expected } => {
+                write!(f, "invalid {}: {} (expected {})", name, value, expected)
+            }
+            Error::Config { entry, reason } => write!(f, "{}: {}", entry, reason),
+            Error::SoiCrossing { spacecraft, time } => {
+                write!(f, "{}: lost the sphere of influence crossing near t={:.6}", spacecraft, time)
+            }
+            Error::Surface(source) => write!(f, "cannot draw a frame: {}", source),
+            Error::Window(source) => write!(f, "cannot create the window: {}", source),
+            Error::EventLoop(source) => write!(f, "event loop failed: {}", source),
+            Error::InvalidArgument { name, value, expected } => {
+                write!(f, "invalid {}: {} (expected {})", name, value, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Json { source, .. } => Some(source),
+            Error::Surface(source) => Some(source),
+            Error::Window(source) => Some(source),
+            Error::EventLoop(source) => Some(source),
+            _ => None,
+        }
+    }
+}
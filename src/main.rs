@@ -5,8 +5,11 @@ use winit::event_loop::{EventLoop, ControlFlow};
 
 pub mod physics;
 pub mod app;
+pub mod error;
 
 use app::app::App;
+use app::states::replay;
+use error::{Error, Result};
 
 
 // fn main() {
@@ -29,19 +32,37 @@ use app::app::App;
 //     }
 // }
 
+// Журнал событий, прыжки и отчёты сцены выводятся на уровне info; RUST_LOG переопределяет фильтр
 fn init_logging() {
-    // TODO : set logging level
-    env_logger::init();
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn,planets_simulator=info")).init();
 }
 
 fn main() {
     init_logging();
 
-    let event_loop = EventLoop::new().unwrap();
+    // `replay <файл>`: воспроизведение записанного прогона без окна
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [command, path, ..] = args.as_slice() && command == "replay" {
+        if let Err(e) = replay::run_headless(std::path::Path::new(path)) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<()> {
+    let event_loop = EventLoop::new().map_err(Error::EventLoop)?;
     event_loop.set_control_flow(ControlFlow::Poll);
     // we use the fastest one, but can be used this one too
     // event_loop.set_control_flow(ControlFlow::Wait);
 
     let mut app = App::default();
-    event_loop.run_app(&mut app).unwrap();
+    event_loop.run_app(&mut app).map_err(Error::EventLoop)?;
+    app.take_error().map_or(Ok(()), Err)
 }
\ No newline at end of file
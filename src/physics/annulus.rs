@@ -0,0 +1,41 @@
+use crate::error::Result;
+use crate::physics::geometry::{Geometry, Mesh, MBV};
+use crate::physics::revolution::{ProfilePoint, revolve};
+
+const DEFAULT_SEGMENTS: usize = 128;
+
+// Плоское кольцо в плоскости XY с нормалью +Z (кольца планет).
+// v текстуры растёт от внутреннего края к внешнему
+pub struct Annulus {
+    pub inner_radius: f64,
+    pub outer_radius: f64,
+    pub segments: usize,
+}
+
+impl Annulus {
+    pub fn new(inner_radius: f64, outer_radius: f64) -> Self {
+        Self { inner_radius, outer_radius, segments: DEFAULT_SEGMENTS }
+    }
+
+    fn build_surface_mesh(&self) -> Mesh {
+        revolve(&[
+            ProfilePoint::new(self.inner_radius, 0.0, (0.0, 1.0)),
+            ProfilePoint::new(self.outer_radius, 0.0, (0.0, 1.0)),
+        ], self.segments)
+    }
+}
+
+impl Geometry for Annulus {
+    fn get_surface_mesh(&self) -> Result<Mesh> {
+        Ok(self.build_surface_mesh())
+    }
+
+    fn get_edges_mesh(&self, bold: f32) -> Result<Mesh> {
+        self.build_surface_mesh().edges_along_normals(bold)
+    }
+
+    fn minimal_bounding_volume(&self) -> MBV {
+        let side = 2.0 * self.outer_radius;
+        MBV(side, side, 0.0)
+    }
+}
@@ -1,16 +1,32 @@
-use std::{f64::consts::PI, ops::Index};
-use crate::physics::geometry::{Geometry, Mesh, MBV, Point3, quick_hull};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use rayon::prelude::*;
+use crate::error::Result;
+use crate::physics::geometry::{Geometry, Mesh, MBV, PARALLEL_MIN_LEN, Point3, check_bold, quick_hull};
+use crate::physics::bounds::{BoundingSphere, Ray, RayHit};
+
+// Способ построения сферы
+#[derive(Debug, Clone, Copy)]
+pub enum SphereKind {
+    // точки спирали Фибоначчи, триангулированные выпуклой оболочкой
+    Fibonacci { points: usize },
+    // икосаэдр, каждая грань которого subdivisions раз делится на 4
+    Icosphere { subdivisions: u32 },
+    // сетка по долготе (segments) и широте (rings)
+    UvSphere { segments: usize, rings: usize },
+}
 
 pub struct Ball {
     pub radius: f64,
+    pub kind: SphereKind,
 }
 
 
 fn fibonacci_sphere_points(n: usize, r: f64) -> Vec<Point3> {
     let golden_ratio = (1.0 + 5f64.sqrt()) / 2.0;
-    let mut pts = Vec::with_capacity(n);
 
-    for i in 0..n {
+    // каждая точка считается независимо, порядок сохраняется при collect
+    (0..n).into_par_iter().with_min_len(PARALLEL_MIN_LEN).map(|i| {
         let z = 1.0 - 2.0 * ((i as f64) + 0.5) / n as f64;
         let phi = 2.0 * PI * (((i as f64) + 0.5) * golden_ratio).fract();
 
@@ -18,23 +34,189 @@ fn fibonacci_sphere_points(n: usize, r: f64) -> Vec<Point3> {
         let x = r_xy * phi.cos();
         let y = r_xy * phi.sin();
 
-        pts.push(Point3::new(x, y, z) * r);
+        Point3::new(x, y, z) * r
+    }).collect()
+}
+
+// Вершины ближе этого к оси Z считаются полюсами: долгота у них не определена
+const POLE_EPSILON: f64 = 1e-9;
+
+// Равнопромежуточная проекция: u - долгота, v - широта от северного полюса (ось Z)
+pub fn sphere_uv(n: &Point3) -> [f64; 2] {
+    let u = 0.5 + n.y.atan2(n.x) / (2.0 * PI);
+    let v = n.z.clamp(-1.0, 1.0).acos() / PI;
+    [u, v]
+}
+
+fn icosahedron() -> (Vec<Point3>, Vec<[usize; 3]>) {
+    let t = (1.0 + 5f64.sqrt()) / 2.0;
+    let vertices = [
+        [-1.0, t, 0.0], [1.0, t, 0.0], [-1.0, -t, 0.0], [1.0, -t, 0.0],
+        [0.0, -1.0, t], [0.0, 1.0, t], [0.0, -1.0, -t], [0.0, 1.0, -t],
+        [t, 0.0, -1.0], [t, 0.0, 1.0], [-t, 0.0, -1.0], [-t, 0.0, 1.0],
+    ].iter().map(|v| Point3::new(v[0], v[1], v[2]).normalize()).collect();
+    // грани обходятся против часовой стрелки при взгляде снаружи
+    let indices = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+    (vertices, indices)
+}
+
+// Единичная икосфера: середины рёбер общие для соседних граней
+fn icosphere(subdivisions: u32) -> (Vec<Point3>, Vec<[usize; 3]>) {
+    let (mut vertices, mut indices) = icosahedron();
+    for _ in 0..subdivisions {
+        let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut midpoint = |a: usize, b: usize, vertices: &mut Vec<Point3>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                vertices.push(((vertices[a] + vertices[b]) / 2.0).normalize());
+                vertices.len() - 1
+            })
+        };
+        indices = indices.iter().flat_map(|&[a, b, c]| {
+            let ab = midpoint(a, b, &mut vertices);
+            let bc = midpoint(b, c, &mut vertices);
+            let ca = midpoint(c, a, &mut vertices);
+            [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+        }).collect();
     }
-    pts
+    (vertices, indices)
+}
+
+fn is_pole(n: &Point3) -> bool {
+    n.x.abs() < POLE_EPSILON && n.y.abs() < POLE_EPSILON
+}
+
+// Текстурные координаты единичной сферы без разрыва на шве: у треугольников, пересекающих
+// меридиан ±180°, вершины с u < 0.5 дублируются с u + 1, а полюсу в каждом треугольнике
+// достаётся своя копия с u, равным среднему двух других вершин
+fn seamless_uvs(normals: &mut Vec<Point3>, indices: &mut [[usize; 3]]) -> Vec<[f64; 2]> {
+    let mut uvs: Vec<[f64; 2]> = normals.iter().map(sphere_uv).collect();
+    let mut wrapped: HashMap<usize, usize> = HashMap::new();
+    for face in indices.iter_mut() {
+        let us: Vec<f64> = face.iter().filter(|&&i| !is_pole(&normals[i])).map(|&i| uvs[i][0]).collect();
+        let span = us.iter().cloned().fold(f64::MIN, f64::max) - us.iter().cloned().fold(f64::MAX, f64::min);
+        if span > 0.5 {
+            for i in face.iter_mut() {
+                if !is_pole(&normals[*i]) && uvs[*i][0] < 0.5 {
+                    *i = *wrapped.entry(*i).or_insert_with(|| {
+                        normals.push(normals[*i]);
+                        uvs.push([uvs[*i][0] + 1.0, uvs[*i][1]]);
+                        normals.len() - 1
+                    });
+                }
+            }
+        }
+
+        let poles: Vec<usize> = (0..3).filter(|&k| is_pole(&normals[face[k]])).collect();
+        if poles.len() == 1 {
+            let k = poles[0];
+            let u = (uvs[face[(k + 1) % 3]][0] + uvs[face[(k + 2) % 3]][0]) / 2.0;
+            normals.push(normals[face[k]]);
+            uvs.push([u, uvs[face[k]][1]]);
+            face[k] = normals.len() - 1;
+        }
+    }
+    uvs
+}
+
+// Единичная UV-сфера; у каждого кольца segments + 1 вершин, первая и последняя совпадают
+// по положению, но имеют u = 0 и u = 1
+fn uv_sphere(segments: usize, rings: usize) -> (Vec<Point3>, Vec<[f64; 2]>, Vec<[usize; 3]>) {
+    let segments = segments.max(3);
+    let rings = rings.max(2);
+    let mut vertices = Vec::with_capacity((segments + 1) * (rings + 1));
+    let mut uvs = Vec::with_capacity(vertices.capacity());
+    for i in 0..=rings {
+        let v = i as f64 / rings as f64;
+        let (sin_theta, cos_theta) = (PI * v).sin_cos();
+        for j in 0..=segments {
+            let u = j as f64 / segments as f64;
+            let (sin_phi, cos_phi) = (2.0 * PI * u - PI).sin_cos();
+            vertices.push(Point3::new(sin_theta * cos_phi, sin_theta * sin_phi, cos_theta));
+            // вершина полюса используется одним треугольником, u берётся по его середине:
+            // у северного полюса это сегмент j - 1, у южного - сегмент j
+            let u = match i {
+                0 => (j as f64 - 0.5) / segments as f64,
+                _ if i == rings => (j as f64 + 0.5) / segments as f64,
+                _ => u,
+            };
+            uvs.push([u, v]);
+        }
+    }
+
+    let row = segments + 1;
+    let mut indices = Vec::with_capacity(2 * segments * rings);
+    for i in 0..rings {
+        for j in 0..segments {
+            let a = i * row + j;
+            let b = a + row;
+            // у полюсов одна из половин четырёхугольника вырождена
+            if i != 0 {
+                indices.push([a, b, a + 1]);
+            }
+            if i != rings - 1 {
+                indices.push([a + 1, b, b + 1]);
+            }
+        }
+    }
+    (vertices, uvs, indices)
 }
 
 impl Ball {
     pub fn new(radius: f64) -> Self {
-        Self { radius }
+        Self { radius, kind: SphereKind::Fibonacci { points: 100 } }
     }
 
-    fn build_surface_mesh(&self) -> Mesh {
-        let vertices = fibonacci_sphere_points(100, self.radius);
-        let indices = quick_hull(&vertices);
-        Mesh { vertices, indices }
+    pub fn icosphere(radius: f64, subdivisions: u32) -> Self {
+        Self { radius, kind: SphereKind::Icosphere { subdivisions } }
+    }
+
+    pub fn uv_sphere(radius: f64, segments: usize, rings: usize) -> Self {
+        Self { radius, kind: SphereKind::UvSphere { segments, rings } }
+    }
+
+    // Оболочка спирали Фибоначчи не строится меньше чем из 4 точек
+    fn build_surface_mesh(&self) -> Result<Mesh> {
+        let (normals, uvs, indices) = match self.kind {
+            SphereKind::Fibonacci { points } => {
+                let mut normals = fibonacci_sphere_points(points, 1.0);
+                let mut indices = quick_hull(&normals)?;
+                let uvs = seamless_uvs(&mut normals, &mut indices);
+                (normals, uvs, indices)
+            }
+            SphereKind::Icosphere { subdivisions } => {
+                let (mut normals, mut indices) = icosphere(subdivisions);
+                let uvs = seamless_uvs(&mut normals, &mut indices);
+                (normals, uvs, indices)
+            }
+            SphereKind::UvSphere { segments, rings } => uv_sphere(segments, rings),
+        };
+        let (tangents, bitangents) = sphere_tangents(&normals, &uvs);
+        Ok(Mesh {
+            vertices: normals.iter().map(|n| n * self.radius).collect(),
+            indices,
+            normals: Some(normals),
+            uvs: Some(uvs),
+            tangents: Some(tangents),
+            bitangents: Some(bitangents),
+        })
     }
 }
 
+// Касательные единичной сферы: tangent - на восток (рост долготы, u),
+// bitangent - на юг (рост v). Долгота берётся из u, поэтому определена и на полюсах
+fn sphere_tangents(normals: &[Point3], uvs: &[[f64; 2]]) -> (Vec<Point3>, Vec<Point3>) {
+    normals.iter().zip(uvs).map(|(n, uv)| {
+        let (sin_phi, cos_phi) = (2.0 * PI * uv[0] - PI).sin_cos();
+        let tangent = Point3::new(-sin_phi, cos_phi, 0.0);
+        (tangent, tangent.cross(n).normalize())
+    }).unzip()
+}
+
 fn shift_point_from(x: &Point3, base: &Point3, shift_range: f32) -> Point3 {
     let dist = x - base;
     let e = dist / dist.magnitude();
@@ -42,54 +224,146 @@ fn shift_point_from(x: &Point3, base: &Point3, shift_range: f32) -> Point3 {
 }
 
 impl Geometry for Ball {
-    fn get_surface_mesh(&self) -> Mesh {
+    fn get_surface_mesh(&self) -> Result<Mesh> {
         self.build_surface_mesh()
     }
-    
-    fn get_edges_mesh(&self, bold: f32) -> Mesh {
-        let vertices = fibonacci_sphere_points(100, self.radius);
-        let surface_indices = quick_hull(&vertices);
-        let surface_mesh = Mesh { vertices: vertices.clone(), indices: surface_indices };
+
+    fn get_edges_mesh(&self, bold: f32) -> Result<Mesh> {
+        check_bold(bold)?;
+        let surface_mesh = self.build_surface_mesh()?;
+        let vertices = &surface_mesh.vertices;
         let indices = surface_mesh.get_edges_indices();
 
+        // для каждой вершины поверхности: (индекс вершины, индекс сдвинутой наружу копии)
+        let mut edge_vertex: Vec<Option<(usize, usize)>> = vec![None; vertices.len()];
         let mut edges_vertices = Vec::new();
         let mut edges_indices = Vec::new();
-        let center_point = surface_mesh.get_center();
+        let center_point = surface_mesh.get_center()?;
+        let mut edge_pair = |i: usize, edges_vertices: &mut Vec<Point3>| {
+            *edge_vertex[i].get_or_insert_with(|| {
+                edges_vertices.push(vertices[i]);
+                edges_vertices.push(shift_point_from(&vertices[i], &center_point, bold));
+                (edges_vertices.len() - 2, edges_vertices.len() - 1)
+            })
+        };
         for (ind1, ind2) in &indices {
-            let (v1, v2) = (&vertices[*ind1], &vertices[*ind2]);
-            let v1_edge = shift_point_from(&v1, &center_point, bold);
-            let v2_edge = shift_point_from(&v2, &center_point, bold);
-
-            let ind11_edge = edges_vertices.iter().position(|v| v == v1).unwrap_or_else(|| {
-                edges_vertices.push(v1.clone());
-                edges_vertices.len() - 1
-            });
-
-            let ind12_edge = edges_vertices.iter().position(|v| *v == v1_edge).unwrap_or_else(|| {
-                edges_vertices.push(v1_edge.clone());
-                edges_vertices.len() - 1
-            });
-
-            // Теперь получаем индексы для v2 и v2_edge
-            let ind21_edge = edges_vertices.iter().position(|v| v == v2).unwrap_or_else(|| {
-                edges_vertices.push(v2.clone());
-                edges_vertices.len() - 1
-            });
-
-            let ind22_edge = edges_vertices.iter().position(|v| *v == v2_edge).unwrap_or_else(|| {
-                edges_vertices.push(v2_edge.clone());
-                edges_vertices.len() - 1
-            });
+            let (ind11_edge, ind12_edge) = edge_pair(*ind1, &mut edges_vertices);
+            let (ind21_edge, ind22_edge) = edge_pair(*ind2, &mut edges_vertices);
 
             edges_indices.push([ind11_edge, ind12_edge, ind21_edge]);
             edges_indices.push([ind12_edge, ind22_edge, ind21_edge]);
         }
 
-        Mesh { vertices: edges_vertices, indices: edges_indices }
+        Ok(Mesh::new(edges_vertices, edges_indices))
     }
 
     fn minimal_bounding_volume(&self) -> MBV {
         let side = 2.0 * self.radius;
         MBV(side, side, side)
     }
-}
\ No newline at end of file
+
+    fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere::new(Point3::zeros(), self.radius)
+    }
+
+    // Точное пересечение со сферой, а не с её триангуляцией
+    fn ray_cast(&self, ray: &Ray) -> Option<RayHit> {
+        self.bounding_sphere().ray_intersect(ray)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_on_sphere(mesh: &Mesh, radius: f64) {
+        for v in &mesh.vertices {
+            assert!((v.norm() - radius).abs() < 1e-12, "{} vs {}", v.norm(), radius);
+        }
+    }
+
+    // каждое деление добавляет по вершине на ребро: V = 10 * 4^n + 2, F = 20 * 4^n
+    #[test]
+    fn icosphere_counts_per_subdivision() {
+        for subdivisions in 0..5 {
+            let (vertices, indices) = icosphere(subdivisions);
+            let faces = 20 * 4usize.pow(subdivisions);
+            assert_eq!(vertices.len(), faces / 2 + 2);
+            assert_eq!(indices.len(), faces);
+
+            // шов и полюса добавляют копии вершин, но не грани
+            let mesh = Ball::icosphere(2.5, subdivisions).get_surface_mesh().unwrap();
+            assert_eq!(mesh.indices.len(), faces);
+            assert!(mesh.vertices.len() >= vertices.len());
+            assert_on_sphere(&mesh, 2.5);
+        }
+    }
+
+    // у полюсов от каждого сегмента остаётся один треугольник
+    #[test]
+    fn uv_sphere_counts() {
+        for (segments, rings) in [(3, 2), (8, 4), (32, 16), (64, 31)] {
+            let mesh = Ball::uv_sphere(0.75, segments, rings).get_surface_mesh().unwrap();
+            assert_eq!(mesh.vertices.len(), (segments + 1) * (rings + 1));
+            assert_eq!(mesh.indices.len(), 2 * segments * (rings - 1));
+            assert_eq!(mesh.uvs.as_ref().unwrap().len(), mesh.vertices.len());
+            assert_on_sphere(&mesh, 0.75);
+        }
+
+        // слишком грубые параметры поднимаются до минимальных
+        let mesh = Ball::uv_sphere(1.0, 1, 1).get_surface_mesh().unwrap();
+        assert_eq!(mesh.indices.len(), 2 * 3);
+    }
+
+    // Треугольник, накрывающий ось Z (у спирали Фибоначчи на полюсах нет вершин), охватывает
+    // все долготы, и его u не развернуть без разрыва; такие треугольники не учитываются
+    fn covers_pole(mesh: &Mesh, face: &[usize; 3]) -> bool {
+        let p = face.map(|i| mesh.vertices[i]);
+        let side = |a: &Point3, b: &Point3| a.x * b.y - a.y * b.x;
+        let sides = [side(&p[0], &p[1]), side(&p[1], &p[2]), side(&p[2], &p[0])];
+        sides.iter().all(|&s| s > 0.0) || sides.iter().all(|&s| s < 0.0)
+    }
+
+    fn max_u_span(mesh: &Mesh) -> f64 {
+        let uvs = mesh.uvs.as_ref().unwrap();
+        mesh.indices.iter().filter(|face| !covers_pole(mesh, face)).map(|face| {
+            let us = face.map(|i| uvs[i][0]);
+            us.iter().cloned().fold(f64::MIN, f64::max) - us.iter().cloned().fold(f64::MAX, f64::min)
+        }).fold(0.0, f64::max)
+    }
+
+    // первая и последняя вершины кольца совпадают по положению, но имеют u = 0 и u = 1
+    #[test]
+    fn uv_sphere_seam_is_duplicated() {
+        let (segments, rings) = (16, 8);
+        let mesh = Ball::uv_sphere(1.0, segments, rings).get_surface_mesh().unwrap();
+        let uvs = mesh.uvs.as_ref().unwrap();
+        for ring in 1..rings {
+            let (first, last) = (ring * (segments + 1), ring * (segments + 1) + segments);
+            assert!((mesh.vertices[first] - mesh.vertices[last]).magnitude() < 1e-12);
+            assert_eq!((uvs[first][0], uvs[last][0]), (0.0, 1.0));
+            assert_eq!(uvs[first][1], uvs[last][1]);
+        }
+        assert!(max_u_span(&mesh) <= 1.0 / segments as f64 + 1e-12);
+    }
+
+    // ни один треугольник не растягивается через всю текстуру на шве ±180°
+    #[test]
+    fn no_triangle_wraps_around_the_texture() {
+        let fibonacci = Ball::new(1.0).get_surface_mesh().unwrap();
+        assert_eq!(fibonacci.indices.iter().filter(|face| covers_pole(&fibonacci, face)).count(), 2);
+
+        for ball in [Ball::icosphere(1.0, 3), Ball::uv_sphere(1.0, 32, 16), Ball { radius: 1.0, kind: SphereKind::Fibonacci { points: 500 } }] {
+            let mesh = ball.get_surface_mesh().unwrap();
+            assert!(max_u_span(&mesh) < 0.5, "{:?}: {}", ball.kind, max_u_span(&mesh));
+
+            // копии на шве и у полюсов совпадают с исходной вершиной по положению
+            let uvs = mesh.uvs.as_ref().unwrap();
+            let seam: Vec<usize> = (0..mesh.vertices.len()).filter(|&i| uvs[i][0] > 1.0).collect();
+            for i in seam {
+                let [u, v] = uvs[i];
+                assert!(mesh.vertices.iter().zip(uvs).any(|(p, uv)| (p - mesh.vertices[i]).magnitude() < 1e-12 && (uv[0] - (u - 1.0)).abs() < 1e-12 && uv[1] == v));
+            }
+        }
+    }
+}
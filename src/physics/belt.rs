@@ -0,0 +1,118 @@
+use std::f64::consts::PI;
+
+use crate::error::{Error, Result};
+use crate::physics::orbit::OrbitalElements;
+use crate::physics::random::Rng;
+
+// Параметры пояса малых тел; расстояния в а.е., углы в радианах, размеры в км
+#[derive(Debug, Clone)]
+pub struct BeltConfig {
+    pub count: usize,
+    pub inner_radius: f64,
+    pub outer_radius: f64,
+    // параметры распределений Рэлея для эксцентриситета и наклона
+    pub eccentricity_sigma: f64,
+    pub max_eccentricity: f64,
+    pub inclination_sigma: f64,
+    // степенной закон размеров N(>D) ~ D^-q
+    pub min_size: f64,
+    pub max_size: f64,
+    pub size_index: f64,
+    pub seed: u64,
+}
+
+impl BeltConfig {
+    // Сравнения записаны так, чтобы NaN тоже считался ошибкой
+    pub fn validate(&self) -> Result<()> {
+        let check = |ok: bool, name: &'static str, value: f64, expected: &'static str| {
+            if ok { Ok(()) } else { Err(Error::InvalidParameter { name, value, expected }) }
+        };
+        check(self.inner_radius > 0.0, "belt inner radius", self.inner_radius, "a positive number")?;
+        check(self.outer_radius >= self.inner_radius, "belt outer radius", self.outer_radius, "at least the inner radius")?;
+        check(self.eccentricity_sigma >= 0.0, "belt eccentricity sigma", self.eccentricity_sigma, "a non-negative number")?;
+        // эксцентриситет разыгрывается заново, пока не станет меньше максимума
+        check(self.max_eccentricity > 0.0 && self.max_eccentricity <= 1.0, "belt max eccentricity", self.max_eccentricity, "a number in (0, 1]")?;
+        check(self.inclination_sigma >= 0.0, "belt inclination sigma", self.inclination_sigma, "a non-negative number")?;
+        check(self.min_size > 0.0, "belt min size", self.min_size, "a positive number")?;
+        check(self.max_size >= self.min_size, "belt max size", self.max_size, "at least the min size")?;
+        check(self.size_index.is_finite(), "belt size index", self.size_index, "a finite number")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SmallBody {
+    pub elements: OrbitalElements,
+    pub size: f64,
+}
+
+// Одинаковые config и seed всегда дают одинаковый набор тел
+pub fn generate_belt(config: &BeltConfig) -> Result<Vec<SmallBody>> {
+    config.validate()?;
+    let mut rng = Rng::new(config.seed);
+    let mut bodies = Vec::with_capacity(config.count);
+
+    for _ in 0..config.count {
+        // равномерная поверхностная плотность: a ~ sqrt(U) между радиусами
+        let inner2 = config.inner_radius * config.inner_radius;
+        let outer2 = config.outer_radius * config.outer_radius;
+        let semi_major_axis = rng.range(inner2, outer2).sqrt();
+
+        let mut eccentricity = rng.rayleigh(config.eccentricity_sigma);
+        while eccentricity >= config.max_eccentricity {
+            eccentricity = rng.rayleigh(config.eccentricity_sigma);
+        }
+        let inclination = rng.rayleigh(config.inclination_sigma).min(PI / 2.0);
+
+        let elements = OrbitalElements {
+            semi_major_axis,
+            eccentricity,
+            inclination,
+            ascending_node: rng.range(0.0, 2.0 * PI),
+            arg_periapsis: rng.range(0.0, 2.0 * PI),
+            mean_anomaly: rng.range(0.0, 2.0 * PI),
+        };
+        let size = rng.power_law(config.min_size, config.max_size, config.size_index);
+
+        bodies.push(SmallBody { elements, size });
+    }
+    Ok(bodies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> BeltConfig {
+        BeltConfig {
+            count: 200,
+            inner_radius: 2.1,
+            outer_radius: 3.3,
+            eccentricity_sigma: 0.1,
+            max_eccentricity: 0.4,
+            inclination_sigma: 0.1,
+            min_size: 1.0,
+            max_size: 500.0,
+            size_index: 1.5,
+            seed: 1,
+        }
+    }
+
+    #[test]
+    fn belt_stays_within_its_bounds() {
+        let bodies = generate_belt(&config()).unwrap();
+        assert_eq!(bodies.len(), 200);
+        assert!(bodies.iter().all(|b| {
+            (2.1..=3.3).contains(&b.elements.semi_major_axis)
+                && b.elements.eccentricity < 0.4
+                && (1.0..=500.0).contains(&b.size)
+        }));
+    }
+
+    #[test]
+    fn rejects_a_non_positive_max_eccentricity() {
+        let circular = BeltConfig { max_eccentricity: 0.0, ..config() };
+        assert!(matches!(generate_belt(&circular), Err(Error::InvalidParameter { name: "belt max eccentricity", .. })));
+        let no_size = BeltConfig { min_size: f64::NAN, ..config() };
+        assert!(generate_belt(&no_size).is_err());
+    }
+}
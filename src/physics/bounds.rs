@@ -0,0 +1,422 @@
+use nalgebra::Rotation3;
+
+use crate::physics::ball::sphere_uv;
+use crate::physics::geometry::Point3;
+
+// Пересечения ближе этого (в длинах direction) отбрасываются: луч, выпущенный с поверхности,
+// не должен задевать её саму
+const RAY_EPSILON: f64 = 1e-9;
+
+// Луч origin + t * direction, t >= 0. direction не обязан быть единичным:
+// при переносе луча в координаты модели t сохраняется
+#[derive(Debug, Clone, Copy)]
+pub struct Ray {
+    pub origin: Point3,
+    pub direction: Point3,
+}
+
+impl Ray {
+    pub fn new(origin: Point3, direction: Point3) -> Self {
+        Self { origin, direction }
+    }
+
+    // Луч от from к to: попадание с distance < 1 лежит между точками
+    // (прямая видимость, покрытие одного тела другим)
+    pub fn between(from: &Point3, to: &Point3) -> Self {
+        Self { origin: *from, direction: to - from }
+    }
+
+    pub fn at(&self, t: f64) -> Point3 {
+        self.origin + self.direction * t
+    }
+}
+
+// Ближайшее пересечение луча с поверхностью. distance - параметр t на луче, normal - внешняя нормаль.
+// Для треугольника uv - барицентрические координаты (веса второй и третьей вершины),
+// для сферы - долгота и широта как у sphere_uv, для параллелепипеда - положение на грани
+#[derive(Debug, Clone, Copy)]
+pub struct RayHit {
+    pub distance: f64,
+    pub normal: Point3,
+    pub uv: [f64; 2],
+    // номер треугольника меша
+    pub triangle: Option<usize>,
+}
+
+impl RayHit {
+    pub fn point(&self, ray: &Ray) -> Point3 {
+        ray.at(self.distance)
+    }
+}
+
+// Параллелепипед, выровненный по осям
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Point3,
+    pub max: Point3,
+}
+
+impl Aabb {
+    pub fn new(min: Point3, max: Point3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_center_size(center: &Point3, size: &Point3) -> Self {
+        Self { min: center - size / 2.0, max: center + size / 2.0 }
+    }
+
+    // None для пустого набора точек
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Point3>) -> Option<Self> {
+        points.into_iter().fold(None, |acc, p| match acc {
+            None => Some(Self { min: *p, max: *p }),
+            Some(aabb) => Some(Self { min: aabb.min.inf(p), max: aabb.max.sup(p) }),
+        })
+    }
+
+    pub fn center(&self) -> Point3 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn size(&self) -> Point3 {
+        self.max - self.min
+    }
+
+    pub fn half_extents(&self) -> Point3 {
+        self.size() / 2.0
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Self { min: self.min.inf(&other.min), max: self.max.sup(&other.max) }
+    }
+
+    pub fn contains(&self, p: &Point3) -> bool {
+        (0..3).all(|k| self.min[k] <= p[k] && p[k] <= self.max[k])
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|k| self.min[k] <= other.max[k] && other.min[k] <= self.max[k])
+    }
+
+    // Описанная сфера
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere { center: self.center(), radius: self.half_extents().norm() }
+    }
+
+    // Метод плит: луч входит в параллелепипед, когда войдёт во все три слоя.
+    // Из точки внутри находится выход
+    pub fn ray_intersect(&self, ray: &Ray) -> Option<RayHit> {
+        let (mut t_near, mut t_far) = (f64::NEG_INFINITY, f64::INFINITY);
+        let (mut near_axis, mut far_axis) = (0, 0);
+        for k in 0..3 {
+            if ray.direction[k] == 0.0 {
+                if ray.origin[k] < self.min[k] || ray.origin[k] > self.max[k] {
+                    return None;
+                }
+                continue;
+            }
+            let t1 = (self.min[k] - ray.origin[k]) / ray.direction[k];
+            let t2 = (self.max[k] - ray.origin[k]) / ray.direction[k];
+            let (t1, t2) = (t1.min(t2), t1.max(t2));
+            if t1 > t_near {
+                t_near = t1;
+                near_axis = k;
+            }
+            if t2 < t_far {
+                t_far = t2;
+                far_axis = k;
+            }
+        }
+        if t_near > t_far || t_far < RAY_EPSILON {
+            return None;
+        }
+        let (distance, axis) = match t_near >= RAY_EPSILON {
+            true => (t_near, near_axis),
+            false => (t_far, far_axis),
+        };
+
+        let point = ray.at(distance);
+        let mut normal = Point3::zeros();
+        normal[axis] = match point[axis] - self.center()[axis] >= 0.0 {
+            true => 1.0,
+            false => -1.0,
+        };
+        let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+        let size = self.size();
+        let face = |k: usize| if size[k] > 0.0 { (point[k] - self.min[k]) / size[k] } else { 0.5 };
+        Some(RayHit { distance, normal, uv: [face(a), face(b)], triangle: None })
+    }
+}
+
+// Ориентированный параллелепипед: полуразмеры по осям rotation
+#[derive(Debug, Clone, Copy)]
+pub struct Obb {
+    pub center: Point3,
+    pub half_extents: Point3,
+    pub rotation: Rotation3<f64>,
+}
+
+impl Obb {
+    pub fn corners(&self) -> [Point3; 8] {
+        std::array::from_fn(|i| {
+            let sign = |bit: usize| if i & (1 << bit) == 0 { -1.0 } else { 1.0 };
+            let local = Point3::new(sign(0), sign(1), sign(2)).component_mul(&self.half_extents);
+            self.center + self.rotation * local
+        })
+    }
+
+    // Наименьший выровненный по осям параллелепипед, содержащий этот
+    pub fn aabb(&self) -> Aabb {
+        let half = self.rotation.matrix().abs() * self.half_extents;
+        Aabb { min: self.center - half, max: self.center + half }
+    }
+
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere { center: self.center, radius: self.half_extents.norm() }
+    }
+
+    pub fn contains(&self, p: &Point3) -> bool {
+        let local = self.rotation.inverse() * (p - self.center);
+        (0..3).all(|k| local[k].abs() <= self.half_extents[k])
+    }
+
+    pub fn ray_intersect(&self, ray: &Ray) -> Option<RayHit> {
+        let inverse = self.rotation.inverse();
+        let local = Ray::new(inverse * (ray.origin - self.center), inverse * ray.direction);
+        let hit = Aabb::new(-self.half_extents, self.half_extents).ray_intersect(&local)?;
+        Some(RayHit { normal: self.rotation * hit.normal, ..hit })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Point3,
+    pub radius: f64,
+}
+
+impl BoundingSphere {
+    pub fn new(center: Point3, radius: f64) -> Self {
+        Self { center, radius }
+    }
+
+    // Сфера Риттера: не минимальная (больше не более чем на ~5 %), но строится за два прохода.
+    // None для пустого набора точек
+    pub fn from_points(points: &[Point3]) -> Option<Self> {
+        let first = points.first()?;
+        let farthest = |from: &Point3| *points.iter()
+            .max_by(|a, b| (*a - from).norm_squared().total_cmp(&(*b - from).norm_squared()))
+            .unwrap();
+        let a = farthest(first);
+        let b = farthest(&a);
+        let mut sphere = Self { center: (a + b) / 2.0, radius: (b - a).norm() / 2.0 };
+        for p in points {
+            let distance = (p - sphere.center).norm();
+            if distance > sphere.radius {
+                // сфера растёт ровно настолько, чтобы захватить точку
+                let radius = (sphere.radius + distance) / 2.0;
+                sphere.center += (p - sphere.center) * ((radius - sphere.radius) / distance);
+                sphere.radius = radius;
+            }
+        }
+        Some(sphere)
+    }
+
+    pub fn contains(&self, p: &Point3) -> bool {
+        (p - self.center).norm_squared() <= self.radius * self.radius
+    }
+
+    pub fn intersects(&self, other: &BoundingSphere) -> bool {
+        (other.center - self.center).norm() <= self.radius + other.radius
+    }
+
+    // Из точки внутри находится выход
+    pub fn ray_intersect(&self, ray: &Ray) -> Option<RayHit> {
+        let oc = ray.origin - self.center;
+        let a = ray.direction.norm_squared();
+        if a == 0.0 {
+            return None;
+        }
+        let half_b = oc.dot(&ray.direction);
+        let c = oc.norm_squared() - self.radius * self.radius;
+        let discriminant = half_b * half_b - a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let distance = [(-half_b - root) / a, (-half_b + root) / a]
+            .into_iter()
+            .find(|&t| t >= RAY_EPSILON)?;
+
+        let normal = (ray.at(distance) - self.center).try_normalize(0.0).unwrap_or(Point3::z());
+        Some(RayHit { distance, normal, uv: sphere_uv(&normal), triangle: None })
+    }
+}
+
+// Пересечение с треугольником по Мёллеру - Трумбору, с обеих сторон.
+// Возвращает t и барицентрические координаты (u, v) точки a + u (b - a) + v (c - a)
+pub fn ray_triangle(ray: &Ray, [a, b, c]: [&Point3; 3]) -> Option<(f64, f64, f64)> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = ray.direction.cross(&edge2);
+    let det = edge1.dot(&p);
+    if det.abs() < f64::EPSILON * edge1.norm() * edge2.norm() * ray.direction.norm() {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let s = ray.origin - a;
+    let u = s.dot(&p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(&edge1);
+    let v = ray.direction.dot(&q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = edge2.dot(&q) * inv_det;
+    (t >= RAY_EPSILON).then_some((t, u, v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::random::Rng;
+
+    const TRIANGLE: [Point3; 3] = [
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+    ];
+
+    fn hit_triangle(origin: Point3, direction: Point3) -> Option<(f64, f64, f64)> {
+        let [a, b, c] = &TRIANGLE;
+        ray_triangle(&Ray::new(origin, direction), [a, b, c])
+    }
+
+    fn assert_close(a: &Point3, b: &Point3) {
+        assert!((a - b).norm() < 1e-12, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn ray_triangle_hits_from_both_sides_and_misses() {
+        let down = Point3::new(0.0, 0.0, -1.0);
+        let (t, u, v) = hit_triangle(Point3::new(0.25, 0.5, 1.0), down).unwrap();
+        assert!((t - 1.0).abs() < 1e-12 && (u - 0.25).abs() < 1e-12 && (v - 0.5).abs() < 1e-12);
+        // нестандартная длина direction меняет t, но не точку попадания
+        let (t, _, _) = hit_triangle(Point3::new(0.25, 0.5, 1.0), down * 4.0).unwrap();
+        assert!((t - 0.25).abs() < 1e-12);
+        // снизу
+        assert!(hit_triangle(Point3::new(0.25, 0.5, -1.0), -down).is_some());
+
+        // мимо треугольника и треугольник позади луча
+        assert!(hit_triangle(Point3::new(0.8, 0.8, 1.0), down).is_none());
+        assert!(hit_triangle(Point3::new(0.25, 0.5, 1.0), -down).is_none());
+    }
+
+    // Рёбра и вершины принадлежат треугольнику, чуть дальше - уже нет
+    #[test]
+    fn ray_triangle_grazing_edges() {
+        let down = Point3::new(0.0, 0.0, -1.0);
+        for p in [Point3::new(0.5, 0.5, 1.0), Point3::new(0.5, 0.0, 1.0), Point3::new(0.0, 0.5, 1.0), Point3::new(0.0, 0.0, 1.0), Point3::new(1.0, 0.0, 1.0)] {
+            assert!(hit_triangle(p, down).is_some(), "{:?}", p);
+        }
+        for p in [Point3::new(0.5 + 1e-9, 0.5, 1.0), Point3::new(0.5, -1e-9, 1.0), Point3::new(-1e-9, 0.5, 1.0)] {
+            assert!(hit_triangle(p, down).is_none(), "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn ray_parallel_to_triangle_misses() {
+        let along = Point3::new(1.0, 0.0, 0.0);
+        assert!(hit_triangle(Point3::new(-1.0, 0.25, 0.0), along).is_none());
+        assert!(hit_triangle(Point3::new(-1.0, 0.25, 1.0), along).is_none());
+    }
+
+    #[test]
+    fn aabb_slab_intersection() {
+        let aabb = Aabb::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0));
+        let x = Point3::new(1.0, 0.0, 0.0);
+
+        let hit = aabb.ray_intersect(&Ray::new(Point3::new(-3.0, 0.5, 0.0), x)).unwrap();
+        assert!((hit.distance - 2.0).abs() < 1e-12);
+        assert_close(&hit.normal, &Point3::new(-1.0, 0.0, 0.0));
+        assert!((hit.uv[0] - 0.75).abs() < 1e-12 && (hit.uv[1] - 0.5).abs() < 1e-12);
+
+        // мимо, от параллелепипеда и параллельно грани вне слоя
+        assert!(aabb.ray_intersect(&Ray::new(Point3::new(-3.0, 0.0, 0.0), Point3::new(1.0, 2.0, 0.0))).is_none());
+        assert!(aabb.ray_intersect(&Ray::new(Point3::new(-3.0, 0.0, 0.0), -x)).is_none());
+        assert!(aabb.ray_intersect(&Ray::new(Point3::new(-3.0, 1.5, 0.0), x)).is_none());
+    }
+
+    #[test]
+    fn ray_from_inside_aabb_finds_the_exit() {
+        let aabb = Aabb::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 2.0));
+        let hit = aabb.ray_intersect(&Ray::new(Point3::zeros(), Point3::new(0.0, 0.0, 1.0))).unwrap();
+        assert!((hit.distance - 2.0).abs() < 1e-12);
+        assert_close(&hit.normal, &Point3::new(0.0, 0.0, 1.0));
+    }
+
+    // Квадрат 2 x 2, повёрнутый на 45 градусов вокруг z, - ромб |x| + |y| <= √2
+    #[test]
+    fn obb_slab_intersection() {
+        let obb = Obb {
+            center: Point3::zeros(),
+            half_extents: Point3::new(1.0, 1.0, 1.0),
+            rotation: Rotation3::from_euler_angles(0.0, 0.0, std::f64::consts::FRAC_PI_4),
+        };
+        let hit = obb.ray_intersect(&Ray::new(Point3::new(-3.0, 0.3, 0.0), Point3::new(1.0, 0.0, 0.0))).unwrap();
+        assert!((hit.distance - (3.0 - (2f64.sqrt() - 0.3))).abs() < 1e-12);
+        assert_close(&hit.normal, &(Point3::new(-1.0, 1.0, 0.0) / 2f64.sqrt()));
+        assert!(obb.ray_intersect(&Ray::new(Point3::new(-3.0, 1.5, 0.0), Point3::new(1.0, 0.0, 0.0))).is_none());
+
+        let exit = obb.ray_intersect(&Ray::new(Point3::zeros(), Point3::new(0.0, 0.0, -1.0))).unwrap();
+        assert!((exit.distance - 1.0).abs() < 1e-12);
+        assert_close(&exit.normal, &Point3::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn ritter_sphere_contains_all_points() {
+        let mut rng = Rng::new(11);
+        // вытянутое облако со смещённым центром
+        let points: Vec<Point3> = (0..2000)
+            .map(|_| Point3::new(rng.normal(3.0, 4.0), rng.normal(-1.0, 1.0), rng.normal(0.0, 0.2)))
+            .collect();
+        let sphere = BoundingSphere::from_points(&points).unwrap();
+        for p in &points {
+            assert!((p - sphere.center).norm() <= sphere.radius * (1.0 + 1e-12));
+        }
+        assert!(BoundingSphere::from_points(&[]).is_none());
+
+        // точки единичной сферы: сфера Риттера не больше минимальной более чем на 5 %
+        let on_sphere: Vec<Point3> = (0..2000)
+            .map(|_| Point3::new(rng.normal(0.0, 1.0), rng.normal(0.0, 1.0), rng.normal(0.0, 1.0)).normalize())
+            .collect();
+        let sphere = BoundingSphere::from_points(&on_sphere).unwrap();
+        assert!(sphere.radius >= 1.0 - 1e-12 && sphere.radius < 1.05);
+    }
+
+    // Вершины повёрнутого и сдвинутого параллелепипеда: после обратного поворота Obb
+    // совпадает с исходным Aabb, а Obb::aabb - с Aabb повёрнутых вершин
+    #[test]
+    fn obb_of_rotated_cube_matches_its_aabb() {
+        let cube = Aabb::new(Point3::new(-1.0, -2.0, -3.0), Point3::new(1.0, 2.0, 3.0));
+        let rotation = Rotation3::from_euler_angles(0.3, -0.5, 1.1);
+        let center = Point3::new(4.0, -2.0, 0.5);
+        let corners: Vec<Point3> = (0..8)
+            .map(|i| {
+                let pick = |bit: usize, k: usize| if i & (1 << bit) == 0 { cube.min[k] } else { cube.max[k] };
+                center + rotation * Point3::new(pick(0, 0), pick(1, 1), pick(2, 2))
+            })
+            .collect();
+        let obb = Obb { center, half_extents: cube.half_extents(), rotation };
+
+        assert!(corners.iter().all(|p| obb.contains(&(center + (p - center) * (1.0 - 1e-9)))));
+        let unrotated: Vec<Point3> = obb.corners().iter().map(|p| rotation.inverse() * (p - center)).collect();
+        let unrotated = Aabb::from_points(&unrotated).unwrap();
+        assert_close(&unrotated.min, &cube.min);
+        assert_close(&unrotated.max, &cube.max);
+
+        let aabb = Aabb::from_points(&corners).unwrap();
+        assert_close(&obb.aabb().min, &aabb.min);
+        assert_close(&obb.aabb().max, &aabb.max);
+    }
+}
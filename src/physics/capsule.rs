@@ -0,0 +1,60 @@
+use std::f64::consts::FRAC_PI_2;
+
+use crate::error::Result;
+use crate::physics::geometry::{Geometry, Mesh, MBV};
+use crate::physics::revolution::{ProfilePoint, revolve};
+
+const DEFAULT_SEGMENTS: usize = 48;
+const DEFAULT_RINGS: usize = 12;
+
+// Капсула вдоль оси Z: цилиндр высотой height, закрытый полусферами радиуса radius.
+// rings - число колец на каждую полусферу
+pub struct Capsule {
+    pub radius: f64,
+    pub height: f64,
+    pub segments: usize,
+    pub rings: usize,
+}
+
+impl Capsule {
+    pub fn new(radius: f64, height: f64) -> Self {
+        Self { radius, height, segments: DEFAULT_SEGMENTS, rings: DEFAULT_RINGS }
+    }
+
+    fn build_surface_mesh(&self) -> Mesh {
+        let rings = self.rings.max(2);
+        let half = self.height / 2.0;
+        let hemisphere = |center: f64, from: f64| (0..=rings).map(move |k| {
+            let psi = from - FRAC_PI_2 * k as f64 / rings as f64;
+            let (sin, cos) = psi.sin_cos();
+            ProfilePoint::new(self.radius * cos, center + self.radius * sin, (cos, sin))
+        });
+        // экватор верхней полусферы переходит в цилиндр без острого ребра
+        let profile: Vec<ProfilePoint> = hemisphere(half, FRAC_PI_2)
+            .chain(hemisphere(-half, 0.0))
+            .map(|mut p| {
+                // cos(pi / 2) не равен нулю точно, а полюсы должны лежать на оси
+                if p.r.abs() < 1e-12 * self.radius {
+                    p.r = 0.0;
+                }
+                p
+            })
+            .collect();
+        revolve(&profile, self.segments)
+    }
+}
+
+impl Geometry for Capsule {
+    fn get_surface_mesh(&self) -> Result<Mesh> {
+        Ok(self.build_surface_mesh())
+    }
+
+    fn get_edges_mesh(&self, bold: f32) -> Result<Mesh> {
+        self.build_surface_mesh().edges_along_normals(bold)
+    }
+
+    fn minimal_bounding_volume(&self) -> MBV {
+        let side = 2.0 * self.radius;
+        MBV(side, side, self.height + side)
+    }
+}
@@ -0,0 +1,181 @@
+use rayon::prelude::*;
+
+use crate::physics::geometry::{PARALLEL_MIN_LEN, Point3};
+use crate::physics::orbit::OrbitalElements;
+use crate::physics::random::Rng;
+
+// Дальше этого расстояния (а.е.) летучие вещества почти не испаряются, хвоста нет
+const ACTIVITY_RADIUS: f64 = 5.0;
+// Максимальный возраст пылинок хвоста в годах
+const MAX_DUST_AGE: f64 = 0.15;
+// Отношение давления излучения к притяжению Солнца для пылинок
+const MIN_BETA: f64 = 0.05;
+const MAX_BETA: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TailKind {
+    Ion,
+    Dust,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TailParticle {
+    pub position: Point3,
+    pub kind: TailKind,
+    // яркость от 0 до 1
+    pub intensity: f64,
+}
+
+// Параметры одной частицы хвоста: фиксированы, чтобы хвост не мерцал от кадра к кадру
+#[derive(Debug, Clone, Copy)]
+struct TailSeed {
+    kind: TailKind,
+    // для ионного хвоста: доля длины; для пылевого: доля максимального возраста
+    along: f64,
+    beta: f64,
+    jitter: Point3,
+}
+
+pub struct Comet {
+    pub name: String,
+    pub elements: OrbitalElements,
+    // длина ионного хвоста (а.е.) на расстоянии 1 а.е. от Солнца
+    pub ion_tail_length: f64,
+    seeds: Vec<TailSeed>,
+}
+
+// Активность ядра: растёт как 1/r^2 при приближении к перигелию и гаснет за ACTIVITY_RADIUS
+pub fn activity(distance: f64) -> f64 {
+    if distance >= ACTIVITY_RADIUS {
+        return 0.0;
+    }
+    let fade = 1.0 - distance / ACTIVITY_RADIUS;
+    (fade / (distance * distance)).min(10.0)
+}
+
+impl Comet {
+    pub fn new(name: String, elements: OrbitalElements, ion_tail_length: f64, tail_particles: usize, seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let seeds = (0..tail_particles).map(|i| {
+            let kind = if i % 2 == 0 { TailKind::Ion } else { TailKind::Dust };
+            TailSeed {
+                kind,
+                along: rng.uniform(),
+                beta: rng.range(MIN_BETA, MAX_BETA),
+                jitter: Point3::new(rng.normal(0.0, 1.0), rng.normal(0.0, 1.0), rng.normal(0.0, 1.0)),
+            }
+        }).collect();
+        Self { name, elements, ion_tail_length, seeds }
+    }
+
+    pub fn tail_capacity(&self) -> usize {
+        self.seeds.len()
+    }
+
+    pub fn position_at(&self, mu: f64, t: f64) -> Point3 {
+        self.elements.state_at(mu, t).0
+    }
+
+    // Частицы хвоста в момент t. Ионный хвост направлен строго от Солнца,
+    // пылевой изгибается назад по орбите: пылинка, вылетевшая age лет назад,
+    // движется с уменьшенным притяжением (1 - beta) mu
+    pub fn tail_at(&self, mu: f64, t: f64) -> Vec<TailParticle> {
+        let (nucleus, _) = self.elements.state_at(mu, t);
+        let distance = nucleus.magnitude();
+        let current_activity = activity(distance);
+        if current_activity == 0.0 {
+            return Vec::new();
+        }
+        let anti_sun = nucleus / distance;
+        let ion_length = self.ion_tail_length * current_activity;
+
+        self.seeds.par_iter().with_min_len(PARALLEL_MIN_LEN).filter_map(|seed| match seed.kind {
+            TailKind::Ion => {
+                let offset = anti_sun * (ion_length * seed.along) + seed.jitter * (0.01 * ion_length * seed.along);
+                Some(TailParticle {
+                    position: nucleus + offset,
+                    kind: TailKind::Ion,
+                    intensity: (1.0 - seed.along) * (current_activity / 2.0).min(1.0),
+                })
+            }
+            TailKind::Dust => {
+                let age = seed.along * MAX_DUST_AGE;
+                let (emitted_at, velocity) = self.elements.state_at(mu, t - age);
+                let r = emitted_at.magnitude();
+                let emission_activity = activity(r);
+                if emission_activity == 0.0 {
+                    return None;
+                }
+                let reduced_mu = (1.0 - seed.beta) * mu;
+                let drifted = if reduced_mu > 0.0 {
+                    OrbitalElements::from_state(&emitted_at, &velocity, reduced_mu).state_at(reduced_mu, age).0
+                } else {
+                    emitted_at + velocity * age
+                };
+                let spread = seed.jitter * (0.02 * age * velocity.magnitude());
+                Some(TailParticle {
+                    position: drifted + spread,
+                    kind: TailKind::Dust,
+                    intensity: (1.0 - seed.along) * (emission_activity / 2.0).min(1.0),
+                })
+            }
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::orbit::MU_SUN;
+
+    // в перигелии при t = 0; наклонная орбита, чтобы хвост не лежал в плоскости XY
+    fn comet_at(perihelion: f64) -> Comet {
+        let elements = OrbitalElements::from_perihelion(perihelion, 0.9, 0.5, 1.0, 2.0, 0.0, MU_SUN);
+        Comet::new("test".into(), elements, 0.1, 400, 3)
+    }
+
+    fn ion_tail_length(comet: &Comet, t: f64) -> f64 {
+        let nucleus = comet.position_at(MU_SUN, t);
+        comet.tail_at(MU_SUN, t).iter()
+            .filter(|p| p.kind == TailKind::Ion)
+            .map(|p| (p.position - nucleus).magnitude())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn tail_points_away_from_the_sun() {
+        let comet = comet_at(0.6);
+        let nucleus = comet.position_at(MU_SUN, 0.0);
+        let anti_sun = nucleus.normalize();
+        let tail = comet.tail_at(MU_SUN, 0.0);
+        assert_eq!(tail.len(), comet.tail_capacity());
+
+        // ионный хвост направлен от Солнца с разбросом в 1% длины
+        for particle in tail.iter().filter(|p| p.kind == TailKind::Ion) {
+            let offset = particle.position - nucleus;
+            assert!(offset.dot(&anti_sun) >= 0.95 * offset.magnitude());
+        }
+        // давление излучения уносит пыль дальше от Солнца, чем ядро
+        let dust: Vec<&TailParticle> = tail.iter().filter(|p| p.kind == TailKind::Dust).collect();
+        let outward = dust.iter().filter(|p| p.position.magnitude() > nucleus.magnitude()).count();
+        assert!(outward > dust.len() * 3 / 4);
+    }
+
+    #[test]
+    fn activity_and_tail_grow_towards_perihelion() {
+        assert!(activity(0.5) > activity(1.0));
+        assert!(activity(1.0) > activity(3.0));
+        assert_eq!(activity(ACTIVITY_RADIUS), 0.0);
+
+        let comet = comet_at(0.6);
+        let lengths: Vec<f64> = [-1.0, -0.5, -0.2, 0.0].iter().map(|&t| ion_tail_length(&comet, t)).collect();
+        assert!(lengths.windows(2).all(|w| w[0] < w[1]), "{:?}", lengths);
+
+        // ближе перигелий - длиннее хвост в перигелии
+        assert!(ion_tail_length(&comet_at(0.4), 0.0) > ion_tail_length(&comet_at(1.0), 0.0));
+
+        // вдали от Солнца хвоста нет
+        let far = Comet::new("far".into(), OrbitalElements::from_perihelion(6.0, 0.1, 0.0, 0.0, 0.0, 0.0, MU_SUN), 0.1, 50, 1);
+        assert!(far.tail_at(MU_SUN, 0.0).is_empty());
+    }
+}
@@ -0,0 +1,45 @@
+use crate::error::Result;
+use crate::physics::geometry::{Geometry, Mesh, MBV};
+use crate::physics::revolution::{ProfilePoint, revolve};
+
+const DEFAULT_SEGMENTS: usize = 48;
+
+// Конус с основанием вдоль оси Z: вершина на height / 2, основание на -height / 2
+pub struct Cone {
+    pub radius: f64,
+    pub height: f64,
+    pub segments: usize,
+}
+
+impl Cone {
+    pub fn new(radius: f64, height: f64) -> Self {
+        Self { radius, height, segments: DEFAULT_SEGMENTS }
+    }
+
+    fn build_surface_mesh(&self) -> Mesh {
+        let (r, top, bottom) = (self.radius, self.height / 2.0, -self.height / 2.0);
+        // нормаль боковой поверхности перпендикулярна образующей (r, -height)
+        let side = (self.height, self.radius);
+        revolve(&[
+            ProfilePoint::new(0.0, top, side),
+            ProfilePoint::new(r, bottom, side),
+            ProfilePoint::new(r, bottom, (0.0, -1.0)),
+            ProfilePoint::new(0.0, bottom, (0.0, -1.0)),
+        ], self.segments)
+    }
+}
+
+impl Geometry for Cone {
+    fn get_surface_mesh(&self) -> Result<Mesh> {
+        Ok(self.build_surface_mesh())
+    }
+
+    fn get_edges_mesh(&self, bold: f32) -> Result<Mesh> {
+        self.build_surface_mesh().edges_along_normals(bold)
+    }
+
+    fn minimal_bounding_volume(&self) -> MBV {
+        let side = 2.0 * self.radius;
+        MBV(side, side, self.height)
+    }
+}
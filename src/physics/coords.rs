@@ -1,4 +1,12 @@
-use std::ops::{Add, Sub, Mul};
+use std::f64::consts::PI;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use nalgebra::{Matrix3, Rotation3, Vector3};
+
+use crate::physics::geometry::Point3;
+
+const GEODETIC_TOLERANCE: f64 = 1e-12;
+const GEODETIC_MAX_ITERATIONS: usize = 20;
 
 #[derive(Debug, Clone)]
 pub struct Coord {
@@ -7,19 +15,36 @@ pub struct Coord {
     pub y: f64,
     pub z: f64,
 
-    // spherical
-    pub r: f64,
-    pub azimuth: f64,
-    pub elevation: f64,
+    // spherical: у ленивых координат поля равны NaN до вызова ensure_spherical,
+    // поэтому снаружи они читаются только через r(), azimuth(), elevation()
+    r: f64,
+    azimuth: f64,
+    elevation: f64,
+
+    lazy: bool,
+}
+
+fn spherical_from_cartesian(x: f64, y: f64, z: f64) -> (f64, f64, f64) {
+    let r = (x * x + y * y + z * z).sqrt();
+    let azimuth = if x == 0.0 && y == 0.0 { 0.0 } else { y.atan2(x) };
+    let elevation = if r == 0.0 { 0.0 } else { (z / r).asin() };
+    (r, azimuth, elevation)
 }
 
 impl Coord {
     pub fn new_cartesian(x: f64, y: f64, z: f64) -> Self {
-        let r = (x * x + y * y + z * z).sqrt();
-        let azimuth = if x == 0.0 && y == 0.0 { 0.0 } else { y.atan2(x) };
-        let elevation = if r == 0.0 { 0.0 } else { (z / r).asin() };
+        let (r, azimuth, elevation) = spherical_from_cartesian(x, y, z);
+        Self { x, y, z, r, azimuth, elevation, lazy: false }
+    }
 
-        Self { x, y, z, r, azimuth, elevation }
+    // Сферические компоненты не вычисляются, пока не понадобятся: для горячих циклов,
+    // где нужна только векторная алгебра. Результаты операций над ленивыми координатами тоже ленивые
+    pub fn new_cartesian_lazy(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z, r: f64::NAN, azimuth: f64::NAN, elevation: f64::NAN, lazy: true }
+    }
+
+    fn with_mode(x: f64, y: f64, z: f64, lazy: bool) -> Self {
+        if lazy { Self::new_cartesian_lazy(x, y, z) } else { Self::new_cartesian(x, y, z) }
     }
 
     pub fn new_spherical(r: f64, azimuth: f64, elevation: f64) -> Self {
@@ -32,20 +57,15 @@ impl Coord {
         let y = r * cos_elev * sin_azim;
         let z = r * sin_elev;
 
-        Self { x, y, z, r, azimuth, elevation }
+        Self { x, y, z, r, azimuth, elevation, lazy: false }
     }
 
     pub fn set_cartesian(&mut self, x: f64, y: f64, z: f64) {
-        self.x = x;
-        self.y = y;
-        self.z = z;
-
-        self.r = (x * x + y * y + z * z).sqrt();
-        self.azimuth = if x == 0.0 && y == 0.0 { 0.0 } else { y.atan2(x) };
-        self.elevation = if self.r == 0.0 { 0.0 } else { (z / self.r).asin() };
+        *self = Self::with_mode(x, y, z, self.lazy);
     }
 
     pub fn set_spherical(&mut self, r: f64, azimuth: f64, elevation: f64) {
+        self.lazy = false;
         self.r = r;
         self.azimuth = azimuth;
         self.elevation = elevation;
@@ -60,6 +80,60 @@ impl Coord {
         self.z = r * sin_elev;
     }
 
+    // Цилиндрические координаты: расстояние до оси Z, азимут, высота над плоскостью XY
+    pub fn new_cylindrical(rho: f64, azimuth: f64, z: f64) -> Self {
+        Self::new_cartesian(rho * azimuth.cos(), rho * azimuth.sin(), z)
+    }
+
+    // Экваториальные координаты: прямое восхождение и склонение в экваториальной системе
+    pub fn new_ra_dec(r: f64, right_ascension: f64, declination: f64) -> Self {
+        Self::new_spherical(r, right_ascension, declination)
+    }
+
+    // Горизонтальные координаты в системе восток-север-зенит;
+    // азимут отсчитывается от севера через восток
+    pub fn new_alt_az(r: f64, altitude: f64, azimuth: f64) -> Self {
+        Self::new_spherical(r, PI / 2.0 - azimuth, altitude)
+    }
+
+    // Геодезические широта, долгота и высота над эллипсоидом в системе, связанной с телом
+    pub fn new_geodetic(ellipsoid: &Ellipsoid, latitude: f64, longitude: f64, altitude: f64) -> Self {
+        let n = ellipsoid.prime_vertical_radius(latitude);
+        let e2 = ellipsoid.eccentricity_squared();
+        let p = (n + altitude) * latitude.cos();
+        Self::new_cartesian(
+            p * longitude.cos(),
+            p * longitude.sin(),
+            (n * (1.0 - e2) + altitude) * latitude.sin(),
+        )
+    }
+
+    /* ---------- Ленивые сферические координаты ---------------- */
+
+    pub fn is_lazy(&self) -> bool {
+        self.lazy
+    }
+
+    // Вычисляет и сохраняет сферические компоненты; после этого координаты не ленивые
+    pub fn ensure_spherical(&mut self) {
+        if self.lazy {
+            (self.r, self.azimuth, self.elevation) = spherical_from_cartesian(self.x, self.y, self.z);
+            self.lazy = false;
+        }
+    }
+
+    pub fn r(&self) -> f64 {
+        if self.lazy { self.norm() } else { self.r }
+    }
+
+    pub fn azimuth(&self) -> f64 {
+        if self.lazy { spherical_from_cartesian(self.x, self.y, self.z).1 } else { self.azimuth }
+    }
+
+    pub fn elevation(&self) -> f64 {
+        if self.lazy { spherical_from_cartesian(self.x, self.y, self.z).2 } else { self.elevation }
+    }
+
     /* ---------- Утилиты ---------------- */
 
     pub fn cartesian(&self) -> (f64, f64, f64) {
@@ -67,7 +141,167 @@ impl Coord {
     }
 
     pub fn spherical(&self) -> (f64, f64, f64) {
-        (self.r, self.azimuth, self.elevation)
+        if self.lazy { spherical_from_cartesian(self.x, self.y, self.z) } else { (self.r, self.azimuth, self.elevation) }
+    }
+
+    pub fn cylindrical(&self) -> (f64, f64, f64) {
+        ((self.x * self.x + self.y * self.y).sqrt(), self.azimuth(), self.z)
+    }
+
+    // (прямое восхождение в [0, 2π), склонение) для координат в экваториальной системе
+    pub fn ra_dec(&self) -> (f64, f64) {
+        (self.azimuth().rem_euclid(2.0 * PI), self.elevation())
+    }
+
+    // (высота, азимут от севера через восток в [0, 2π)) для координат в горизонтальной системе
+    pub fn alt_az(&self) -> (f64, f64) {
+        (self.elevation(), self.x.atan2(self.y).rem_euclid(2.0 * PI))
+    }
+
+    /* ---------- Векторная алгебра ---------------- */
+
+    pub fn dot(&self, rhs: &Coord) -> f64 {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+
+    pub fn cross(&self, rhs: &Coord) -> Coord {
+        Self::with_mode(
+            self.y * rhs.z - self.z * rhs.y,
+            self.z * rhs.x - self.x * rhs.z,
+            self.x * rhs.y - self.y * rhs.x,
+            self.lazy || rhs.lazy,
+        )
+    }
+
+    pub fn norm_squared(&self) -> f64 {
+        self.dot(self)
+    }
+
+    pub fn norm(&self) -> f64 {
+        self.norm_squared().sqrt()
+    }
+
+    // Единичный вектор того же направления; нулевой вектор остаётся нулевым
+    pub fn normalize(&self) -> Coord {
+        let norm = self.norm();
+        if norm == 0.0 {
+            return self.clone();
+        }
+        Self::with_mode(self.x / norm, self.y / norm, self.z / norm, self.lazy)
+    }
+
+    // Сужение до f32 теряет точность, поэтому это явный метод, а не From
+    pub fn to_vec3(&self) -> glam::Vec3 {
+        glam::Vec3::new(self.x as f32, self.y as f32, self.z as f32)
+    }
+
+    // Итерационное обращение геодезических координат: (широта, долгота, высота)
+    pub fn geodetic(&self, ellipsoid: &Ellipsoid) -> (f64, f64, f64) {
+        let e2 = ellipsoid.eccentricity_squared();
+        let p = (self.x * self.x + self.y * self.y).sqrt();
+        let mut latitude = self.z.atan2(p * (1.0 - e2));
+        for _ in 0..GEODETIC_MAX_ITERATIONS {
+            let n = ellipsoid.prime_vertical_radius(latitude);
+            let next = (self.z + e2 * n * latitude.sin()).atan2(p);
+            let delta = (next - latitude).abs();
+            latitude = next;
+            if delta < GEODETIC_TOLERANCE {
+                break;
+            }
+        }
+        // формула без деления на cos(широты), пригодна и у полюсов
+        let altitude = p * latitude.cos() + self.z * latitude.sin()
+            - ellipsoid.equatorial_radius * ellipsoid.equatorial_radius / ellipsoid.prime_vertical_radius(latitude);
+        (latitude, self.azimuth(), altitude)
+    }
+
+    // Пересчёт из системы from в систему to; начала систем совпадают
+    pub fn to_frame(&self, from: &Frame, to: &Frame) -> Coord {
+        let mut coord = to.from_ecliptic(&from.to_ecliptic(self));
+        if !self.lazy {
+            coord.ensure_spherical();
+        }
+        coord
+    }
+}
+
+// Часовой угол в [-π, π) по местному звёздному времени и прямому восхождению
+pub fn hour_angle(local_sidereal_time: f64, right_ascension: f64) -> f64 {
+    (local_sidereal_time - right_ascension + PI).rem_euclid(2.0 * PI) - PI
+}
+
+// Эллипсоид вращения, задающий фигуру тела для геодезических координат
+#[derive(Debug, Clone, Copy)]
+pub struct Ellipsoid {
+    pub equatorial_radius: f64,
+    pub polar_radius: f64,
+}
+
+impl Ellipsoid {
+    pub fn new(equatorial_radius: f64, polar_radius: f64) -> Self {
+        Self { equatorial_radius, polar_radius }
+    }
+
+    pub fn flattening(&self) -> f64 {
+        1.0 - self.polar_radius / self.equatorial_radius
+    }
+
+    pub fn eccentricity_squared(&self) -> f64 {
+        let f = self.flattening();
+        f * (2.0 - f)
+    }
+
+    // Радиус кривизны первого вертикала на широте latitude
+    pub fn prime_vertical_radius(&self, latitude: f64) -> f64 {
+        let sin = latitude.sin();
+        self.equatorial_radius / (1.0 - self.eccentricity_squared() * sin * sin).sqrt()
+    }
+}
+
+// Системы координат. Все они повёрнуты относительно эклиптической системы сцены
+// (ось X - точка весеннего равноденствия, Z - полюс эклиптики) и различаются только поворотом,
+// поэтому положения перед пересчётом в топоцентрические системы переносятся к наблюдателю
+#[derive(Debug, Clone, Copy)]
+pub enum Frame {
+    Ecliptic,
+    // экватор тела наклонён к эклиптике на obliquity
+    Equatorial { obliquity: f64 },
+    // вращается вместе с телом: rotation - угол поворота нулевого меридиана от точки равноденствия
+    BodyFixed { obliquity: f64, rotation: f64 },
+    // восток-север-зенит для наблюдателя на широте latitude
+    Horizontal { obliquity: f64, latitude: f64, local_sidereal_time: f64 },
+}
+
+impl Frame {
+    // Поворот, переводящий эклиптические координаты в координаты этой системы
+    pub fn rotation_from_ecliptic(&self) -> Rotation3<f64> {
+        let equatorial = |obliquity: f64| Rotation3::from_axis_angle(&Vector3::x_axis(), obliquity);
+        let about_pole = |angle: f64| Rotation3::from_axis_angle(&Vector3::z_axis(), -angle);
+        match *self {
+            Frame::Ecliptic => Rotation3::identity(),
+            Frame::Equatorial { obliquity } => equatorial(obliquity),
+            Frame::BodyFixed { obliquity, rotation } => about_pole(rotation) * equatorial(obliquity),
+            Frame::Horizontal { obliquity, latitude, local_sidereal_time } => {
+                // строки: восток, север, зенит в системе часового угла
+                let (sin, cos) = latitude.sin_cos();
+                let horizon = Rotation3::from_matrix_unchecked(Matrix3::new(
+                    0.0, 1.0, 0.0,
+                    -sin, 0.0, cos,
+                    cos, 0.0, sin,
+                ));
+                horizon * about_pole(local_sidereal_time) * equatorial(obliquity)
+            }
+        }
+    }
+
+    pub fn from_ecliptic(&self, coord: &Coord) -> Coord {
+        let v = self.rotation_from_ecliptic() * Point3::from(coord);
+        Coord::with_mode(v.x, v.y, v.z, coord.lazy)
+    }
+
+    pub fn to_ecliptic(&self, coord: &Coord) -> Coord {
+        let v = self.rotation_from_ecliptic().inverse() * Point3::from(coord);
+        Coord::with_mode(v.x, v.y, v.z, coord.lazy)
     }
 }
 
@@ -80,7 +314,7 @@ impl Add for Coord {
         let x = self.x + rhs.x;
         let y = self.y + rhs.y;
         let z = self.z + rhs.z;
-        Self::new_cartesian(x, y, z)
+        Self::with_mode(x, y, z, self.lazy || rhs.lazy)
     }
 }
 
@@ -91,7 +325,7 @@ impl Sub for Coord {
         let x = self.x - rhs.x;
         let y = self.y - rhs.y;
         let z = self.z - rhs.z;
-        Self::new_cartesian(x, y, z)
+        Self::with_mode(x, y, z, self.lazy || rhs.lazy)
     }
 }
 
@@ -102,7 +336,109 @@ impl Mul<f64> for Coord {
         let x = self.x * rhs;
         let y = self.y * rhs;
         let z = self.z * rhs;
-        Coord::new_cartesian(x, y, z)
+        Coord::with_mode(x, y, z, self.lazy)
+    }
+}
+
+impl Mul<Coord> for f64 {
+    type Output = Coord;
+
+    fn mul(self, rhs: Coord) -> Self::Output {
+        rhs * self
+    }
+}
+
+impl Div<f64> for Coord {
+    type Output = Coord;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        let x = self.x / rhs;
+        let y = self.y / rhs;
+        let z = self.z / rhs;
+        Coord::with_mode(x, y, z, self.lazy)
+    }
+}
+
+impl Neg for Coord {
+    type Output = Coord;
+
+    // при смене знака сферические компоненты пересчитываются без тригонометрии
+    fn neg(self) -> Self::Output {
+        if self.lazy {
+            return Coord::new_cartesian_lazy(-self.x, -self.y, -self.z);
+        }
+        let azimuth = if self.x == 0.0 && self.y == 0.0 { 0.0 } else { (self.azimuth + 2.0 * PI).rem_euclid(2.0 * PI) - PI };
+        Coord { x: -self.x, y: -self.y, z: -self.z, r: self.r, azimuth, elevation: -self.elevation, lazy: false }
+    }
+}
+
+impl AddAssign for Coord {
+    fn add_assign(&mut self, rhs: Self) {
+        let lazy = self.lazy || rhs.lazy;
+        *self = Coord::with_mode(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z, lazy);
+    }
+}
+
+impl SubAssign for Coord {
+    fn sub_assign(&mut self, rhs: Self) {
+        let lazy = self.lazy || rhs.lazy;
+        *self = Coord::with_mode(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z, lazy);
+    }
+}
+
+impl MulAssign<f64> for Coord {
+    fn mul_assign(&mut self, rhs: f64) {
+        *self = Coord::with_mode(self.x * rhs, self.y * rhs, self.z * rhs, self.lazy);
+    }
+}
+
+impl DivAssign<f64> for Coord {
+    fn div_assign(&mut self, rhs: f64) {
+        *self = Coord::with_mode(self.x / rhs, self.y / rhs, self.z / rhs, self.lazy);
+    }
+}
+
+/* ---------- Преобразования типов ---------------- */
+
+impl From<Point3> for Coord {
+    fn from(v: Point3) -> Self {
+        Coord::new_cartesian(v.x, v.y, v.z)
+    }
+}
+
+impl From<&Coord> for Point3 {
+    fn from(c: &Coord) -> Self {
+        Point3::new(c.x, c.y, c.z)
+    }
+}
+
+impl From<Coord> for Point3 {
+    fn from(c: Coord) -> Self {
+        Point3::from(&c)
+    }
+}
+
+impl From<glam::DVec3> for Coord {
+    fn from(v: glam::DVec3) -> Self {
+        Coord::new_cartesian(v.x, v.y, v.z)
+    }
+}
+
+impl From<&Coord> for glam::DVec3 {
+    fn from(c: &Coord) -> Self {
+        glam::DVec3::new(c.x, c.y, c.z)
+    }
+}
+
+impl From<Coord> for glam::DVec3 {
+    fn from(c: Coord) -> Self {
+        glam::DVec3::from(&c)
+    }
+}
+
+impl From<glam::Vec3> for Coord {
+    fn from(v: glam::Vec3) -> Self {
+        Coord::new_cartesian(v.x as f64, v.y as f64, v.z as f64)
     }
 }
 
@@ -113,7 +449,155 @@ impl fmt::Display for Coord {
         write!(
             f,
             "Cartesian: ({:.3}, {:.3}, {:.3})\nSpherical: (r={:.3}, azimuth={:.3} rad, elevation={:.3} rad)",
-            self.x, self.y, self.z, self.r, self.azimuth, self.elevation
+            self.x, self.y, self.z, self.r(), self.azimuth(), self.elevation()
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WGS84: Ellipsoid = Ellipsoid { equatorial_radius: 6378.137, polar_radius: 6356.752314245 };
+    const OBLIQUITY: f64 = 23.439281 * PI / 180.0;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!((actual - expected).abs() < tolerance, "{} vs {}", actual, expected);
+    }
+
+    #[test]
+    fn geodetic_round_trip_at_the_poles_and_the_equator() {
+        for (latitude, longitude, altitude) in [
+            (PI / 2.0, 0.0, 0.0),
+            (-PI / 2.0, 0.0, 10.0),
+            (0.0, 0.0, 0.0),
+            (0.0, PI / 2.0, 5.0),
+            (0.0, -3.0, 100.0),
+            (0.8, 2.0, 1.5),
+        ] {
+            let ecef = Coord::new_geodetic(&WGS84, latitude, longitude, altitude);
+            let (lat, lon, alt) = ecef.geodetic(&WGS84);
+            assert_close(lat, latitude, 1e-12);
+            assert_close(alt, altitude, 1e-9);
+            if latitude.abs() < PI / 2.0 {
+                assert_close(lon, longitude, 1e-12);
+            }
+        }
+
+        // полюс лежит на малой полуоси, точка экватора - на большой
+        let pole = Coord::new_geodetic(&WGS84, PI / 2.0, 0.0, 0.0);
+        assert_close(pole.z, WGS84.polar_radius, 1e-9);
+        assert_close(pole.x.hypot(pole.y), 0.0, 1e-9);
+        let equator = Coord::new_geodetic(&WGS84, 0.0, PI / 2.0, 0.0);
+        assert_close(equator.y, WGS84.equatorial_radius, 1e-9);
+        assert_close(equator.z, 0.0, 1e-9);
+    }
+
+    #[test]
+    fn equatorial_ecliptic_round_trip() {
+        let (ecliptic, equatorial) = (Frame::Ecliptic, Frame::Equatorial { obliquity: OBLIQUITY });
+        let coord = Coord::new_spherical(2.5, 1.2, -0.4);
+        let back = coord.to_frame(&ecliptic, &equatorial).to_frame(&equatorial, &ecliptic);
+        assert_close(back.r(), 2.5, 1e-12);
+        assert_close(back.azimuth(), 1.2, 1e-12);
+        assert_close(back.elevation(), -0.4, 1e-12);
+
+        // точка равноденствия общая, полюс эклиптики имеет склонение 90° - наклон
+        let equinox = Coord::new_cartesian(1.0, 0.0, 0.0).to_frame(&ecliptic, &equatorial);
+        assert_close(equinox.ra_dec().0, 0.0, 1e-12);
+        assert_close(equinox.ra_dec().1, 0.0, 1e-12);
+        let pole = Coord::new_cartesian(0.0, 0.0, 1.0).to_frame(&ecliptic, &equatorial);
+        assert_close(pole.ra_dec().0, 1.5 * PI, 1e-12);
+        assert_close(pole.ra_dec().1, PI / 2.0 - OBLIQUITY, 1e-12);
+    }
+
+    // Пример из Duffett-Smith, "Practical Astronomy with your Calculator": часовой угол 5h51m44s,
+    // склонение +23°13'10" на широте 52° N дают высоту 19°20'04" и азимут 283°16'16"
+    #[test]
+    fn alt_az_of_a_known_star() {
+        let degrees = |d: f64, m: f64, s: f64| (d + m / 60.0 + s / 3600.0) * PI / 180.0;
+        let hour_angle_of_star = degrees(5.0, 51.0, 44.0) * 15.0;
+        let declination = degrees(23.0, 13.0, 10.0);
+        let local_sidereal_time = 1.3;
+        let right_ascension = local_sidereal_time - hour_angle_of_star;
+        assert_close(hour_angle(local_sidereal_time, right_ascension), hour_angle_of_star, 1e-12);
+
+        let equatorial = Frame::Equatorial { obliquity: OBLIQUITY };
+        let horizontal = Frame::Horizontal { obliquity: OBLIQUITY, latitude: degrees(52.0, 0.0, 0.0), local_sidereal_time };
+        let star = Coord::new_ra_dec(1.0, right_ascension, declination).to_frame(&equatorial, &horizontal);
+        let (altitude, azimuth) = star.alt_az();
+        assert_close(altitude, degrees(19.0, 20.0, 4.0), 1e-5);
+        assert_close(azimuth, degrees(283.0, 16.0, 16.0), 1e-5);
+
+        let back = Coord::new_alt_az(1.0, altitude, azimuth).to_frame(&horizontal, &equatorial);
+        assert_close(back.ra_dec().0, right_ascension.rem_euclid(2.0 * PI), 1e-12);
+        assert_close(back.ra_dec().1, declination, 1e-12);
+    }
+
+    // сохранённые сферические поля совпадают с пересчитанными из декартовых
+    fn assert_consistent(coord: &Coord) {
+        let (r, azimuth, elevation) = spherical_from_cartesian(coord.x, coord.y, coord.z);
+        assert_close(coord.r(), r, 1e-12);
+        assert_close(coord.azimuth(), azimuth, 1e-12);
+        assert_close(coord.elevation(), elevation, 1e-12);
+        if !coord.is_lazy() {
+            assert_eq!((coord.r, coord.azimuth, coord.elevation), (coord.r(), coord.azimuth(), coord.elevation()));
+        }
+    }
+
+    #[test]
+    fn spherical_fields_follow_arithmetic() {
+        let a = Coord::new_spherical(2.0, 0.7, 0.3);
+        let b = Coord::new_cartesian(-1.0, 0.5, 2.0);
+        let lazy = Coord::new_cartesian_lazy(0.2, -3.0, 1.0);
+
+        for coord in [a.clone() + b.clone(), a.clone() - b.clone(), a.clone() * 3.0, 0.5 * b.clone(), a.clone() / 4.0, -a.clone(), a.cross(&b)] {
+            assert!(!coord.is_lazy());
+            assert_consistent(&coord);
+        }
+
+        let mut assigned = a.clone();
+        assigned += b.clone();
+        assigned *= -2.0;
+        assigned -= a.clone();
+        assert_consistent(&assigned);
+
+        // ленивость заразна, а ensure_spherical заполняет поля
+        for mut coord in [a.clone() + lazy.clone(), lazy.clone() - b.clone(), lazy.clone() * 2.0, -lazy.clone(), lazy.normalize()] {
+            assert!(coord.is_lazy());
+            assert_consistent(&coord);
+            coord.ensure_spherical();
+            assert!(!coord.is_lazy());
+            assert_consistent(&coord);
+        }
+
+        let mut moved = a.clone();
+        moved.set_cartesian(0.0, 0.0, -1.0);
+        assert_consistent(&moved);
+        assert_close(moved.elevation(), -PI / 2.0, 1e-12);
+    }
+
+    #[test]
+    fn glam_and_nalgebra_round_trips_keep_spherical_fields() {
+        let coord = Coord::new_spherical(1.5, -2.0, 0.9);
+
+        let via_nalgebra = Coord::from(Point3::from(&coord));
+        assert_consistent(&via_nalgebra);
+        assert_close(via_nalgebra.azimuth(), -2.0, 1e-12);
+        assert_close(via_nalgebra.elevation(), 0.9, 1e-12);
+
+        let via_glam = Coord::from(glam::DVec3::from(coord.clone()));
+        assert_consistent(&via_glam);
+        assert_eq!(via_glam.cartesian(), coord.cartesian());
+
+        // из ленивых координат получаются обычные
+        let lazy = Coord::new_cartesian_lazy(1.0, 2.0, -2.0);
+        let from_lazy = Coord::from(glam::DVec3::from(&lazy));
+        assert!(!from_lazy.is_lazy());
+        assert_close(from_lazy.r, 3.0, 1e-12);
+
+        let single = Coord::from(coord.to_vec3());
+        assert_consistent(&single);
+        assert_close(single.r(), 1.5, 1e-6);
+    }
+}
@@ -1,5 +1,6 @@
 use std::f64::consts::PI;
-use crate::physics::geometry::{Geometry, Mesh, MBV, Point3};
+use crate::error::Result;
+use crate::physics::geometry::{Geometry, Mesh, MBV, Point3, check_bold};
 
 pub struct Cube {
     side_len: f64,
@@ -17,71 +18,64 @@ impl Cube {
         Self { side_len }
     }
     
+    // У каждой грани свои 4 вершины: нормали, UV и касательные на рёбрах куба разрывны
     fn build_surface_mesh(&self) -> Mesh {
         let scale = self.side_len / 2.0;
-        let vertices = [
-            // bottom
-            scale * vertex([-1, -1, -1]),
-            scale * vertex([1, -1, -1]),
-            scale * vertex([-1, 1, -1]),
-            scale * vertex([1, 1, -1]),
-            // top
-            scale * vertex([-1, -1, 1]),
-            scale * vertex([1, -1, 1]),
-            scale * vertex([-1, 1, 1]),
-            scale * vertex([1, 1, 1]),
+        // (нормаль, направление роста u); v растёт вдоль n x u, обход против часовой стрелки снаружи
+        let faces = [
+            (vertex([1, 0, 0]), vertex([0, 1, 0])),
+            (vertex([-1, 0, 0]), vertex([0, -1, 0])),
+            (vertex([0, 1, 0]), vertex([-1, 0, 0])),
+            (vertex([0, -1, 0]), vertex([1, 0, 0])),
+            (vertex([0, 0, 1]), vertex([1, 0, 0])),
+            (vertex([0, 0, -1]), vertex([-1, 0, 0])),
         ];
-        
-        let indices: &[[usize; 3]] = &[
-            [0, 1, 3], [0, 2, 3], // bottom (xy-plane)
-            [4, 5, 7], [4, 6, 7], // top (xy-plane + 1)
-            [0, 1, 5], [0, 4, 5], // (xz-plane)
-            [2, 3, 7], [2, 6, 7], // (xz-plane + 1)
-            [0, 2, 6], [0, 4, 6], // (yz-plane)
-            [1, 3, 7], [1, 5, 7], // (yz-plane + 1)
-        ];
-        
-        Mesh { vertices: vertices.to_vec(), indices: indices.to_vec() }
-    }
 
-    pub fn build_edges_mesh(&self, bold: f32) -> Mesh {
-        if bold <= 0.0 {
-            panic!("bold cannot be <= 0");
+        let mut mesh = Mesh::new(Vec::new(), Vec::new());
+        let (mut normals, mut uvs, mut tangents, mut bitangents) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for (normal, tangent) in faces {
+            let bitangent = normal.cross(&tangent);
+            let first = mesh.vertices.len();
+            for (u, v) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+                let corner = normal + tangent * (2.0 * u - 1.0) + bitangent * (2.0 * v - 1.0);
+                mesh.vertices.push(corner * scale);
+                normals.push(normal);
+                uvs.push([u, v]);
+                tangents.push(tangent);
+                bitangents.push(bitangent);
+            }
+            mesh.indices.push([first, first + 1, first + 2]);
+            mesh.indices.push([first, first + 2, first + 3]);
         }
+        mesh.normals = Some(normals);
+        mesh.uvs = Some(uvs);
+        mesh.tangents = Some(tangents);
+        mesh.bitangents = Some(bitangents);
+        mesh
+    }
 
-        let scale = self.side_len / 2.0;
-        let vertices = [
-            // bottom
-            scale * vertex([-1, -1, -1]),
-            scale * vertex([1, -1, -1]),
-            scale * vertex([-1, 1, -1]),
-            scale * vertex([1, 1, -1]),
-            // top
-            scale * vertex([-1, -1, 1]),
-            scale * vertex([1, -1, 1]),
-            scale * vertex([-1, 1, 1]),
-            scale * vertex([1, 1, 1]),
-        ];
-        
-        let indices: &[[usize; 3]] = &[
-            [0, 1, 3], [0, 2, 3], // bottom (xy-plane)
-            [4, 5, 7], [4, 6, 7], // top (xy-plane + 1)
-            [0, 1, 5], [0, 4, 5], // (xz-plane)
-            [2, 3, 7], [2, 6, 7], // (xz-plane + 1)
-            [0, 2, 6], [0, 4, 6], // (yz-plane)
-            [1, 3, 7], [1, 5, 7], // (yz-plane + 1)
-        ];
-        
-        Mesh { vertices: vertices.to_vec(), indices: indices.to_vec() }
+    // Полоски вдоль 12 рёбер куба, сдвинутые от центра наружу на bold
+    pub fn build_edges_mesh(&self, bold: f32) -> Result<Mesh> {
+        check_bold(bold)?;
+
+        let shift = |v: Point3| v + v.normalize() * bold as f64;
+        let mut mesh = Mesh::new(Vec::new(), Vec::new());
+        for [a, b] in self.get_wireframe()? {
+            let first = mesh.vertices.len();
+            mesh.vertices.extend([a, shift(a), b, shift(b)]);
+            mesh.indices.push([first, first + 1, first + 2]);
+            mesh.indices.push([first + 1, first + 3, first + 2]);
+        }
+        Ok(mesh)
     }
 }
 
 impl Geometry for Cube {
-    fn get_surface_mesh(&self) -> Mesh {
-        self.build_surface_mesh()
+    fn get_surface_mesh(&self) -> Result<Mesh> {
+        Ok(self.build_surface_mesh())
     }
 
-    fn get_edges_mesh(&self, bold: f32) -> Mesh {
+    fn get_edges_mesh(&self, bold: f32) -> Result<Mesh> {
         self.build_edges_mesh(bold)
     }
 
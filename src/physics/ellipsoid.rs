@@ -0,0 +1,75 @@
+use crate::error::Result;
+use crate::physics::ball::Ball;
+use crate::physics::coords;
+use crate::physics::geometry::{Geometry, Mesh, MBV, Point3};
+use crate::physics::bounds::BoundingSphere;
+
+const DEFAULT_SEGMENTS: usize = 64;
+const DEFAULT_RINGS: usize = 32;
+
+// Трёхосный эллипсоид с полуосями a, b, c вдоль X, Y, Z. Сплюснутые планеты (Юпитер, Сатурн)
+// - частный случай a = b > c, Хаумеа - трёхосная
+pub struct Ellipsoid {
+    pub radii: Point3,
+    pub segments: usize,
+    pub rings: usize,
+}
+
+fn normalize(v: Point3) -> Point3 {
+    v / v.magnitude()
+}
+
+impl Ellipsoid {
+    pub fn new(a: f64, b: f64, c: f64) -> Self {
+        Self { radii: Point3::new(a, b, c), segments: DEFAULT_SEGMENTS, rings: DEFAULT_RINGS }
+    }
+
+    // Эллипсоид вращения вокруг Z
+    pub fn oblate(equatorial_radius: f64, polar_radius: f64) -> Self {
+        Self::new(equatorial_radius, equatorial_radius, polar_radius)
+    }
+
+    pub fn from_reference(ellipsoid: &coords::Ellipsoid) -> Self {
+        Self::oblate(ellipsoid.equatorial_radius, ellipsoid.polar_radius)
+    }
+
+    // Растяжение единичной UV-сферы: точки и касательные умножаются на diag(a, b, c),
+    // нормали - на обратную матрицу
+    fn build_surface_mesh(&self) -> Result<Mesh> {
+        let sphere = Ball::uv_sphere(1.0, self.segments, self.rings).get_surface_mesh()?;
+        let stretch = |v: &Point3| v.component_mul(&self.radii);
+        let normals: Vec<Point3> = sphere.normals.as_ref().unwrap().iter()
+            .map(|n| normalize(n.component_div(&self.radii)))
+            .collect();
+        let tangents: Vec<Point3> = sphere.tangents.as_ref().unwrap().iter()
+            .map(|t| normalize(stretch(t)))
+            .collect();
+        let bitangents = tangents.iter().zip(&normals).map(|(t, n)| t.cross(n)).collect();
+        Ok(Mesh {
+            vertices: sphere.vertices.iter().map(stretch).collect(),
+            indices: sphere.indices,
+            normals: Some(normals),
+            uvs: sphere.uvs,
+            tangents: Some(tangents),
+            bitangents: Some(bitangents),
+        })
+    }
+}
+
+impl Geometry for Ellipsoid {
+    fn get_surface_mesh(&self) -> Result<Mesh> {
+        self.build_surface_mesh()
+    }
+
+    fn get_edges_mesh(&self, bold: f32) -> Result<Mesh> {
+        self.build_surface_mesh()?.edges_along_normals(bold)
+    }
+
+    fn minimal_bounding_volume(&self) -> MBV {
+        MBV(2.0 * self.radii.x, 2.0 * self.radii.y, 2.0 * self.radii.z)
+    }
+
+    fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere::new(Point3::zeros(), self.radii.max())
+    }
+}
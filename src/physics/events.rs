@@ -0,0 +1,642 @@
+use std::collections::{HashMap, VecDeque};
+use std::f64::consts::PI;
+use std::fmt;
+
+use rayon::prelude::*;
+
+use crate::physics::geometry::Point3;
+
+// Шаг численной производной и точность уточнения корней (в единицах времени траекторий)
+const DERIVATIVE_STEP: f64 = 1e-3;
+const ROOT_TOLERANCE: f64 = 1e-6;
+// События одного типа для одних и тех же тел ближе этого считаются дубликатами
+const DUPLICATE_WINDOW: f64 = 1e-2;
+// Больше стольких событий журнал не хранит
+const MAX_EVENTS: usize = 10_000;
+// На интервале короче стольких шагов поиска (например, за один шаг симуляции) работы на тело
+// меньше, чем стоит запуск задачи rayon, и scan считает все тела и пары в одной задаче
+const PARALLEL_MIN_STEPS: f64 = 64.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    Conjunction,
+    Opposition,
+    GreatestElongation,
+    Transit,
+    SolarEclipse,
+    LunarEclipse,
+    ClosestApproach,
+}
+
+#[derive(Debug, Clone)]
+pub struct AstroEvent {
+    pub time: f64,
+    pub kind: EventKind,
+    // для событий относительно Солнца первое тело - наблюдатель,
+    // для соединений и противостояний двух тел и сближений - сами тела
+    pub bodies: (String, String),
+    // угол (рад) для элонгаций, соединений и затмений, расстояние для сближений
+    pub value: f64,
+}
+
+impl fmt::Display for AstroEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            EventKind::ClosestApproach => write!(
+                f,
+                "t={:.4}: {:?} {} - {} (distance={:.4})",
+                self.time, self.kind, self.bodies.0, self.bodies.1, self.value
+            ),
+            _ => write!(
+                f,
+                "t={:.4}: {:?} {} - {} ({:.3} deg)",
+                self.time, self.kind, self.bodies.0, self.bodies.1, self.value.to_degrees()
+            ),
+        }
+    }
+}
+
+pub struct EventBody {
+    pub name: String,
+    pub radius: f64,
+    // индекс тела, вокруг которого обращается спутник (для затмений)
+    pub parent: Option<usize>,
+    pub position: Box<dyn Fn(f64) -> Point3 + Send + Sync>,
+}
+
+// Солнце находится в начале координат, события ищутся с точки зрения тела-наблюдателя
+pub struct EventFinder {
+    pub bodies: Vec<EventBody>,
+    pub observer: usize,
+    pub sun_radius: f64,
+    pub step: f64,
+    pub max_encounter_distance: Option<f64>,
+}
+
+fn wrap_angle(angle: f64) -> f64 {
+    let wrapped = (angle + PI).rem_euclid(2.0 * PI) - PI;
+    if wrapped == -PI { PI } else { wrapped }
+}
+
+fn angle_between(a: &Point3, b: &Point3) -> f64 {
+    let cos = a.dot(b) / (a.magnitude() * b.magnitude());
+    cos.clamp(-1.0, 1.0).acos()
+}
+
+fn derivative<F: Fn(f64) -> f64>(f: &F, t: f64) -> f64 {
+    (f(t + DERIVATIVE_STEP) - f(t - DERIVATIVE_STEP)) / (2.0 * DERIVATIVE_STEP)
+}
+
+fn bisect<F: Fn(f64) -> f64>(f: &F, mut a: f64, mut b: f64) -> f64 {
+    let mut fa = f(a);
+    while b - a > ROOT_TOLERANCE {
+        let mid = 0.5 * (a + b);
+        let fm = f(mid);
+        if (fa <= 0.0) == (fm <= 0.0) {
+            a = mid;
+            fa = fm;
+        } else {
+            b = mid;
+        }
+    }
+    0.5 * (a + b)
+}
+
+// Корни f на [t0, t1) вместе с направлением перехода через ноль (true: f растёт).
+// Для углов, заданных по модулю 2π, скачки через ±π отбрасываются
+fn find_roots<F: Fn(f64) -> f64>(f: &F, t0: f64, t1: f64, step: f64, wrapped: bool) -> Vec<(f64, bool)> {
+    let mut roots = Vec::new();
+    let mut a = t0;
+    let mut fa = f(a);
+    // корень ровно в a уже найден на предыдущем шаге как смена знака
+    let mut found_at_a = false;
+    while a < t1 {
+        let b = (a + step).min(t1);
+        let fb = f(b);
+        let jump = wrapped && (fa.abs() > PI / 2.0 || fb.abs() > PI / 2.0);
+        let mut found_at_b = false;
+        if fa == 0.0 {
+            if !found_at_a {
+                roots.push((a, fb > 0.0));
+            }
+        } else if (fa < 0.0) != (fb < 0.0) && !jump {
+            roots.push((bisect(f, a, b), fb > fa));
+            found_at_b = fb == 0.0;
+        }
+        a = b;
+        fa = fb;
+        found_at_a = found_at_b;
+    }
+    roots
+}
+
+// Экстремумы f на [t0, t1): maximum = true ищет максимумы, иначе минимумы
+fn find_extrema<F: Fn(f64) -> f64>(f: &F, t0: f64, t1: f64, step: f64, maximum: bool) -> Vec<f64> {
+    let df = |t: f64| derivative(f, t);
+    find_roots(&df, t0, t1, step, false)
+        .into_iter()
+        .filter(|&(_, rising)| rising != maximum)
+        .map(|(t, _)| t)
+        .collect()
+}
+
+impl EventFinder {
+    pub fn new(observer: usize, sun_radius: f64, step: f64) -> Self {
+        Self { bodies: Vec::new(), observer, sun_radius, step, max_encounter_distance: None }
+    }
+
+    pub fn add_body(&mut self, body: EventBody) -> usize {
+        self.bodies.push(body);
+        self.bodies.len() - 1
+    }
+
+    fn event(&self, time: f64, kind: EventKind, other: usize, value: f64) -> AstroEvent {
+        AstroEvent {
+            time,
+            kind,
+            bodies: (self.bodies[self.observer].name.clone(), self.bodies[other].name.clone()),
+            value,
+        }
+    }
+
+    // Разность эклиптических долгот тела и Солнца, видимая наблюдателем
+    fn longitude_difference(&self, body: usize, t: f64) -> f64 {
+        let obs = (self.bodies[self.observer].position)(t);
+        let p = (self.bodies[body].position)(t) - obs;
+        let s = -obs;
+        wrap_angle(p.y.atan2(p.x) - s.y.atan2(s.x))
+    }
+
+    // Направления на два тела, видимые наблюдателем
+    fn directions(&self, a: usize, b: usize, t: f64) -> (Point3, Point3) {
+        let obs = (self.bodies[self.observer].position)(t);
+        ((self.bodies[a].position)(t) - obs, (self.bodies[b].position)(t) - obs)
+    }
+
+    fn pair_longitude_difference(&self, a: usize, b: usize, t: f64) -> f64 {
+        let (pa, pb) = self.directions(a, b, t);
+        wrap_angle(pa.y.atan2(pa.x) - pb.y.atan2(pb.x))
+    }
+
+    fn elongation(&self, body: usize, t: f64) -> f64 {
+        let obs = (self.bodies[self.observer].position)(t);
+        let p = (self.bodies[body].position)(t) - obs;
+        angle_between(&p, &(-obs))
+    }
+
+    fn distance(&self, a: usize, b: usize, t: f64) -> f64 {
+        ((self.bodies[a].position)(t) - (self.bodies[b].position)(t)).magnitude()
+    }
+
+    fn conjunction_events(&self, body: usize, t: f64) -> Vec<AstroEvent> {
+        let obs = (self.bodies[self.observer].position)(t);
+        let p = (self.bodies[body].position)(t) - obs;
+        let s = -obs;
+        let separation = angle_between(&p, &s);
+
+        let mut events = vec![self.event(t, EventKind::Conjunction, body, separation)];
+        // тело перед диском Солнца
+        if p.magnitude() < s.magnitude() {
+            let sun_size = (self.sun_radius / s.magnitude()).asin();
+            let body_size = (self.bodies[body].radius / p.magnitude()).min(1.0).asin();
+            if separation < sun_size + body_size {
+                let kind = if self.bodies[body].parent == Some(self.observer) {
+                    EventKind::SolarEclipse
+                } else {
+                    EventKind::Transit
+                };
+                events.push(self.event(t, kind, body, separation));
+            }
+        }
+        events
+    }
+
+    fn opposition_events(&self, body: usize, t: f64) -> Vec<AstroEvent> {
+        let obs = (self.bodies[self.observer].position)(t);
+        let p = (self.bodies[body].position)(t) - obs;
+        let separation = PI - angle_between(&p, &(-obs));
+
+        let mut events = vec![self.event(t, EventKind::Opposition, body, separation)];
+        // спутник наблюдателя в полутени наблюдателя
+        if self.bodies[body].parent == Some(self.observer) {
+            let sun_distance = obs.magnitude();
+            let observer_radius = self.bodies[self.observer].radius;
+            let axis = obs / sun_distance;
+            let along = p.dot(&axis);
+            let off_axis = (p - axis * along).magnitude();
+            let penumbra = observer_radius + along * (self.sun_radius + observer_radius) / sun_distance;
+            if along > 0.0 && off_axis < penumbra + self.bodies[body].radius {
+                events.push(self.event(t, EventKind::LunarEclipse, body, off_axis.atan2(along)));
+            }
+        }
+        events
+    }
+
+    fn body_events(&self, body: usize, t0: f64, t1: f64) -> Vec<AstroEvent> {
+        let mut events = Vec::new();
+
+        let conj = |t: f64| self.longitude_difference(body, t);
+        for (t, _) in find_roots(&conj, t0, t1, self.step, true) {
+            events.extend(self.conjunction_events(body, t));
+        }
+
+        let opp = |t: f64| wrap_angle(self.longitude_difference(body, t) - PI);
+        for (t, _) in find_roots(&opp, t0, t1, self.step, true) {
+            events.extend(self.opposition_events(body, t));
+        }
+
+        let elong = |t: f64| self.elongation(body, t);
+        for t in find_extrema(&elong, t0, t1, self.step, true) {
+            // для внешних тел максимум элонгации совпадает с противостоянием
+            let observer = (self.bodies[self.observer].position)(t);
+            let inferior = (self.bodies[body].position)(t).magnitude() < observer.magnitude();
+            if inferior {
+                events.push(self.event(t, EventKind::GreatestElongation, body, elong(t)));
+            }
+        }
+        events
+    }
+
+    // Соединения и противостояния двух тел, кроме наблюдателя: равенство или разность в π
+    // их эклиптических долгот, видимых наблюдателем. Значение - угол между телами на небе
+    fn pair_events(&self, a: usize, b: usize, t0: f64, t1: f64) -> Vec<AstroEvent> {
+        let pair_event = |t: f64, kind: EventKind| {
+            let (pa, pb) = self.directions(a, b, t);
+            let separation = angle_between(&pa, &pb);
+            AstroEvent {
+                time: t,
+                kind,
+                bodies: (self.bodies[a].name.clone(), self.bodies[b].name.clone()),
+                value: if kind == EventKind::Opposition { PI - separation } else { separation },
+            }
+        };
+
+        let conj = |t: f64| self.pair_longitude_difference(a, b, t);
+        let opp = |t: f64| wrap_angle(self.pair_longitude_difference(a, b, t) - PI);
+        let mut events: Vec<AstroEvent> = find_roots(&conj, t0, t1, self.step, true)
+            .into_iter()
+            .map(|(t, _)| pair_event(t, EventKind::Conjunction))
+            .collect();
+        events.extend(find_roots(&opp, t0, t1, self.step, true).into_iter().map(|(t, _)| pair_event(t, EventKind::Opposition)));
+        events
+    }
+
+    fn encounter_events(&self, a: usize, b: usize, t0: f64, t1: f64) -> Vec<AstroEvent> {
+        let dist = |t: f64| self.distance(a, b, t);
+        find_extrema(&dist, t0, t1, self.step, false)
+            .into_iter()
+            .map(|t| (t, dist(t)))
+            .filter(|&(_, d)| self.max_encounter_distance.is_none_or(|max| d <= max))
+            .map(|(t, d)| AstroEvent {
+                time: t,
+                kind: EventKind::ClosestApproach,
+                bodies: (self.bodies[a].name.clone(), self.bodies[b].name.clone()),
+                value: d,
+            })
+            .collect()
+    }
+
+    // Тела и пары тел обрабатываются параллельно, если интервал не короче PARALLEL_MIN_STEPS
+    // шагов: тогда каждое тело - тысячи вычислений положений. collect сохраняет порядок,
+    // а сортировка стабильна, поэтому результат совпадает с последовательным
+    pub fn scan(&self, t0: f64, t1: f64) -> Vec<AstroEvent> {
+        let n = self.bodies.len();
+        let pairs: Vec<(usize, usize)> = (0..n)
+            .flat_map(|a| ((a + 1)..n).map(move |b| (a, b)))
+            .collect();
+        let min_len = if (t1 - t0) / self.step < PARALLEL_MIN_STEPS { usize::MAX } else { 1 };
+
+        let mut events: Vec<AstroEvent> = (0..n)
+            .into_par_iter()
+            .with_min_len(min_len)
+            .filter(|&body| body != self.observer)
+            .flat_map_iter(|body| self.body_events(body, t0, t1))
+            .collect();
+        events.par_extend(pairs.par_iter().with_min_len(min_len).flat_map_iter(|&(a, b)| {
+            let mut events = self.encounter_events(a, b, t0, t1);
+            if a != self.observer && b != self.observer {
+                events.extend(self.pair_events(a, b, t0, t1));
+            }
+            events
+        }));
+
+        events.sort_by(|a, b| a.time.total_cmp(&b.time));
+        events
+    }
+}
+
+// Ключ события в журнале: тип, тела и номер окна длиной DUPLICATE_WINDOW, в которое попало время
+type EventKey = (EventKind, (String, String), i64);
+
+// Журнал событий, упорядоченный по времени. Повторы ищутся по ключу в соседних окнах,
+// а не перебором журнала; событий хранится не больше MAX_EVENTS, лишние вытесняются с начала.
+// Выбранное в списке событие остаётся выбранным, когда перед ним добавляются другие
+#[derive(Debug, Clone, Default)]
+pub struct EventLog {
+    events: VecDeque<AstroEvent>,
+    index: HashMap<EventKey, f64>,
+    selected: Option<usize>,
+}
+
+impl EventLog {
+    fn window(time: f64) -> i64 {
+        (time / DUPLICATE_WINDOW).floor() as i64
+    }
+
+    // Возвращает false, если такое событие уже есть в журнале.
+    // Времена в одном окне ближе DUPLICATE_WINDOW, поэтому в окне не больше одного события
+    pub fn insert(&mut self, event: AstroEvent) -> bool {
+        let window = Self::window(event.time);
+        let mut key = (event.kind, event.bodies.clone(), window - 1);
+        for neighbour in (window - 1)..=(window + 1) {
+            key.2 = neighbour;
+            if self.index.get(&key).is_some_and(|&t| (t - event.time).abs() < DUPLICATE_WINDOW) {
+                return false;
+            }
+        }
+        key.2 = window;
+        self.index.insert(key, event.time);
+        let pos = self.events.partition_point(|e| e.time <= event.time);
+        self.events.insert(pos, event);
+        self.selected = self.selected.map(|i| if i >= pos { i + 1 } else { i });
+
+        if self.events.len() > MAX_EVENTS
+            && let Some(oldest) = self.events.pop_front()
+        {
+            let window = Self::window(oldest.time);
+            self.index.remove(&(oldest.kind, oldest.bodies, window));
+            self.selected = self.selected.and_then(|i| i.checked_sub(1));
+        }
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AstroEvent> {
+        self.events.iter()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&AstroEvent> {
+        self.events.get(index)
+    }
+
+    pub fn selected(&self) -> Option<(usize, &AstroEvent)> {
+        self.selected.and_then(|i| self.events.get(i).map(|e| (i, e)))
+    }
+
+    pub fn select(&mut self, index: usize) -> Option<(usize, &AstroEvent)> {
+        let event = self.events.get(index)?;
+        self.selected = Some(index);
+        Some((index, event))
+    }
+
+    // Следующее событие в списке; если ничего не выбрано - первое после t
+    pub fn select_next(&mut self, t: f64) -> Option<(usize, &AstroEvent)> {
+        let index = match self.selected {
+            Some(i) => (i + 1).min(self.events.len().checked_sub(1)?),
+            None => self.events.partition_point(|e| e.time <= t + DUPLICATE_WINDOW),
+        };
+        self.select(index)
+    }
+
+    // Предыдущее событие в списке; если ничего не выбрано - последнее до t
+    pub fn select_prev(&mut self, t: f64) -> Option<(usize, &AstroEvent)> {
+        let index = match self.selected {
+            Some(i) => i.saturating_sub(1),
+            None => self.events.partition_point(|e| e.time < t - DUPLICATE_WINDOW).checked_sub(1)?,
+        };
+        self.select(index)
+    }
+
+    pub fn next_after(&self, t: f64) -> Option<&AstroEvent> {
+        let pos = self.events.partition_point(|e| e.time <= t + DUPLICATE_WINDOW);
+        self.events.get(pos)
+    }
+
+    pub fn prev_before(&self, t: f64) -> Option<&AstroEvent> {
+        let pos = self.events.partition_point(|e| e.time < t - DUPLICATE_WINDOW);
+        pos.checked_sub(1).and_then(|i| self.events.get(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn root_on_step_boundary_is_reported_once() {
+        // f(1) == 0 ровно, и 1 - граница шага
+        let roots = find_roots(&|t: f64| t - 1.0, 0.0, 2.0, 0.5, false);
+        assert_eq!(roots.len(), 1);
+        assert!((roots[0].0 - 1.0).abs() < ROOT_TOLERANCE);
+        assert!(roots[0].1);
+    }
+
+    // Земля на круговой орбите радиуса 1 с периодом 1, Луна в плоскости эклиптики
+    // делает 12 синодических оборотов за год: в t = 0 она между Землёй и Солнцем
+    fn earth_moon() -> EventFinder {
+        let earth_angle = |t: f64| 2.0 * PI * t;
+        let moon_angle = move |t: f64| earth_angle(t) + PI + 2.0 * PI * 12.0 * t;
+        let earth_position = move |t: f64| Point3::new(earth_angle(t).cos(), earth_angle(t).sin(), 0.0);
+
+        let mut finder = EventFinder::new(0, 0.005, 0.005);
+        let earth = finder.add_body(EventBody {
+            name: "Earth".to_string(),
+            radius: 0.002,
+            parent: None,
+            position: Box::new(earth_position),
+        });
+        finder.add_body(EventBody {
+            name: "Moon".to_string(),
+            radius: 0.001,
+            parent: Some(earth),
+            position: Box::new(move |t| earth_position(t) + Point3::new(moon_angle(t).cos(), moon_angle(t).sin(), 0.0) * 0.05),
+        });
+        finder
+    }
+
+    #[test]
+    fn finds_solar_and_lunar_eclipses() {
+        let events = earth_moon().scan(0.01, 0.1);
+        let time_of = |kind: EventKind| events.iter().find(|e| e.kind == kind).map(|e| e.time);
+        // полнолуние через полмесяца, следующее новолуние через месяц
+        let lunar = time_of(EventKind::LunarEclipse).expect("lunar eclipse");
+        let solar = time_of(EventKind::SolarEclipse).expect("solar eclipse");
+        assert!((lunar - 1.0 / 24.0).abs() < 1e-4);
+        assert!((solar - 1.0 / 12.0).abs() < 1e-4);
+        assert!(events.iter().all(|e| e.kind != EventKind::Transit));
+    }
+
+    // Интервал длиннее PARALLEL_MIN_STEPS шагов: тела и пары делятся между потоками
+    #[test]
+    fn parallel_scan_matches_serial() {
+        let finder = earth_moon();
+        let key = |events: Vec<AstroEvent>| -> Vec<_> {
+            events.into_iter().map(|e| (e.time, e.kind, e.bodies, e.value)).collect()
+        };
+        let serial = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        let expected = key(serial.install(|| finder.scan(0.0, 1.0)));
+        let parallel = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        assert!(!expected.is_empty());
+        assert_eq!(key(parallel.install(|| finder.scan(0.0, 1.0))), expected);
+    }
+
+    // Земля и Венера на круговых орбитах с реальными радиусами тел и Солнца в а.е.
+    // и периодами в годах. В t = 0 нижнее соединение, орбита Венеры наклонена на inclination
+    // вокруг оси y, так что в соединении Венера дальше всего от плоскости эклиптики
+    fn earth_venus(inclination: f64) -> EventFinder {
+        let mut finder = EventFinder::new(0, 0.00465, 0.005);
+        finder.add_body(EventBody {
+            name: "Earth".to_string(),
+            radius: 4.26e-5,
+            parent: None,
+            position: Box::new(|t| Point3::new((2.0 * PI * t).cos(), (2.0 * PI * t).sin(), 0.0)),
+        });
+        finder.add_body(EventBody {
+            name: "Venus".to_string(),
+            radius: 4.05e-5,
+            parent: None,
+            position: Box::new(move |t| {
+                let angle = 2.0 * PI * t / 0.615;
+                Point3::new(angle.cos() * inclination.cos(), angle.sin(), angle.cos() * inclination.sin()) * 0.723
+            }),
+        });
+        finder
+    }
+
+    #[test]
+    fn inclined_conjunction_is_not_a_transit() {
+        let has = |events: &[AstroEvent], kind: EventKind| events.iter().any(|e| e.kind == kind);
+        let coplanar = earth_venus(0.0).scan(-0.1, 0.1);
+        assert!(has(&coplanar, EventKind::Transit));
+
+        // Венера проходит почти в 9 градусах от Солнца при его видимом радиусе 0.27 градуса
+        let inclined = earth_venus(3.39_f64.to_radians()).scan(-0.1, 0.1);
+        assert!(has(&inclined, EventKind::Conjunction));
+        assert!(!has(&inclined, EventKind::Transit));
+    }
+
+    // Наименьшие расстояния в t = 0: Земля - Венера 0.277, Земля - Марс 0.524, Венера - Марс 0.801
+    #[test]
+    fn distant_encounters_are_filtered_out() {
+        let mut finder = earth_venus(0.0);
+        finder.add_body(EventBody {
+            name: "Mars".to_string(),
+            radius: 2.27e-5,
+            parent: None,
+            position: Box::new(|t| {
+                let angle = 2.0 * PI * t / 1.881;
+                Point3::new(angle.cos(), angle.sin(), 0.0) * 1.524
+            }),
+        });
+        let encounters = |finder: &EventFinder| -> Vec<(String, String)> {
+            finder.scan(-0.1, 0.1).into_iter()
+                .filter(|e| e.kind == EventKind::ClosestApproach)
+                .map(|e| e.bodies)
+                .collect()
+        };
+        assert_eq!(encounters(&finder).len(), 3);
+
+        finder.max_encounter_distance = Some(0.3);
+        assert_eq!(encounters(&finder), vec![("Earth".to_string(), "Venus".to_string())]);
+    }
+
+    // С Земли Венера (период 0.615) и Марс (1.881) в t = 0 оба в соединении с Солнцем,
+    // Венера в нижнем, Марс в верхнем: на небе они вместе
+    #[test]
+    fn finds_conjunctions_and_oppositions_between_planets() {
+        let mut finder = earth_venus(0.0);
+        let mars = finder.add_body(EventBody {
+            name: "Mars".to_string(),
+            radius: 2.27e-5,
+            parent: None,
+            position: Box::new(|t| {
+                let angle = 2.0 * PI * t / 1.881 + PI;
+                Point3::new(angle.cos(), angle.sin(), 0.0) * 1.524
+            }),
+        });
+        let events: Vec<AstroEvent> = finder.scan(-0.1, 2.0).into_iter()
+            .filter(|e| e.bodies == ("Venus".to_string(), "Mars".to_string()))
+            .filter(|e| e.kind != EventKind::ClosestApproach)
+            .collect();
+        let first = |kind: EventKind| events.iter().find(|e| e.kind == kind).expect("pair event");
+
+        let conjunction = first(EventKind::Conjunction);
+        assert!(conjunction.time.abs() < 1e-4);
+        assert!(conjunction.value < 1e-4);
+        let opposition = first(EventKind::Opposition);
+        let (venus, mars) = finder.directions(1, mars, opposition.time);
+        assert!((angle_between(&venus, &mars) - PI).abs() < 1e-3);
+        assert!(opposition.value < 1e-3);
+        assert!(events.iter().all(|e| e.kind == EventKind::Conjunction || e.kind == EventKind::Opposition));
+    }
+
+    fn logged(time: f64, kind: EventKind) -> AstroEvent {
+        AstroEvent { time, kind, bodies: ("Earth".to_string(), "Venus".to_string()), value: 0.0 }
+    }
+
+    #[test]
+    fn log_drops_duplicates_across_window_edges() {
+        let mut log = EventLog::default();
+        // 0.0099 и 0.0101 в соседних окнах, но ближе DUPLICATE_WINDOW
+        assert!(log.insert(logged(0.0099, EventKind::Conjunction)));
+        assert!(!log.insert(logged(0.0101, EventKind::Conjunction)));
+        assert!(log.insert(logged(0.0101, EventKind::Transit)));
+        assert!(log.insert(logged(0.025, EventKind::Conjunction)));
+        assert!(log.insert(logged(-0.5, EventKind::Conjunction)));
+
+        let times: Vec<f64> = log.iter().map(|e| e.time).collect();
+        assert_eq!(times, vec![-0.5, 0.0099, 0.0101, 0.025]);
+        assert_eq!(log.next_after(0.0).map(|e| e.time), Some(0.0101));
+        assert_eq!(log.prev_before(0.0099).map(|e| e.time), Some(-0.5));
+    }
+
+    #[test]
+    fn selection_follows_the_event_and_starts_at_the_current_time() {
+        let mut log = EventLog::default();
+        for time in [1.0, 2.0, 3.0] {
+            log.insert(logged(time, EventKind::Conjunction));
+        }
+        let time = |selected: Option<(usize, &AstroEvent)>| selected.map(|(_, e)| e.time);
+        assert_eq!(time(log.select_next(1.5)), Some(2.0));
+        assert_eq!(time(log.select_next(1.5)), Some(3.0));
+        // за концом списка выбор не меняется
+        assert_eq!(time(log.select_next(1.5)), Some(3.0));
+
+        log.insert(logged(0.5, EventKind::Conjunction));
+        assert_eq!(log.selected().map(|(i, e)| (i, e.time)), Some((3, 3.0)));
+        assert_eq!(time(log.select_prev(1.5)), Some(2.0));
+        assert_eq!(time(log.select(0)), Some(0.5));
+        assert!(log.select(4).is_none());
+
+        let mut empty = EventLog::default();
+        assert!(empty.select_next(0.0).is_none());
+        assert!(empty.select_prev(0.0).is_none());
+    }
+
+    #[test]
+    fn log_keeps_the_latest_events() {
+        let mut log = EventLog::default();
+        for i in 0..=MAX_EVENTS {
+            assert!(log.insert(logged(i as f64, EventKind::Conjunction)));
+        }
+        assert_eq!(log.len(), MAX_EVENTS);
+        assert_eq!(log.iter().next().map(|e| e.time), Some(1.0));
+        assert_eq!(log.index.len(), MAX_EVENTS);
+    }
+
+    #[test]
+    fn satellite_without_parent_transits_instead_of_eclipsing() {
+        let mut finder = earth_moon();
+        finder.bodies[1].parent = None;
+        let events = finder.scan(0.01, 0.1);
+        assert!(events.iter().any(|e| e.kind == EventKind::Transit));
+        assert!(events.iter().all(|e| e.kind != EventKind::SolarEclipse && e.kind != EventKind::LunarEclipse));
+    }
+}
@@ -0,0 +1,229 @@
+use std::f64::consts::PI;
+
+use nalgebra::Rotation3;
+
+use crate::physics::geometry::Point3;
+use crate::physics::nbody::MassiveBody;
+use crate::physics::random::Rng;
+
+// Доля массы сферы Пламмера, за которой хвост распределения обрезается
+const PLUMMER_MASS_CUTOFF: f64 = 0.999;
+// Максимум q^2 (1 - q^2)^3.5 на [0, 1] с запасом, для выборки с отклонением
+const PLUMMER_VELOCITY_BOUND: f64 = 0.1;
+
+// Начальные условия генерируются в единицах G = 1
+#[derive(Debug, Clone)]
+pub struct PlummerConfig {
+    pub count: usize,
+    pub total_mass: f64,
+    pub scale_radius: f64,
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiskConfig {
+    pub count: usize,
+    pub disk_mass: f64,
+    // масса центрального тела (балдж, чёрная дыра), 0 - без него
+    pub central_mass: f64,
+    pub scale_length: f64,
+    pub scale_height: f64,
+    pub max_radius: f64,
+    // дисперсия скоростей в долях круговой скорости
+    pub velocity_dispersion: f64,
+    pub softening: f64,
+    pub seed: u64,
+}
+
+fn isotropic(rng: &mut Rng, length: f64) -> Point3 {
+    let z = rng.range(-1.0, 1.0);
+    let phi = rng.range(0.0, 2.0 * PI);
+    let r_xy = (1.0 - z * z).sqrt();
+    Point3::new(r_xy * phi.cos(), r_xy * phi.sin(), z) * length
+}
+
+// Переводит тела в систему их центра масс
+fn to_center_of_mass(bodies: &mut [MassiveBody]) {
+    let total: f64 = bodies.iter().map(|b| b.mu).sum();
+    if total == 0.0 {
+        return;
+    }
+    let position = bodies.iter().fold(Point3::zeros(), |acc, b| acc + b.position * b.mu) / total;
+    let velocity = bodies.iter().fold(Point3::zeros(), |acc, b| acc + b.velocity * b.mu) / total;
+    for body in bodies {
+        body.position -= position;
+        body.velocity -= velocity;
+    }
+}
+
+// Сфера Пламмера в равновесии (Aarseth, Henon, Wielen 1974): радиусы по обращённой
+// функции массы, скорости выборкой с отклонением из функции распределения
+pub fn plummer_sphere(config: &PlummerConfig) -> Vec<MassiveBody> {
+    let mut rng = Rng::new(config.seed);
+    let a = config.scale_radius;
+    let mu = config.total_mass / config.count.max(1) as f64;
+
+    let mut bodies: Vec<MassiveBody> = (0..config.count).map(|_| {
+        let x = rng.range(1e-6, PLUMMER_MASS_CUTOFF);
+        let r = a / (x.powf(-2.0 / 3.0) - 1.0).sqrt();
+
+        let q = loop {
+            let q = rng.uniform();
+            let g = q * q * (1.0 - q * q).powf(3.5);
+            if rng.uniform() * PLUMMER_VELOCITY_BOUND < g {
+                break q;
+            }
+        };
+        let escape_speed = (2.0 * config.total_mass).sqrt() * (r * r + a * a).powf(-0.25);
+
+        MassiveBody {
+            position: isotropic(&mut rng, r),
+            velocity: isotropic(&mut rng, q * escape_speed),
+            mu,
+        }
+    }).collect();
+    to_center_of_mass(&mut bodies);
+    bodies
+}
+
+// Масса экспоненциального диска внутри цилиндрического радиуса radius
+fn disk_mass_within(config: &DiskConfig, radius: f64) -> f64 {
+    let x = radius / config.scale_length;
+    config.disk_mass * (1.0 - (1.0 + x) * (-x).exp())
+}
+
+// Экспоненциальный диск с поверхностной плотностью ~ exp(-R / scale_length) и профилем
+// sech^2 по высоте, вращается вокруг оси Z. Круговая скорость оценивается по массе внутри
+// радиуса как для сферического распределения, поэтому диск близок к равновесию, но не точно в нём
+pub fn exponential_disk(config: &DiskConfig) -> Vec<MassiveBody> {
+    let mut rng = Rng::new(config.seed);
+    let mu = config.disk_mass / config.count.max(1) as f64;
+    let eps2 = config.softening * config.softening;
+
+    let mut bodies: Vec<MassiveBody> = Vec::with_capacity(config.count + 1);
+    if config.central_mass > 0.0 {
+        bodies.push(MassiveBody { position: Point3::zeros(), velocity: Point3::zeros(), mu: config.central_mass });
+    }
+    while bodies.len() < config.count + (config.central_mass > 0.0) as usize {
+        // радиус с плотностью R exp(-R / h) - гамма-распределение порядка 2
+        let radius = -config.scale_length * ((1.0 - rng.uniform()) * (1.0 - rng.uniform())).ln();
+        if radius > config.max_radius {
+            continue;
+        }
+        let phi = rng.range(0.0, 2.0 * PI);
+        let z = config.scale_height * rng.range(-1.0 + 1e-9, 1.0 - 1e-9).atanh();
+
+        let enclosed = disk_mass_within(config, radius) + config.central_mass;
+        let r2 = radius * radius + eps2;
+        let circular_speed = (enclosed * radius * radius / (r2 * r2.sqrt())).sqrt();
+        let sigma = config.velocity_dispersion * circular_speed;
+
+        let (sin, cos) = phi.sin_cos();
+        bodies.push(MassiveBody {
+            position: Point3::new(radius * cos, radius * sin, z),
+            velocity: Point3::new(
+                -circular_speed * sin + rng.normal(0.0, sigma),
+                circular_speed * cos + rng.normal(0.0, sigma),
+                rng.normal(0.0, sigma),
+            ),
+            mu,
+        });
+    }
+    to_center_of_mass(&mut bodies);
+    bodies
+}
+
+// Размещение системы в сцене: поворот на углы Эйлера, затем сдвиг и добавление скорости
+pub fn place(bodies: &mut [MassiveBody], rotation_euler: (f64, f64, f64), offset: Point3, velocity: Point3) {
+    let rotation = Rotation3::from_euler_angles(rotation_euler.0, rotation_euler.1, rotation_euler.2);
+    for body in bodies {
+        body.position = rotation * body.position + offset;
+        body.velocity = rotation * body.velocity + velocity;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COUNT: usize = 4000;
+
+    fn plummer() -> Vec<MassiveBody> {
+        plummer_sphere(&PlummerConfig { count: COUNT, total_mass: 2.0, scale_radius: 1.5, seed: 5 })
+    }
+
+    fn disk() -> Vec<MassiveBody> {
+        exponential_disk(&DiskConfig {
+            count: COUNT,
+            disk_mass: 1.0,
+            central_mass: 0.5,
+            scale_length: 2.0,
+            scale_height: 0.1,
+            max_radius: 40.0,
+            velocity_dispersion: 0.05,
+            softening: 0.05,
+            seed: 6,
+        })
+    }
+
+    fn assert_at_rest_in_the_origin(bodies: &[MassiveBody]) {
+        let mass: f64 = bodies.iter().map(|b| b.mu).sum();
+        let center = bodies.iter().fold(Point3::zeros(), |acc, b| acc + b.position * b.mu) / mass;
+        let momentum = bodies.iter().fold(Point3::zeros(), |acc, b| acc + b.velocity * b.mu);
+        assert!(center.magnitude() < 1e-12, "{:?}", center);
+        assert!(momentum.magnitude() < 1e-12, "{:?}", momentum);
+    }
+
+    // все тела равной массы: радиус половины массы - медиана расстояний
+    fn median(mut values: Vec<f64>) -> f64 {
+        values.sort_by(f64::total_cmp);
+        values[values.len() / 2]
+    }
+
+    #[test]
+    fn plummer_sphere_is_centred_with_the_analytic_half_mass_radius() {
+        let bodies = plummer();
+        assert_eq!(bodies.len(), COUNT);
+        assert_at_rest_in_the_origin(&bodies);
+
+        // r_1/2 = a / sqrt(2^(2/3) - 1) ~ 1.305 a
+        let half_mass = median(bodies.iter().map(|b| b.position.magnitude()).collect());
+        let expected = 1.5 / (2f64.powf(2.0 / 3.0) - 1.0).sqrt();
+        assert!((half_mass - expected).abs() < 0.05 * expected, "{} vs {}", half_mass, expected);
+
+        // равновесие по теореме вириала: 2K = -W
+        let kinetic: f64 = bodies.iter().map(|b| 0.5 * b.mu * b.velocity.magnitude_squared()).sum();
+        let potential: f64 = (0..bodies.len()).map(|i| {
+            (i + 1..bodies.len()).map(|j| -bodies[i].mu * bodies[j].mu / (bodies[i].position - bodies[j].position).magnitude()).sum::<f64>()
+        }).sum();
+        let virial_ratio = -2.0 * kinetic / potential;
+        assert!((virial_ratio - 1.0).abs() < 0.1, "{}", virial_ratio);
+    }
+
+    #[test]
+    fn exponential_disk_is_flat_and_rotating() {
+        let bodies = disk();
+        assert_eq!(bodies.len(), COUNT + 1);
+        assert_at_rest_in_the_origin(&bodies);
+
+        // центральное тело идёт первым
+        assert_eq!(bodies[0].mu, 0.5);
+        let stars = &bodies[1..];
+        let radii: Vec<f64> = stars.iter().map(|b| b.position.xy().magnitude()).collect();
+        // для exp(-R/h): 1 - (1 + x) e^-x = 1/2 при x ~ 1.678
+        let expected = 1.678 * 2.0;
+        let half_mass = median(radii.clone());
+        assert!((half_mass - expected).abs() < 0.05 * expected, "{} vs {}", half_mass, expected);
+        assert!(radii.iter().all(|&r| r <= 40.0 + 1e-9));
+
+        // для sech^2 средний |z| равен h_z ln 2
+        let mean_z = stars.iter().map(|b| b.position.z.abs()).sum::<f64>() / stars.len() as f64;
+        assert!((mean_z - 0.1 * 2f64.ln()).abs() < 0.01, "{}", mean_z);
+        assert!(mean_z < 0.05 * 2.0);
+
+        // диск вращается против часовой стрелки вокруг +Z
+        let angular_momentum = stars.iter().fold(Point3::zeros(), |acc, b| acc + b.position.cross(&b.velocity) * b.mu);
+        assert!(angular_momentum.z > 0.0);
+        assert!(angular_momentum.xy().magnitude() < 0.05 * angular_momentum.z);
+    }
+}
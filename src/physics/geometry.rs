@@ -2,28 +2,27 @@ use std::f64::consts::PI;
 
 use nalgebra::{Vector3, Rotation3};
 use convexhull3d::{ConvexHull3D, Vertex};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use rayon::prelude::*;
 
-pub fn quick_hull(vertices: &Vec<Point3>) -> Vec<[usize; 3]> {
-    let vertices: Vec<Vertex> = vertices.iter()
+use crate::error::{Error, Result};
+use crate::physics::bounds::{Aabb, BoundingSphere, Ray, RayHit, ray_triangle};
+
+// Меньше стольких лёгких элементов (вершин, точек) на задачу rayon не делит работу:
+// для сотни точек запуск потоков дороже самих вычислений, и такой ввод считается последовательно
+pub const PARALLEL_MIN_LEN: usize = 4096;
+
+pub fn quick_hull(points: &[Point3]) -> Result<Vec<[usize; 3]>> {
+    let vertices: Vec<Vertex> = points.iter()
         .map(|v| Vertex::new(v.x, v.y, v.z))
         .collect();
 
-    match ConvexHull3D::build(&vertices) {
-        Ok(hull) => {
-            let mut indices = Vec::new();
-            
-            for face in hull.faces() {
-                indices.push([
-                    face.v0,
-                    face.v1,
-                    face.v2,
-                ]);
-            }
-            indices
-        }
-        Err(_) => vec![],
-    }
+    let hull = ConvexHull3D::build(&vertices).map_err(|e| Error::DegenerateHull {
+        points: points.len(),
+        bounds: Aabb::from_points(points),
+        reason: e.to_string(),
+    })?;
+    Ok(hull.faces().iter().map(|face| [face.v0, face.v1, face.v2]).collect())
 }
 
 pub type Point3 = Vector3<f64>;
@@ -35,9 +34,98 @@ pub struct MBV(pub f64, pub f64, pub f64);
 pub struct Mesh {
     pub vertices: Vec<Point3>,
     pub indices: Vec<[usize; 3]>,
+    // нормали, текстурные координаты и касательный базис по вершинам, если генератор умеет их считать.
+    // tangent направлен по росту u, bitangent - по росту v
+    pub normals: Option<Vec<Point3>>,
+    pub uvs: Option<Vec<[f64; 2]>>,
+    pub tangents: Option<Vec<Point3>>,
+    pub bitangents: Option<Vec<Point3>>,
+}
+
+// Нормаль треугольника, длина равна удвоенной площади
+pub(crate) fn face_normal(vertices: &[Point3], face: &[usize; 3]) -> Point3 {
+    let [a, b, c] = *face;
+    (vertices[b] - vertices[a]).cross(&(vertices[c] - vertices[a]))
+}
+
+pub(crate) fn normalize_or_zero(v: Point3) -> Point3 {
+    let norm = v.magnitude();
+    if norm > 0.0 { v / norm } else { v }
 }
 
 impl Mesh {
+    pub fn new(vertices: Vec<Point3>, indices: Vec<[usize; 3]>) -> Self {
+        Self { vertices, indices, normals: None, uvs: None, tangents: None, bitangents: None }
+    }
+
+    // Сглаженные нормали: сумма нормалей прилегающих треугольников, взвешенных по площади
+    pub fn smooth_normals(&self) -> Vec<Point3> {
+        let mut normals = vec![Point3::zeros(); self.vertices.len()];
+        for face in &self.indices {
+            let n = face_normal(&self.vertices, face);
+            for &i in face {
+                normals[i] += n;
+            }
+        }
+        normals.into_iter().map(normalize_or_zero).collect()
+    }
+
+    // Плоское затенение: у каждого треугольника свои вершины с нормалью грани
+    pub fn flat_shaded(&self) -> Mesh {
+        let vertices: Vec<Point3> = self.indices.iter().flat_map(|face| face.map(|i| self.vertices[i])).collect();
+        let normals = self.indices.iter()
+            .flat_map(|face| [normalize_or_zero(face_normal(&self.vertices, face)); 3])
+            .collect();
+        let uvs = self.uvs.as_ref().map(|uvs| self.indices.iter().flat_map(|face| face.map(|i| uvs[i])).collect());
+        let indices = (0..self.indices.len()).map(|f| [3 * f, 3 * f + 1, 3 * f + 2]).collect();
+        let mut mesh = Mesh { vertices, indices, normals: Some(normals), uvs, tangents: None, bitangents: None };
+        mesh.ensure_tangents();
+        mesh
+    }
+
+    pub fn ensure_normals(&mut self) {
+        if self.normals.is_none() {
+            self.normals = Some(self.smooth_normals());
+        }
+    }
+
+    // Касательный базис по UV (метод Ленгиела), ортогонализованный относительно нормалей.
+    // Без UV базис не определён
+    pub fn ensure_tangents(&mut self) {
+        if self.tangents.is_some() || self.uvs.is_none() {
+            return;
+        }
+        self.ensure_normals();
+        let uvs = self.uvs.as_ref().unwrap();
+        let normals = self.normals.as_ref().unwrap();
+
+        let mut tangents = vec![Point3::zeros(); self.vertices.len()];
+        let mut bitangents = vec![Point3::zeros(); self.vertices.len()];
+        for &[a, b, c] in &self.indices {
+            let e1 = self.vertices[b] - self.vertices[a];
+            let e2 = self.vertices[c] - self.vertices[a];
+            let (du1, dv1) = (uvs[b][0] - uvs[a][0], uvs[b][1] - uvs[a][1]);
+            let (du2, dv2) = (uvs[c][0] - uvs[a][0], uvs[c][1] - uvs[a][1]);
+            let det = du1 * dv2 - du2 * dv1;
+            if det == 0.0 {
+                continue;
+            }
+            let t = (e1 * dv2 - e2 * dv1) / det;
+            let bt = (e2 * du1 - e1 * du2) / det;
+            for i in [a, b, c] {
+                tangents[i] += t;
+                bitangents[i] += bt;
+            }
+        }
+        for (i, n) in normals.iter().enumerate() {
+            tangents[i] = normalize_or_zero(tangents[i] - n * n.dot(&tangents[i]));
+            let bt = bitangents[i] - n * n.dot(&bitangents[i]) - tangents[i] * tangents[i].dot(&bitangents[i]);
+            bitangents[i] = normalize_or_zero(bt);
+        }
+        self.tangents = Some(tangents);
+        self.bitangents = Some(bitangents);
+    }
+
     pub fn get_edges_indices(&self) -> Vec<(usize, usize)> {
         let mut edges = HashSet::new();
 
@@ -51,28 +139,165 @@ impl Mesh {
         edges.into_iter().collect()
     }
 
-    pub fn get_center(&self) -> Point3 {
-        let count = self.vertices.len();
-        assert_ne!(count, 0, "count of points must be > 0");
-        let sum = self.vertices.iter().fold(Point3::new(0.0, 0.0, 0.0), |acc, &point| {
-            Point3::new(
-                acc.x + point.x,
-                acc.y + point.y,
-                acc.z + point.z,
-            )
-        });
-        Point3::new(
-            sum.x / count as f64,
-            sum.y / count as f64,
-            sum.z / count as f64,
-        )
+    // Уникальные рёбра после сшивания совпадающих по положению вершин (швы UV, рёбра куба).
+    // Рёбра между гранями, нормали которых расходятся меньше чем на min_angle, пропускаются:
+    // так из каркаса уходят диагонали плоских четырёхугольников
+    pub fn feature_edges(&self, min_angle: f64) -> Vec<[Point3; 2]> {
+        let remap = self.coincident_vertices();
+
+        let mut edges: BTreeMap<(usize, usize), (usize, usize, Vec<Point3>)> = BTreeMap::new();
+        for face in &self.indices {
+            let n = normalize_or_zero(face_normal(&self.vertices, face));
+            for k in 0..3 {
+                let (a, b) = (face[k], face[(k + 1) % 3]);
+                let (wa, wb) = (remap[a], remap[b]);
+                if wa != wb {
+                    edges.entry((wa.min(wb), wa.max(wb))).or_insert_with(|| (a, b, Vec::new())).2.push(n);
+                }
+            }
+        }
+
+        let cos_min = min_angle.cos();
+        edges.into_values()
+            .filter(|(_, _, normals)| normals.len() != 2 || normals[0].dot(&normals[1]) <= cos_min)
+            .map(|(a, b, _)| [self.vertices[a], self.vertices[b]])
+            .collect()
+    }
+
+    // Для каждой вершины - индекс первой вершины не дальше tolerance от неё (или её собственный).
+    // Сетка с ячейкой tolerance: соседи ищутся в 27 ячейках вокруг
+    pub fn weld_map(&self, tolerance: f64) -> Vec<usize> {
+        let cell = if tolerance > 0.0 { tolerance } else { 1.0 };
+        let mut grid: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        let mut remap = Vec::with_capacity(self.vertices.len());
+        for (i, v) in self.vertices.iter().enumerate() {
+            let key = [v.x, v.y, v.z].map(|x| (x / cell).floor() as i64);
+            let mut found = None;
+            'search: for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let neighbour = [key[0].saturating_add(dx), key[1].saturating_add(dy), key[2].saturating_add(dz)];
+                        let near = grid.get(&neighbour).into_iter().flatten()
+                            .find(|&&j| (self.vertices[j] - v).magnitude() <= tolerance);
+                        if let Some(&j) = near {
+                            found = Some(j);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+            remap.push(found.unwrap_or_else(|| {
+                grid.entry(key).or_default().push(i);
+                i
+            }));
+        }
+        remap
+    }
+
+    // Вершины, совпадающие по положению: швы UV, рёбра куба.
+    // sin(pi) и sin(-pi) различаются в последних битах, поэтому допуск относительный
+    pub fn coincident_vertices(&self) -> Vec<usize> {
+        let extent = self.vertices.iter().fold(0.0, |acc: f64, v| acc.max(v.amax()));
+        self.weld_map(WELD_TOLERANCE * extent.max(f64::MIN_POSITIVE))
+    }
+
+    // Каркас для get_edges_mesh: каждое ребро - полоска из вершины и её копии,
+    // сдвинутой по нормали на bold
+    pub fn edges_along_normals(&self, bold: f32) -> Result<Mesh> {
+        check_bold(bold)?;
+        let normals = match &self.normals {
+            Some(normals) => normals.clone(),
+            None => self.smooth_normals(),
+        };
+        let mut edge_vertex: Vec<Option<(usize, usize)>> = vec![None; self.vertices.len()];
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut edge_pair = |i: usize, vertices: &mut Vec<Point3>| {
+            *edge_vertex[i].get_or_insert_with(|| {
+                vertices.push(self.vertices[i]);
+                vertices.push(self.vertices[i] + normals[i] * bold as f64);
+                (vertices.len() - 2, vertices.len() - 1)
+            })
+        };
+        for (a, b) in self.get_edges_indices() {
+            let (a1, a2) = edge_pair(a, &mut vertices);
+            let (b1, b2) = edge_pair(b, &mut vertices);
+            indices.push([a1, a2, b1]);
+            indices.push([a2, b2, b1]);
+        }
+        Ok(Mesh::new(vertices, indices))
+    }
+
+    pub fn aabb(&self) -> Option<Aabb> {
+        Aabb::from_points(&self.vertices)
+    }
+
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        BoundingSphere::from_points(&self.vertices)
+    }
+
+    // Ближайшее пересечение луча с треугольниками. Нормаль интерполируется по нормалям вершин,
+    // если они есть, иначе берётся нормаль грани
+    pub fn ray_cast(&self, ray: &Ray) -> Option<RayHit> {
+        self.indices.par_iter().with_min_len(PARALLEL_MIN_LEN).enumerate().filter_map(|(i, face)| {
+            let [a, b, c] = face.map(|v| &self.vertices[v]);
+            let (distance, u, v) = ray_triangle(ray, [a, b, c])?;
+            let normal = match &self.normals {
+                Some(normals) => normals[face[0]] * (1.0 - u - v) + normals[face[1]] * u + normals[face[2]] * v,
+                None => (b - a).cross(&(c - a)),
+            };
+            Some(RayHit { distance, normal: normal.normalize(), uv: [u, v], triangle: Some(i) })
+        }).min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    // Среднее вершин
+    pub fn get_center(&self) -> Result<Point3> {
+        if self.vertices.is_empty() {
+            return Err(Error::EmptyMesh);
+        }
+        let sum = self.vertices.iter().fold(Point3::zeros(), |acc, point| acc + point);
+        Ok(sum / self.vertices.len() as f64)
     }
 }
 
-pub trait Geometry {
-    fn get_surface_mesh(&self) -> Mesh;
-    fn get_edges_mesh(&self, bold: f32) -> Mesh;
+// Относительный допуск сшивания совпадающих вершин
+const WELD_TOLERANCE: f64 = 1e-9;
+// Угол между гранями, ниже которого ребро не попадает в каркас
+pub const WIREFRAME_CREASE_ANGLE: f64 = 1e-3;
+
+// Толщина полосок каркаса get_edges_mesh
+pub(crate) fn check_bold(bold: f32) -> Result<()> {
+    if bold > 0.0 && bold.is_finite() {
+        return Ok(());
+    }
+    Err(Error::InvalidParameter { name: "edge thickness", value: bold as f64, expected: "a positive number" })
+}
+
+pub trait Geometry: Send + Sync {
+    fn get_surface_mesh(&self) -> Result<Mesh>;
+    fn get_edges_mesh(&self, bold: f32) -> Result<Mesh>;
     fn minimal_bounding_volume(&self) -> MBV;
+
+    // Отрезки каркаса в координатах модели, для отрисовки линиями
+    fn get_wireframe(&self) -> Result<Vec<[Point3; 2]>> {
+        Ok(self.get_surface_mesh()?.feature_edges(WIREFRAME_CREASE_ANGLE))
+    }
+
+    // Ограничивающие объёмы в координатах модели. По умолчанию MBV считается
+    // размерами параллелепипеда с центром в начале координат
+    fn bounding_box(&self) -> Aabb {
+        let MBV(x, y, z) = self.minimal_bounding_volume();
+        Aabb::from_center_size(&Point3::zeros(), &Point3::new(x, y, z))
+    }
+
+    fn bounding_sphere(&self) -> BoundingSphere {
+        self.bounding_box().bounding_sphere()
+    }
+
+    // Луч в координатах модели
+    fn ray_cast(&self, ray: &Ray) -> Option<RayHit> {
+        self.get_surface_mesh().ok()?.ray_cast(ray)
+    }
 }
 
 pub fn generate_transform(aspect_ratio: f32) -> glam::Mat4 {
@@ -83,4 +308,74 @@ pub fn generate_transform(aspect_ratio: f32) -> glam::Mat4 {
         glam::Vec3::Z,
     );
     projection * view
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::ball::Ball;
+
+    fn assert_unit(v: &Point3) {
+        assert!((v.magnitude() - 1.0).abs() < 1e-9, "{:?}", v);
+    }
+
+    // без швов, чтобы у каждой вершины были все соседние треугольники
+    fn closed_sphere() -> Mesh {
+        let welded = Ball::icosphere(2.0, 3).get_surface_mesh().unwrap().welded(1e-9);
+        Mesh::new(welded.vertices, welded.indices)
+    }
+
+    #[test]
+    fn smooth_normals_of_a_sphere_are_radial() {
+        let sphere = closed_sphere();
+        for (v, n) in sphere.vertices.iter().zip(sphere.smooth_normals()) {
+            assert_unit(&n);
+            assert!(n.dot(&v.normalize()) > 0.9999, "{:?} at {:?}", n, v);
+        }
+    }
+
+    #[test]
+    fn flat_shaded_vertices_take_the_face_normal() {
+        let sphere = closed_sphere();
+        let flat = sphere.flat_shaded();
+        assert_eq!(flat.vertices.len(), 3 * sphere.indices.len());
+        let normals = flat.normals.as_ref().unwrap();
+        for (face, flat_face) in sphere.indices.iter().zip(&flat.indices) {
+            let expected = face_normal(&sphere.vertices, face).normalize();
+            for &i in flat_face {
+                assert!((normals[i] - expected).magnitude() < 1e-12);
+            }
+            // грани обходятся против часовой стрелки снаружи: нормаль смотрит наружу
+            assert!(expected.dot(&flat.vertices[flat_face[0]]) > 0.0);
+        }
+    }
+
+    // Касательные, пересчитанные по UV, единичные, перпендикулярны нормали и направлены
+    // по росту u - на восток, как у аналитических касательных сферы
+    #[test]
+    fn tangents_follow_increasing_u() {
+        let mut sphere = Ball::uv_sphere(1.0, 48, 24).get_surface_mesh().unwrap();
+        let analytic = sphere.tangents.take().unwrap();
+        sphere.bitangents = None;
+        sphere.ensure_tangents();
+
+        let tangents = sphere.tangents.as_ref().unwrap();
+        let bitangents = sphere.bitangents.as_ref().unwrap();
+        let normals = sphere.normals.as_ref().unwrap();
+        // по одной вершине на каждом полюсе не входит ни в один треугольник
+        let used: HashSet<usize> = sphere.indices.iter().flatten().copied().collect();
+        assert_eq!(used.len(), sphere.vertices.len() - 2);
+        for i in used {
+            let (t, b, n) = (tangents[i], bitangents[i], normals[i]);
+            assert_unit(&t);
+            assert_unit(&b);
+            assert!(t.dot(&n).abs() < 1e-9);
+            assert!(b.dot(&n).abs() < 1e-9 && b.dot(&t).abs() < 1e-9);
+            // у полюсов восток меняется от треугольника к треугольнику
+            if n.z.abs() < 0.95 {
+                assert!(t.dot(&analytic[i]) > 0.99, "{:?} vs {:?}", t, analytic[i]);
+                let east = Point3::new(-n.y, n.x, 0.0).normalize();
+                assert!(t.dot(&east) > 0.99);
+            }
+        }
+    }
+}
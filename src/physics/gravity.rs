@@ -0,0 +1,55 @@
+use rayon::prelude::*;
+
+use crate::physics::geometry::{PARALLEL_MIN_LEN, Point3};
+
+// Массивное тело, движение которого задано заранее (Солнце, планеты)
+#[derive(Debug, Clone, Copy)]
+pub struct Attractor {
+    pub position: Point3,
+    pub mu: f64,
+}
+
+// Безмассовая частица: притягивается массивными телами, но сама на них не влияет
+#[derive(Debug, Clone, Copy)]
+pub struct TestParticle {
+    pub position: Point3,
+    pub velocity: Point3,
+}
+
+pub fn acceleration(position: &Point3, attractors: &[Attractor], softening: f64) -> Point3 {
+    let eps2 = softening * softening;
+    // суммирование в фиксированном порядке тел
+    attractors.iter().fold(Point3::zeros(), |acc, attractor| {
+        let d = attractor.position - position;
+        let r2 = d.magnitude_squared() + eps2;
+        acc + d * (attractor.mu / (r2 * r2.sqrt()))
+    })
+}
+
+// Ускорение центрального тела attractors[0] со стороны остальных. Система отсчёта связана
+// с центральным телом, поэтому на частицы оно действует с обратным знаком (косвенный член).
+// Без него у неподвижного Солнца нет треугольных точек Лагранжа
+fn frame_acceleration(attractors: &[Attractor]) -> Point3 {
+    match attractors.split_first() {
+        Some((central, others)) => acceleration(&central.position, others, 0.0),
+        None => Point3::zeros(),
+    }
+}
+
+// Шаг leapfrog (kick-drift-kick). attractors_at(t) возвращает массивные тела в момент t,
+// первым - центральное тело, от которого отсчитываются координаты.
+// Частицы независимы, поэтому параллельный результат совпадает с последовательным
+pub fn step_test_particles<F>(particles: &mut [TestParticle], attractors_at: F, t: f64, dt: f64, softening: f64)
+where
+    F: Fn(f64) -> Vec<Attractor>,
+{
+    let start = attractors_at(t);
+    let end = attractors_at(t + dt);
+    let (start_frame, end_frame) = (frame_acceleration(&start), frame_acceleration(&end));
+
+    particles.par_iter_mut().with_min_len(PARALLEL_MIN_LEN).for_each(|p| {
+        p.velocity += (acceleration(&p.position, &start, softening) - start_frame) * (0.5 * dt);
+        p.position += p.velocity * dt;
+        p.velocity += (acceleration(&p.position, &end, softening) - end_frame) * (0.5 * dt);
+    });
+}
@@ -0,0 +1,1031 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::error::{Error, Result};
+use crate::physics::geometry::{Geometry, Mesh, Point3, face_normal, normalize_or_zero};
+
+// Форматы файлов мешей. UV внутри Mesh отсчитываются от верхнего края текстуры (как в glTF),
+// в OBJ и PLY v растёт вверх и при чтении/записи переворачивается.
+// glTF по спецификации Y-up, сцена Z-up: оси поворачиваются при чтении и записи
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshFormat {
+    Obj,
+    Gltf,
+    Glb,
+    Stl,
+    Ply,
+}
+
+impl MeshFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "obj" => Some(MeshFormat::Obj),
+            "gltf" => Some(MeshFormat::Gltf),
+            "glb" => Some(MeshFormat::Glb),
+            "stl" => Some(MeshFormat::Stl),
+            "ply" => Some(MeshFormat::Ply),
+            _ => None,
+        }
+    }
+}
+
+// Ошибка разбора содержимого; путь к файлу добавляет load_mesh.
+// line считается с 1 и известна только для текстовых форматов
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ParseError {
+    fn at(self, path: &Path) -> Error {
+        Error::Parse { path: path.to_path_buf(), line: self.line, message: self.message }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl From<String> for ParseError {
+    fn from(message: String) -> Self {
+        Self { line: None, message }
+    }
+}
+
+impl From<&str> for ParseError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl From<std::num::ParseFloatError> for ParseError {
+    fn from(e: std::num::ParseFloatError) -> Self {
+        e.to_string().into()
+    }
+}
+
+impl From<std::num::ParseIntError> for ParseError {
+    fn from(e: std::num::ParseIntError) -> Self {
+        e.to_string().into()
+    }
+}
+
+impl From<std::str::Utf8Error> for ParseError {
+    fn from(e: std::str::Utf8Error) -> Self {
+        e.to_string().into()
+    }
+}
+
+impl From<serde_json::Error> for ParseError {
+    fn from(e: serde_json::Error) -> Self {
+        let message = e.to_string();
+        let message = message.split(" at line ").next().unwrap_or(&message).to_string();
+        Self { line: Some(e.line()).filter(|&line| line > 0), message }
+    }
+}
+
+fn format_of(path: &Path) -> Result<MeshFormat> {
+    MeshFormat::from_path(path).ok_or_else(|| Error::io(path, io::Error::new(io::ErrorKind::Unsupported, "unknown mesh format")))
+}
+
+pub fn load_mesh<P: AsRef<Path>>(path: P) -> Result<Mesh> {
+    let path = path.as_ref();
+    let text = || fs::read_to_string(path).map_err(|e| Error::io(path, e));
+    let bytes = || fs::read(path).map_err(|e| Error::io(path, e));
+    let mesh = match format_of(path)? {
+        MeshFormat::Obj => read_obj(&text()?),
+        MeshFormat::Gltf => read_gltf(&text()?, path.parent()),
+        MeshFormat::Glb => read_glb(&bytes()?, path.parent()),
+        MeshFormat::Stl => read_stl(&bytes()?),
+        MeshFormat::Ply => read_ply(&bytes()?),
+    };
+    mesh.map_err(|e| e.at(path))
+}
+
+pub fn save_mesh<P: AsRef<Path>>(mesh: &Mesh, path: P) -> Result<()> {
+    let path = path.as_ref();
+    let contents = match format_of(path)? {
+        MeshFormat::Obj => write_obj(mesh).into_bytes(),
+        MeshFormat::Gltf => write_gltf(mesh).into_bytes(),
+        MeshFormat::Glb => write_glb(mesh),
+        MeshFormat::Stl => write_stl(mesh),
+        MeshFormat::Ply => write_ply(mesh).into_bytes(),
+    };
+    fs::write(path, contents).map_err(|e| Error::io(path, e))
+}
+
+// Поверхность любой геометрии в файл, например для просмотра в Blender или MeshLab
+pub fn save_geometry<P: AsRef<Path>>(geometry: &dyn Geometry, path: P) -> Result<()> {
+    save_mesh(&geometry.get_surface_mesh()?, path)
+}
+
+// Индексы за пределами вершин и атрибуты не той длины отбрасываются здесь, а не в рендере
+fn validated(mesh: Mesh) -> Result<Mesh, ParseError> {
+    let count = mesh.vertices.len();
+    if let Some(face) = mesh.indices.iter().find(|face| face.iter().any(|&i| i >= count)) {
+        return Err(format!("face {:?} refers to a missing vertex (vertices: {})", face, count).into());
+    }
+    let normals_ok = mesh.normals.as_ref().is_none_or(|n| n.len() == count);
+    let uvs_ok = mesh.uvs.as_ref().is_none_or(|uv| uv.len() == count);
+    if !normals_ok || !uvs_ok {
+        return Err("attribute count differs from vertex count".into());
+    }
+    Ok(mesh)
+}
+
+fn parse_floats<'a, const N: usize>(tokens: &mut impl Iterator<Item = &'a str>) -> Result<[f64; N], ParseError> {
+    let mut values = [0.0; N];
+    for value in values.iter_mut() {
+        *value = tokens.next().ok_or("missing coordinate")?.parse()?;
+    }
+    Ok(values)
+}
+
+// ---------------------------------------------------------------- Wavefront OBJ
+
+// Индекс OBJ: с единицы, отрицательный отсчитывается от конца уже прочитанного списка
+fn obj_index(token: &str, len: usize) -> Result<usize, ParseError> {
+    let index: i64 = token.parse()?;
+    let resolved = match index {
+        i if i > 0 => i - 1,
+        i if i < 0 => len as i64 + i,
+        _ => -1,
+    };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(format!("index {} out of range (list length {})", index, len).into());
+    }
+    Ok(resolved as usize)
+}
+
+// Многоугольники разбиваются веером; вершина меша - уникальная тройка v/vt/vn.
+// Если хотя бы у одного угла нет vt или vn, соответствующий атрибут не загружается
+pub fn read_obj(text: &str) -> Result<Mesh, ParseError> {
+    let (mut positions, mut texcoords, mut normals) = (Vec::new(), Vec::new(), Vec::new());
+    let mut mesh = Mesh::new(Vec::new(), Vec::new());
+    let (mut mesh_uvs, mut mesh_normals) = (Vec::new(), Vec::new());
+    let (mut all_uvs, mut all_normals) = (true, true);
+    let mut corners: HashMap<(usize, Option<usize>, Option<usize>), usize> = HashMap::new();
+
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("");
+        let mut tokens = line.split_whitespace();
+        let parsed: Result<(), ParseError> = (|| {
+            match tokens.next() {
+                Some("v") => positions.push(Point3::from(parse_floats::<3>(&mut tokens)?)),
+                Some("vn") => normals.push(Point3::from(parse_floats::<3>(&mut tokens)?)),
+                Some("vt") => {
+                    let [u, v] = parse_floats::<2>(&mut tokens)?;
+                    texcoords.push([u, 1.0 - v]);
+                }
+                Some("f") => {
+                    let mut face = Vec::new();
+                    for corner in tokens.by_ref() {
+                        let mut parts = corner.split('/');
+                        let v = obj_index(parts.next().unwrap_or(""), positions.len())?;
+                        let vt = match parts.next() {
+                            Some(t) if !t.is_empty() => Some(obj_index(t, texcoords.len())?),
+                            _ => None,
+                        };
+                        let vn = match parts.next() {
+                            Some(n) if !n.is_empty() => Some(obj_index(n, normals.len())?),
+                            _ => None,
+                        };
+                        all_uvs &= vt.is_some();
+                        all_normals &= vn.is_some();
+                        let index = *corners.entry((v, vt, vn)).or_insert_with(|| {
+                            mesh.vertices.push(positions[v]);
+                            mesh_uvs.push(vt.map_or([0.0, 0.0], |t| texcoords[t]));
+                            mesh_normals.push(vn.map_or(Point3::zeros(), |n| normals[n]));
+                            mesh.vertices.len() - 1
+                        });
+                        face.push(index);
+                    }
+                    if face.len() < 3 {
+                        return Err("face with fewer than 3 vertices".into());
+                    }
+                    for k in 1..face.len() - 1 {
+                        mesh.indices.push([face[0], face[k], face[k + 1]]);
+                    }
+                }
+                _ => (),
+            }
+            Ok(())
+        })();
+        parsed.map_err(|e| ParseError { line: Some(line_number + 1), ..e })?;
+    }
+
+    if all_uvs && !mesh.vertices.is_empty() {
+        mesh.uvs = Some(mesh_uvs);
+    }
+    if all_normals && !mesh.vertices.is_empty() {
+        mesh.normals = Some(mesh_normals);
+    }
+    validated(mesh)
+}
+
+pub fn write_obj(mesh: &Mesh) -> String {
+    let mut out = format!("# {} vertices, {} triangles\n", mesh.vertices.len(), mesh.indices.len());
+    for v in &mesh.vertices {
+        out += &format!("v {} {} {}\n", v.x, v.y, v.z);
+    }
+    for uv in mesh.uvs.iter().flatten() {
+        out += &format!("vt {} {}\n", uv[0], 1.0 - uv[1]);
+    }
+    for n in mesh.normals.iter().flatten() {
+        out += &format!("vn {} {} {}\n", n.x, n.y, n.z);
+    }
+    let corner = |i: usize| match (mesh.uvs.is_some(), mesh.normals.is_some()) {
+        (true, true) => format!("{0}/{0}/{0}", i + 1),
+        (true, false) => format!("{0}/{0}", i + 1),
+        (false, true) => format!("{0}//{0}", i + 1),
+        (false, false) => format!("{}", i + 1),
+    };
+    for &[a, b, c] in &mesh.indices {
+        out += &format!("f {} {} {}\n", corner(a), corner(b), corner(c));
+    }
+    out
+}
+
+// ---------------------------------------------------------------- STL
+
+const STL_HEADER_SIZE: usize = 80;
+const STL_TRIANGLE_SIZE: usize = 50;
+
+fn read_f32_le(data: &[u8], offset: usize) -> f64 {
+    f32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as f64
+}
+
+// STL хранит отдельные треугольники: одинаковые по координатам вершины сшиваются.
+// Нормали граней не загружаются, сглаженные считает Mesh::ensure_normals
+pub fn read_stl(data: &[u8]) -> Result<Mesh, ParseError> {
+    let triangles = if data.len() >= STL_HEADER_SIZE + 4
+        && STL_HEADER_SIZE + 4 + STL_TRIANGLE_SIZE * u32::from_le_bytes(data[80..84].try_into().unwrap()) as usize == data.len()
+    {
+        let count = (data.len() - STL_HEADER_SIZE - 4) / STL_TRIANGLE_SIZE;
+        (0..count).map(|t| {
+            let base = STL_HEADER_SIZE + 4 + t * STL_TRIANGLE_SIZE + 12;
+            [0, 1, 2].map(|k| {
+                let offset = base + 12 * k;
+                Point3::new(read_f32_le(data, offset), read_f32_le(data, offset + 4), read_f32_le(data, offset + 8))
+            })
+        }).collect()
+    } else {
+        let text = std::str::from_utf8(data).map_err(|_| "not a binary STL and not valid ASCII STL")?;
+        if !text.trim_start().starts_with("solid") {
+            return Err("not a binary STL and not valid ASCII STL".into());
+        }
+        let mut corners = Vec::new();
+        let mut tokens = text.split_whitespace();
+        while let Some(token) = tokens.next() {
+            if token == "vertex" {
+                corners.push(Point3::from(parse_floats::<3>(&mut tokens)?));
+            }
+        }
+        if corners.len() % 3 != 0 {
+            return Err("ASCII STL: vertex count is not a multiple of 3".into());
+        }
+        corners.chunks(3).map(|c| [c[0], c[1], c[2]]).collect::<Vec<_>>()
+    };
+
+    let mut mesh = Mesh::new(Vec::new(), Vec::new());
+    // + 0.0 превращает -0.0 в 0.0, чтобы они сшивались
+    let mut welded: HashMap<[u64; 3], usize> = HashMap::new();
+    for triangle in triangles {
+        let face = triangle.map(|p| {
+            let key = [(p.x + 0.0).to_bits(), (p.y + 0.0).to_bits(), (p.z + 0.0).to_bits()];
+            *welded.entry(key).or_insert_with(|| {
+                mesh.vertices.push(p);
+                mesh.vertices.len() - 1
+            })
+        });
+        mesh.indices.push(face);
+    }
+    validated(mesh)
+}
+
+// Двоичный STL с нормалями граней
+pub fn write_stl(mesh: &Mesh) -> Vec<u8> {
+    let mut out = vec![0u8; STL_HEADER_SIZE];
+    let header = b"planets_simulator";
+    out[..header.len()].copy_from_slice(header);
+    out.extend((mesh.indices.len() as u32).to_le_bytes());
+    for face in &mesh.indices {
+        let normal = normalize_or_zero(face_normal(&mesh.vertices, face));
+        for p in [normal, mesh.vertices[face[0]], mesh.vertices[face[1]], mesh.vertices[face[2]]] {
+            for x in [p.x, p.y, p.z] {
+                out.extend((x as f32).to_le_bytes());
+            }
+        }
+        out.extend(0u16.to_le_bytes());
+    }
+    out
+}
+
+// ---------------------------------------------------------------- PLY
+
+#[derive(Debug, Clone, Copy)]
+enum PlyType {
+    I8, U8, I16, U16, I32, U32, F32, F64,
+}
+
+impl PlyType {
+    fn parse(name: &str) -> Result<Self, ParseError> {
+        Ok(match name {
+            "char" | "int8" => PlyType::I8,
+            "uchar" | "uint8" => PlyType::U8,
+            "short" | "int16" => PlyType::I16,
+            "ushort" | "uint16" => PlyType::U16,
+            "int" | "int32" => PlyType::I32,
+            "uint" | "uint32" => PlyType::U32,
+            "float" | "float32" => PlyType::F32,
+            "double" | "float64" => PlyType::F64,
+            _ => return Err(format!("unknown PLY type {}", name).into()),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            PlyType::I8 | PlyType::U8 => 1,
+            PlyType::I16 | PlyType::U16 => 2,
+            PlyType::I32 | PlyType::U32 | PlyType::F32 => 4,
+            PlyType::F64 => 8,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum PlyProperty {
+    Scalar { name: String, ty: PlyType },
+    List { name: String, count_ty: PlyType, item_ty: PlyType },
+}
+
+#[derive(Debug)]
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+enum PlyBody<'a> {
+    Ascii(std::str::SplitWhitespace<'a>),
+    Binary { data: &'a [u8], pos: usize, big_endian: bool },
+}
+
+impl PlyBody<'_> {
+    fn read(&mut self, ty: PlyType) -> Result<f64, ParseError> {
+        match self {
+            PlyBody::Ascii(tokens) => Ok(tokens.next().ok_or("unexpected end of PLY data")?.parse()?),
+            PlyBody::Binary { data, pos, big_endian } => {
+                let size = ty.size();
+                let bytes = data.get(*pos..*pos + size).ok_or("unexpected end of PLY data")?;
+                *pos += size;
+                let mut buf = [0u8; 8];
+                buf[..size].copy_from_slice(bytes);
+                if *big_endian {
+                    buf[..size].reverse();
+                }
+                Ok(match ty {
+                    PlyType::I8 => buf[0] as i8 as f64,
+                    PlyType::U8 => buf[0] as f64,
+                    PlyType::I16 => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+                    PlyType::U16 => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+                    PlyType::I32 => i32::from_le_bytes(buf[..4].try_into().unwrap()) as f64,
+                    PlyType::U32 => u32::from_le_bytes(buf[..4].try_into().unwrap()) as f64,
+                    PlyType::F32 => f32::from_le_bytes(buf[..4].try_into().unwrap()) as f64,
+                    PlyType::F64 => f64::from_le_bytes(buf),
+                })
+            }
+        }
+    }
+}
+
+// Поддерживаются ascii, binary_little_endian и binary_big_endian. Из элемента vertex берутся
+// x y z, nx ny nz и u v (или s t, texture_u texture_v), из face - список vertex_indices
+pub fn read_ply(data: &[u8]) -> Result<Mesh, ParseError> {
+    let marker = b"end_header";
+    let marker_pos = data.windows(marker.len()).position(|w| w == marker).ok_or("PLY: no end_header")?;
+    let body_start = data[marker_pos..].iter().position(|&b| b == b'\n').map(|p| marker_pos + p + 1).unwrap_or(data.len());
+    let header = std::str::from_utf8(&data[..marker_pos])?;
+
+    let mut lines = header.lines().map(str::trim);
+    if lines.next() != Some("ply") {
+        return Err("not a PLY file".into());
+    }
+    let mut format = None;
+    let mut elements: Vec<PlyElement> = Vec::new();
+    for line in lines {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["format", f, _] => format = Some(f.to_string()),
+            ["element", name, count] => elements.push(PlyElement { name: name.to_string(), count: count.parse()?, properties: Vec::new() }),
+            ["property", "list", count_ty, item_ty, name] => elements.last_mut().ok_or("PLY: property before element")?.properties.push(
+                PlyProperty::List { name: name.to_string(), count_ty: PlyType::parse(count_ty)?, item_ty: PlyType::parse(item_ty)? }
+            ),
+            ["property", ty, name] => elements.last_mut().ok_or("PLY: property before element")?.properties.push(
+                PlyProperty::Scalar { name: name.to_string(), ty: PlyType::parse(ty)? }
+            ),
+            _ => (),
+        }
+    }
+
+    let body = &data[body_start..];
+    let mut reader = match format.as_deref() {
+        Some("ascii") => PlyBody::Ascii(std::str::from_utf8(body)?.split_whitespace()),
+        Some("binary_little_endian") => PlyBody::Binary { data: body, pos: 0, big_endian: false },
+        Some("binary_big_endian") => PlyBody::Binary { data: body, pos: 0, big_endian: true },
+        other => return Err(format!("unsupported PLY format {:?}", other).into()),
+    };
+
+    let mut mesh = Mesh::new(Vec::new(), Vec::new());
+    let (mut normals, mut uvs) = (Vec::new(), Vec::new());
+    let mut has_normals = false;
+    let mut has_uvs = false;
+    for element in &elements {
+        for _ in 0..element.count {
+            let mut scalars: HashMap<&str, f64> = HashMap::new();
+            let mut face = Vec::new();
+            for property in &element.properties {
+                match property {
+                    PlyProperty::Scalar { name, ty } => {
+                        scalars.insert(name, reader.read(*ty)?);
+                    }
+                    PlyProperty::List { name, count_ty, item_ty } => {
+                        let count = reader.read(*count_ty)? as usize;
+                        let items = (0..count).map(|_| reader.read(*item_ty)).collect::<Result<Vec<f64>, _>>()?;
+                        if name == "vertex_indices" || name == "vertex_index" {
+                            face = items.into_iter().map(|i| i as usize).collect();
+                        }
+                    }
+                }
+            }
+
+            match element.name.as_str() {
+                "vertex" => {
+                    let get = |names: &[&str]| names.iter().find_map(|n| scalars.get(n).copied());
+                    let position = [get(&["x"]), get(&["y"]), get(&["z"])];
+                    let [Some(x), Some(y), Some(z)] = position else {
+                        return Err("PLY vertex without x, y, z".into());
+                    };
+                    mesh.vertices.push(Point3::new(x, y, z));
+                    if let [Some(nx), Some(ny), Some(nz)] = [get(&["nx"]), get(&["ny"]), get(&["nz"])] {
+                        has_normals = true;
+                        normals.push(Point3::new(nx, ny, nz));
+                    }
+                    let u = get(&["u", "s", "texture_u"]);
+                    let v = get(&["v", "t", "texture_v"]);
+                    if let (Some(u), Some(v)) = (u, v) {
+                        has_uvs = true;
+                        uvs.push([u, 1.0 - v]);
+                    }
+                }
+                "face" => {
+                    if face.len() < 3 {
+                        return Err("PLY face with fewer than 3 vertices".into());
+                    }
+                    for k in 1..face.len() - 1 {
+                        mesh.indices.push([face[0], face[k], face[k + 1]]);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+    if has_normals {
+        mesh.normals = Some(normals);
+    }
+    if has_uvs {
+        mesh.uvs = Some(uvs);
+    }
+    validated(mesh)
+}
+
+// Текстовый PLY: его удобно читать глазами и понимают все редакторы
+pub fn write_ply(mesh: &Mesh) -> String {
+    let mut out = String::from("ply\nformat ascii 1.0\ncomment planets_simulator\n");
+    out += &format!("element vertex {}\n", mesh.vertices.len());
+    out += "property float x\nproperty float y\nproperty float z\n";
+    if mesh.normals.is_some() {
+        out += "property float nx\nproperty float ny\nproperty float nz\n";
+    }
+    if mesh.uvs.is_some() {
+        out += "property float s\nproperty float t\n";
+    }
+    out += &format!("element face {}\nproperty list uchar uint vertex_indices\nend_header\n", mesh.indices.len());
+
+    for (i, v) in mesh.vertices.iter().enumerate() {
+        out += &format!("{} {} {}", v.x, v.y, v.z);
+        if let Some(normals) = &mesh.normals {
+            out += &format!(" {} {} {}", normals[i].x, normals[i].y, normals[i].z);
+        }
+        if let Some(uvs) = &mesh.uvs {
+            out += &format!(" {} {}", uvs[i][0], 1.0 - uvs[i][1]);
+        }
+        out.push('\n');
+    }
+    for &[a, b, c] in &mesh.indices {
+        out += &format!("3 {} {} {}\n", a, b, c);
+    }
+    out
+}
+
+// ---------------------------------------------------------------- glTF 2.0
+
+const GLB_MAGIC: u32 = 0x4654_6C67; // "glTF"
+const GLB_CHUNK_JSON: u32 = 0x4E4F_534A;
+const GLB_CHUNK_BIN: u32 = 0x004E_4942;
+const GLTF_FLOAT: u64 = 5126;
+const GLTF_UNSIGNED_INT: u64 = 5125;
+const GLTF_ARRAY_BUFFER: u64 = 34962;
+const GLTF_ELEMENT_ARRAY_BUFFER: u64 = 34963;
+const GLTF_TRIANGLES: u64 = 4;
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for k in 0..4 {
+            if k <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * k) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode(text: &str) -> Result<Vec<u8>, ParseError> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let (mut acc, mut bits) = (0u32, 0);
+    for c in text.bytes().filter(|&c| c != b'=' && !c.is_ascii_whitespace()) {
+        let value = BASE64_ALPHABET.iter().position(|&a| a == c).ok_or("invalid base64 data")? as u32;
+        acc = acc << 6 | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+// Y-up glTF <-> Z-up сцены
+fn from_gltf_axes(p: [f64; 3]) -> Point3 {
+    Point3::new(p[0], -p[2], p[1])
+}
+
+fn to_gltf_axes(p: &Point3) -> [f64; 3] {
+    [p.x, p.z, -p.y]
+}
+
+fn gltf_buffers(document: &Value, base_dir: Option<&Path>, glb_bin: Option<&[u8]>) -> Result<Vec<Vec<u8>>, ParseError> {
+    let buffers = document["buffers"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+    buffers.iter().map(|buffer| match buffer["uri"].as_str() {
+        Some(uri) if uri.starts_with("data:") => {
+            let (_, data) = uri.split_once(',').ok_or("malformed data URI")?;
+            base64_decode(data)
+        }
+        Some(uri) => fs::read(base_dir.unwrap_or(Path::new(".")).join(uri))
+            .map_err(|e| format!("cannot read buffer {}: {}", uri, e).into()),
+        None => glb_bin.map(<[u8]>::to_vec).ok_or_else(|| "buffer without uri outside of GLB".into()),
+    }).collect()
+}
+
+// Значения accessor-а в f64, по components на элемент
+fn gltf_accessor(document: &Value, buffers: &[Vec<u8>], index: u64) -> Result<(Vec<f64>, usize), ParseError> {
+    let accessor = &document["accessors"][index as usize];
+    if accessor.is_null() {
+        return Err(format!("missing accessor {}", index).into());
+    }
+    if !accessor["sparse"].is_null() {
+        return Err("sparse accessors are not supported".into());
+    }
+    let count = accessor["count"].as_u64().ok_or("accessor without count")? as usize;
+    let components = match accessor["type"].as_str() {
+        Some("SCALAR") => 1,
+        Some("VEC2") => 2,
+        Some("VEC3") => 3,
+        Some("VEC4") => 4,
+        other => return Err(format!("unsupported accessor type {:?}", other).into()),
+    };
+    let ty = match accessor["componentType"].as_u64() {
+        Some(5120) => PlyType::I8,
+        Some(5121) => PlyType::U8,
+        Some(5122) => PlyType::I16,
+        Some(5123) => PlyType::U16,
+        Some(GLTF_UNSIGNED_INT) => PlyType::U32,
+        Some(GLTF_FLOAT) => PlyType::F32,
+        other => return Err(format!("unsupported component type {:?}", other).into()),
+    };
+    // без bufferView accessor заполнен нулями
+    let Some(view_index) = accessor["bufferView"].as_u64() else {
+        return Ok((vec![0.0; count * components], components));
+    };
+    let view = &document["bufferViews"][view_index as usize];
+    let buffer = buffers.get(view["buffer"].as_u64().unwrap_or(0) as usize).ok_or("missing buffer")?;
+    let element_size = ty.size() * components;
+    let stride = view["byteStride"].as_u64().map_or(element_size, |s| s as usize);
+    let start = view["byteOffset"].as_u64().unwrap_or(0) as usize + accessor["byteOffset"].as_u64().unwrap_or(0) as usize;
+    let end = start + stride * count.saturating_sub(1) + element_size;
+    if count > 0 && end > buffer.len() {
+        return Err("accessor runs past the end of its buffer".into());
+    }
+
+    // нормированные целые (например, UV в u16) переводятся в [0, 1] или [-1, 1]
+    let scale = match (accessor["normalized"].as_bool().unwrap_or(false), ty) {
+        (true, PlyType::I8) => 1.0 / 127.0,
+        (true, PlyType::U8) => 1.0 / 255.0,
+        (true, PlyType::I16) => 1.0 / 32767.0,
+        (true, PlyType::U16) => 1.0 / 65535.0,
+        _ => 1.0,
+    };
+    let mut values = Vec::with_capacity(count * components);
+    for i in 0..count {
+        let mut element = PlyBody::Binary { data: &buffer[start + i * stride..], pos: 0, big_endian: false };
+        for _ in 0..components {
+            values.push(element.read(ty)? * scale);
+        }
+    }
+    Ok((values, components))
+}
+
+// Собирает треугольные примитивы всех мешей документа в один Mesh. Трансформации узлов
+// не применяются; примитивы из точек и линий пропускаются
+fn gltf_mesh(document: &Value, buffers: &[Vec<u8>]) -> Result<Mesh, ParseError> {
+    let mut mesh = Mesh::new(Vec::new(), Vec::new());
+    let (mut normals, mut uvs) = (Vec::new(), Vec::new());
+    let (mut all_normals, mut all_uvs) = (true, true);
+    let primitives = document["meshes"].as_array().map(Vec::as_slice).unwrap_or(&[])
+        .iter()
+        .flat_map(|m| m["primitives"].as_array().map(Vec::as_slice).unwrap_or(&[]));
+    for primitive in primitives {
+        if primitive["mode"].as_u64().unwrap_or(GLTF_TRIANGLES) != GLTF_TRIANGLES {
+            continue;
+        }
+        let attributes = &primitive["attributes"];
+        let position = attributes["POSITION"].as_u64().ok_or("primitive without POSITION")?;
+        let (positions, _) = gltf_accessor(document, buffers, position)?;
+        let count = positions.len() / 3;
+        let first = mesh.vertices.len();
+        mesh.vertices.extend(positions.chunks(3).map(|p| from_gltf_axes([p[0], p[1], p[2]])));
+
+        match attributes["NORMAL"].as_u64() {
+            Some(index) => normals.extend(gltf_accessor(document, buffers, index)?.0.chunks(3).map(|n| from_gltf_axes([n[0], n[1], n[2]]))),
+            None => {
+                all_normals = false;
+                normals.extend(std::iter::repeat_n(Point3::zeros(), count));
+            }
+        }
+        match attributes["TEXCOORD_0"].as_u64() {
+            Some(index) => uvs.extend(gltf_accessor(document, buffers, index)?.0.chunks(2).map(|t| [t[0], t[1]])),
+            None => {
+                all_uvs = false;
+                uvs.extend(std::iter::repeat_n([0.0, 0.0], count));
+            }
+        }
+
+        let indices: Vec<usize> = match primitive["indices"].as_u64() {
+            Some(index) => gltf_accessor(document, buffers, index)?.0.iter().map(|&i| first + i as usize).collect(),
+            None => (first..first + count).collect(),
+        };
+        mesh.indices.extend(indices.chunks_exact(3).map(|f| [f[0], f[1], f[2]]));
+    }
+    if all_normals && !mesh.vertices.is_empty() {
+        mesh.normals = Some(normals);
+    }
+    if all_uvs && !mesh.vertices.is_empty() {
+        mesh.uvs = Some(uvs);
+    }
+    validated(mesh)
+}
+
+pub fn read_gltf(text: &str, base_dir: Option<&Path>) -> Result<Mesh, ParseError> {
+    let document: Value = serde_json::from_str(text)?;
+    let buffers = gltf_buffers(&document, base_dir, None)?;
+    gltf_mesh(&document, &buffers)
+}
+
+fn read_u32_le(data: &[u8], offset: usize) -> Result<u32, ParseError> {
+    let bytes = data.get(offset..offset + 4).ok_or("truncated GLB")?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+pub fn read_glb(data: &[u8], base_dir: Option<&Path>) -> Result<Mesh, ParseError> {
+    if read_u32_le(data, 0)? != GLB_MAGIC {
+        return Err("not a GLB file".into());
+    }
+    if read_u32_le(data, 4)? != 2 {
+        return Err("only glTF 2.0 is supported".into());
+    }
+    let (mut json_chunk, mut bin_chunk) = (None, None);
+    let mut offset = 12;
+    while offset + 8 <= data.len() {
+        let length = read_u32_le(data, offset)? as usize;
+        let kind = read_u32_le(data, offset + 4)?;
+        let chunk = data.get(offset + 8..offset + 8 + length).ok_or("truncated GLB chunk")?;
+        match kind {
+            GLB_CHUNK_JSON => json_chunk = Some(chunk),
+            GLB_CHUNK_BIN => bin_chunk = Some(chunk),
+            _ => (),
+        }
+        offset += 8 + length;
+    }
+    let document: Value = serde_json::from_slice(json_chunk.ok_or("GLB without JSON chunk")?)?;
+    let buffers = gltf_buffers(&document, base_dir, bin_chunk)?;
+    gltf_mesh(&document, &buffers)
+}
+
+// Документ и двоичный буфер: позиции, нормали, UV (f32) и индексы (u32)
+fn gltf_document(mesh: &Mesh) -> (Value, Vec<u8>) {
+    let mut bin = Vec::new();
+    let mut views = Vec::new();
+    let mut accessors = Vec::new();
+    let mut attributes = serde_json::Map::new();
+
+    let mut push_vec3 = |name: &str, points: &[Point3], bin: &mut Vec<u8>, with_bounds: bool| {
+        let offset = bin.len();
+        let (mut min, mut max) = ([f64::MAX; 3], [f64::MIN; 3]);
+        for p in points {
+            let p = to_gltf_axes(p);
+            for k in 0..3 {
+                let x = p[k] as f32;
+                min[k] = min[k].min(x as f64);
+                max[k] = max[k].max(x as f64);
+                bin.extend(x.to_le_bytes());
+            }
+        }
+        views.push(json!({ "buffer": 0, "byteOffset": offset, "byteLength": bin.len() - offset, "target": GLTF_ARRAY_BUFFER }));
+        let mut accessor = json!({ "bufferView": views.len() - 1, "componentType": GLTF_FLOAT, "count": points.len(), "type": "VEC3" });
+        // для POSITION границы обязательны
+        if with_bounds && !points.is_empty() {
+            accessor["min"] = json!(min);
+            accessor["max"] = json!(max);
+        }
+        accessors.push(accessor);
+        attributes.insert(name.to_string(), json!(accessors.len() - 1));
+    };
+    push_vec3("POSITION", &mesh.vertices, &mut bin, true);
+    if let Some(normals) = &mesh.normals {
+        push_vec3("NORMAL", normals, &mut bin, false);
+    }
+
+    if let Some(uvs) = &mesh.uvs {
+        let offset = bin.len();
+        for uv in uvs {
+            bin.extend((uv[0] as f32).to_le_bytes());
+            bin.extend((uv[1] as f32).to_le_bytes());
+        }
+        views.push(json!({ "buffer": 0, "byteOffset": offset, "byteLength": bin.len() - offset, "target": GLTF_ARRAY_BUFFER }));
+        accessors.push(json!({ "bufferView": views.len() - 1, "componentType": GLTF_FLOAT, "count": uvs.len(), "type": "VEC2" }));
+        attributes.insert("TEXCOORD_0".to_string(), json!(accessors.len() - 1));
+    }
+
+    let offset = bin.len();
+    for face in &mesh.indices {
+        for &i in face {
+            bin.extend((i as u32).to_le_bytes());
+        }
+    }
+    views.push(json!({ "buffer": 0, "byteOffset": offset, "byteLength": bin.len() - offset, "target": GLTF_ELEMENT_ARRAY_BUFFER }));
+    accessors.push(json!({ "bufferView": views.len() - 1, "componentType": GLTF_UNSIGNED_INT, "count": 3 * mesh.indices.len(), "type": "SCALAR" }));
+
+    let document = json!({
+        "asset": { "version": "2.0", "generator": "planets_simulator" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "mesh": 0 }],
+        "meshes": [{ "primitives": [{ "attributes": attributes, "indices": accessors.len() - 1, "mode": GLTF_TRIANGLES }] }],
+        "accessors": accessors,
+        "bufferViews": views,
+        "buffers": [{ "byteLength": bin.len() }],
+    });
+    (document, bin)
+}
+
+// Текстовый glTF, буфер встроен в data URI
+pub fn write_gltf(mesh: &Mesh) -> String {
+    let (mut document, bin) = gltf_document(mesh);
+    document["buffers"][0]["uri"] = json!(format!("data:application/octet-stream;base64,{}", base64_encode(&bin)));
+    serde_json::to_string_pretty(&document).unwrap()
+}
+
+pub fn write_glb(mesh: &Mesh) -> Vec<u8> {
+    let (document, mut bin) = gltf_document(mesh);
+    // чанки выравниваются на 4 байта: JSON пробелами, BIN нулями
+    let mut json_bytes = serde_json::to_vec(&document).unwrap();
+    json_bytes.resize(json_bytes.len().next_multiple_of(4), b' ');
+    bin.resize(bin.len().next_multiple_of(4), 0);
+
+    let total = 12 + 8 + json_bytes.len() + 8 + bin.len();
+    let mut out = Vec::with_capacity(total);
+    for word in [GLB_MAGIC, 2, total as u32, json_bytes.len() as u32, GLB_CHUNK_JSON] {
+        out.extend(word.to_le_bytes());
+    }
+    out.extend(json_bytes);
+    out.extend((bin.len() as u32).to_le_bytes());
+    out.extend(GLB_CHUNK_BIN.to_le_bytes());
+    out.extend(bin);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    // Тетраэдр с нормалями и UV; координаты точно представимы в f32
+    fn tetrahedron() -> Mesh {
+        let vertices = vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ];
+        let mut mesh = Mesh::new(vertices, vec![[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]]);
+        mesh.normals = Some(vec![
+            Point3::new(-0.5, -0.5, -0.5),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ]);
+        mesh.uvs = Some(vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [0.5, 0.25]]);
+        mesh
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("planets_simulator_{}_{}", std::process::id(), name))
+    }
+
+    // Треугольники как тройки координат: не зависит от нумерации вершин
+    fn triangles(mesh: &Mesh) -> Vec<[Point3; 3]> {
+        mesh.indices.iter().map(|face| face.map(|i| mesh.vertices[i])).collect()
+    }
+
+    fn assert_same_surface(loaded: &Mesh, original: &Mesh) {
+        let (a, b) = (triangles(loaded), triangles(original));
+        assert_eq!(a.len(), b.len());
+        for (ta, tb) in a.iter().zip(&b) {
+            for (pa, pb) in ta.iter().zip(tb) {
+                assert!((pa - pb).magnitude() < 1e-6, "{:?} != {:?}", pa, pb);
+            }
+        }
+    }
+
+    // Нормали и UV сравниваются по углам треугольников
+    fn assert_same_attributes(loaded: &Mesh, original: &Mesh) {
+        assert_eq!(loaded.vertices.len(), original.vertices.len());
+        let (normals, expected_normals) = (loaded.normals.as_ref().unwrap(), original.normals.as_ref().unwrap());
+        let (uvs, expected_uvs) = (loaded.uvs.as_ref().unwrap(), original.uvs.as_ref().unwrap());
+        for (face, expected) in loaded.indices.iter().zip(&original.indices) {
+            for (&i, &j) in face.iter().zip(expected) {
+                assert!((normals[i] - expected_normals[j]).magnitude() < 1e-6);
+                assert!((uvs[i][0] - expected_uvs[j][0]).abs() < 1e-6 && (uvs[i][1] - expected_uvs[j][1]).abs() < 1e-6);
+            }
+        }
+    }
+
+    fn round_trip(name: &str) -> Mesh {
+        let path = temp_path(name);
+        save_mesh(&tetrahedron(), &path).unwrap();
+        let loaded = load_mesh(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        loaded
+    }
+
+    fn load_bytes(name: &str, data: &[u8]) -> Result<Mesh> {
+        let path = temp_path(name);
+        fs::write(&path, data).unwrap();
+        let loaded = load_mesh(&path);
+        fs::remove_file(&path).unwrap();
+        loaded
+    }
+
+    // Двоичный PLY с позициями и гранями в заданном порядке байт
+    fn binary_ply(mesh: &Mesh, big_endian: bool) -> Vec<u8> {
+        let format = if big_endian { "binary_big_endian" } else { "binary_little_endian" };
+        let mut out = format!(
+            "ply\nformat {} 1.0\nelement vertex {}\nproperty float x\nproperty float y\nproperty float z\n\
+             element face {}\nproperty list uchar int vertex_indices\nend_header\n",
+            format, mesh.vertices.len(), mesh.indices.len(),
+        ).into_bytes();
+        let push = |bytes: &mut Vec<u8>, mut word: [u8; 4]| {
+            if big_endian {
+                word.reverse();
+            }
+            bytes.extend(word);
+        };
+        for v in &mesh.vertices {
+            for x in [v.x, v.y, v.z] {
+                push(&mut out, (x as f32).to_le_bytes());
+            }
+        }
+        for face in &mesh.indices {
+            out.push(3);
+            for &i in face {
+                push(&mut out, (i as i32).to_le_bytes());
+            }
+        }
+        out
+    }
+
+    fn ascii_stl(mesh: &Mesh) -> String {
+        let mut out = String::from("solid test\n");
+        for triangle in triangles(mesh) {
+            out += "facet normal 0 0 0\nouter loop\n";
+            for p in triangle {
+                out += &format!("vertex {} {} {}\n", p.x, p.y, p.z);
+            }
+            out += "endloop\nendfacet\n";
+        }
+        out + "endsolid test\n"
+    }
+
+    #[test]
+    fn obj_round_trip() {
+        let loaded = round_trip("round_trip.obj");
+        assert_same_surface(&loaded, &tetrahedron());
+        assert_same_attributes(&loaded, &tetrahedron());
+    }
+
+    #[test]
+    fn gltf_and_glb_round_trip() {
+        for name in ["round_trip.gltf", "round_trip.glb"] {
+            let loaded = round_trip(name);
+            assert_same_surface(&loaded, &tetrahedron());
+            assert_same_attributes(&loaded, &tetrahedron());
+        }
+    }
+
+    // STL не хранит нормали вершин и UV, одинаковые вершины сшиваются обратно
+    #[test]
+    fn binary_and_ascii_stl_round_trip() {
+        let binary = round_trip("round_trip.stl");
+        assert_same_surface(&binary, &tetrahedron());
+        assert_eq!(binary.vertices.len(), 4);
+
+        let ascii = load_bytes("ascii.stl", ascii_stl(&tetrahedron()).as_bytes()).unwrap();
+        assert_same_surface(&ascii, &tetrahedron());
+        assert_eq!(ascii.vertices.len(), 4);
+    }
+
+    #[test]
+    fn ascii_and_binary_ply_round_trip() {
+        let ascii = round_trip("round_trip.ply");
+        assert_same_surface(&ascii, &tetrahedron());
+        assert_same_attributes(&ascii, &tetrahedron());
+
+        for (name, big_endian) in [("little.ply", false), ("big.ply", true)] {
+            let loaded = load_bytes(name, &binary_ply(&tetrahedron(), big_endian)).unwrap();
+            assert_same_surface(&loaded, &tetrahedron());
+            assert!(loaded.normals.is_none() && loaded.uvs.is_none());
+        }
+    }
+
+    #[test]
+    fn rejects_obj_indices_out_of_range() {
+        let vertices = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+        for face in ["f 1 2 4", "f 0 1 2", "f -1 -2 -4"] {
+            let result = load_bytes("bad_index.obj", format!("{}{}\n", vertices, face).as_bytes());
+            assert!(matches!(result, Err(Error::Parse { line: Some(4), .. })), "{}: {:?}", face, result);
+        }
+        // отрицательные индексы в пределах списка допустимы
+        let mesh = load_bytes("negative_index.obj", format!("{}f -3 -2 -1\n", vertices).as_bytes()).unwrap();
+        assert_eq!(mesh.indices, vec![[0, 1, 2]]);
+    }
+
+    #[test]
+    fn rejects_truncated_binary_data() {
+        let stl = write_stl(&tetrahedron());
+        assert!(matches!(load_bytes("truncated.stl", &stl[..stl.len() - 10]), Err(Error::Parse { line: None, .. })));
+
+        let glb = write_glb(&tetrahedron());
+        assert!(matches!(load_bytes("truncated.glb", &glb[..glb.len() - 10]), Err(Error::Parse { .. })));
+
+        for (name, big_endian) in [("truncated_little.ply", false), ("truncated_big.ply", true)] {
+            let ply = binary_ply(&tetrahedron(), big_endian);
+            assert!(matches!(load_bytes(name, &ply[..ply.len() - 3]), Err(Error::Parse { .. })));
+        }
+    }
+
+    #[test]
+    fn reports_missing_files_and_unknown_formats_with_the_path() {
+        let missing = temp_path("missing.obj");
+        assert!(matches!(load_mesh(&missing), Err(Error::Io { path, .. }) if path == missing));
+        let unknown = temp_path("mesh.xyz");
+        assert!(matches!(save_mesh(&tetrahedron(), &unknown), Err(Error::Io { path, .. }) if path == unknown));
+    }
+}
@@ -0,0 +1,524 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use nalgebra::{Matrix3, Matrix4, Vector4};
+
+use crate::error::Result;
+use crate::physics::geometry::{Mesh, Point3, face_normal, normalize_or_zero};
+
+// Вес плоскостей, удерживающих границу открытого меша при прореживании
+const BOUNDARY_QUADRIC_WEIGHT: f64 = 1e3;
+
+// Ребро по индексам вершин без учёта направления
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+impl Mesh {
+    // Сшивание вершин ближе tolerance друг к другу. Остаются положение и UV первой вершины группы,
+    // нормали усредняются; касательный базис пересчитывается по UV.
+    // Вырожденные после сшивания треугольники удаляются
+    pub fn welded(&self, tolerance: f64) -> Mesh {
+        let remap = self.weld_map(tolerance);
+        let mut new_index = vec![usize::MAX; self.vertices.len()];
+        let mut representatives = Vec::new();
+        for (i, &r) in remap.iter().enumerate() {
+            if r == i {
+                new_index[i] = representatives.len();
+                representatives.push(i);
+            }
+        }
+        let index = |i: usize| new_index[remap[i]];
+
+        let indices = self.indices.iter()
+            .map(|face| face.map(index))
+            .filter(|[a, b, c]| a != b && b != c && c != a)
+            .collect();
+        let normals = self.normals.as_ref().map(|normals| {
+            let mut merged = vec![Point3::zeros(); representatives.len()];
+            for (i, n) in normals.iter().enumerate() {
+                merged[index(i)] += n;
+            }
+            merged.into_iter().map(normalize_or_zero).collect()
+        });
+        let mut mesh = Mesh {
+            vertices: representatives.iter().map(|&i| self.vertices[i]).collect(),
+            indices,
+            normals,
+            uvs: self.uvs.as_ref().map(|uvs| representatives.iter().map(|&i| uvs[i]).collect()),
+            tangents: None,
+            bitangents: None,
+        };
+        mesh.ensure_tangents();
+        mesh
+    }
+
+    // Подразбиение Лупа, levels раз: каждый треугольник делится на 4, вершины сглаживаются.
+    // Соседство считается по совпадающим положениям, поэтому швы UV не раскрываются;
+    // края открытого меша сглаживаются как кривые. UV интерполируются линейно
+    pub fn loop_subdivided(&self, levels: usize) -> Mesh {
+        (0..levels).fold(self.clone(), |mesh, _| mesh.loop_subdivided_once())
+    }
+
+    fn loop_subdivided_once(&self) -> Mesh {
+        let position = self.coincident_vertices();
+
+        // противолежащие вершины рёбер и соседи вершин по сшитым положениям
+        let mut opposite: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for face in &self.indices {
+            let [a, b, c] = face.map(|i| position[i]);
+            for (u, v, w) in [(a, b, c), (b, c, a), (c, a, b)] {
+                opposite.entry(edge_key(u, v)).or_default().push(w);
+            }
+        }
+        let mut neighbours: HashMap<usize, HashSet<usize>> = HashMap::new();
+        let mut boundary_neighbours: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (&(u, v), faces) in &opposite {
+            neighbours.entry(u).or_default().insert(v);
+            neighbours.entry(v).or_default().insert(u);
+            if faces.len() != 2 {
+                boundary_neighbours.entry(u).or_default().push(v);
+                boundary_neighbours.entry(v).or_default().push(u);
+            }
+        }
+
+        let p = |i: usize| self.vertices[i];
+        let smoothed = |i: usize| -> Point3 {
+            let v = position[i];
+            match boundary_neighbours.get(&v) {
+                Some(boundary) if boundary.len() == 2 => p(v) * 0.75 + (p(boundary[0]) + p(boundary[1])) * 0.125,
+                // вершина с неоднозначной границей не двигается
+                Some(_) => p(v),
+                None => {
+                    // вершина, не входящая ни в одну грань, не двигается
+                    let Some(ring) = neighbours.get(&v) else { return p(v) };
+                    let n = ring.len() as f64;
+                    let beta = if ring.len() == 3 { 3.0 / 16.0 } else { 3.0 / (8.0 * n) };
+                    p(v) * (1.0 - n * beta) + ring.iter().map(|&j| p(j)).sum::<Point3>() * beta
+                }
+            }
+        };
+        let edge_point = |a: usize, b: usize| -> Point3 {
+            let (u, v) = (position[a], position[b]);
+            match opposite[&edge_key(u, v)].as_slice() {
+                [c, d] => (p(u) + p(v)) * 0.375 + (p(*c) + p(*d)) * 0.125,
+                _ => (p(u) + p(v)) * 0.5,
+            }
+        };
+
+        // старые вершины сохраняют свои индексы, новые добавляются по одной на ребро
+        let mut vertices: Vec<Point3> = (0..self.vertices.len()).map(smoothed).collect();
+        let mut uvs = self.uvs.clone();
+        let mut edge_vertex: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut indices = Vec::with_capacity(4 * self.indices.len());
+        for &[a, b, c] in &self.indices {
+            let mut midpoint = |u: usize, v: usize| *edge_vertex.entry(edge_key(u, v)).or_insert_with(|| {
+                vertices.push(edge_point(u, v));
+                if let Some(uvs) = uvs.as_mut() {
+                    uvs.push([(uvs[u][0] + uvs[v][0]) / 2.0, (uvs[u][1] + uvs[v][1]) / 2.0]);
+                }
+                vertices.len() - 1
+            });
+            let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+            indices.extend([[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]);
+        }
+
+        let mut mesh = Mesh { vertices, indices, normals: None, uvs, tangents: None, bitangents: None };
+        if self.normals.is_some() {
+            mesh.normals = Some(mesh.seamless_normals());
+        }
+        mesh.ensure_tangents();
+        mesh
+    }
+
+    // Сглаженные нормали, одинаковые у всех копий вершины на швах
+    pub fn seamless_normals(&self) -> Vec<Point3> {
+        let position = self.coincident_vertices();
+        let mut normals = vec![Point3::zeros(); self.vertices.len()];
+        for face in &self.indices {
+            let n = face_normal(&self.vertices, face);
+            for &i in face {
+                normals[position[i]] += n;
+            }
+        }
+        position.iter().map(|&p| normalize_or_zero(normals[p])).collect()
+    }
+
+    // Прореживание по квадрикам ошибки (Гарланд - Хекберт) до target_triangles треугольников:
+    // рёбра стягиваются в порядке роста ошибки, стягивания с переворотом граней или
+    // нарушением многообразия пропускаются. Меш сшивается по положениям, UV и касательные
+    // не сохраняются, нормали пересчитываются, если были
+    pub fn decimated(&self, target_triangles: usize) -> Mesh {
+        let position = self.coincident_vertices();
+        let mut vertices = self.vertices.clone();
+        let mut faces: Vec<[usize; 3]> = self.indices.iter()
+            .map(|face| face.map(|i| position[i]))
+            .filter(|[a, b, c]| a != b && b != c && c != a)
+            .collect();
+        let mut alive_faces = vec![true; faces.len()];
+        let mut alive_count = faces.len();
+        let mut removed = vec![false; vertices.len()];
+        let mut vertex_faces: Vec<Vec<usize>> = vec![Vec::new(); vertices.len()];
+        for (f, face) in faces.iter().enumerate() {
+            for &v in face {
+                vertex_faces[v].push(f);
+            }
+        }
+
+        let mut quadrics = vec![Matrix4::<f64>::zeros(); vertices.len()];
+        let mut edge_faces: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (f, face) in faces.iter().enumerate() {
+            let n = face_normal(&vertices, face);
+            let area = n.magnitude() / 2.0;
+            let n = normalize_or_zero(n);
+            let plane = Vector4::new(n.x, n.y, n.z, -n.dot(&vertices[face[0]]));
+            for &v in face {
+                quadrics[v] += plane * plane.transpose() * area;
+            }
+            for k in 0..3 {
+                edge_faces.entry(edge_key(face[k], face[(k + 1) % 3])).or_default().push(f);
+            }
+        }
+        // граница удерживается плоскостями, перпендикулярными граням вдоль граничных рёбер
+        for (&(a, b), adjacent) in &edge_faces {
+            if let [f] = adjacent.as_slice() {
+                let edge = vertices[b] - vertices[a];
+                let n = normalize_or_zero(edge.cross(&face_normal(&vertices, &faces[*f])));
+                let plane = Vector4::new(n.x, n.y, n.z, -n.dot(&vertices[a]));
+                let quadric = plane * plane.transpose() * (BOUNDARY_QUADRIC_WEIGHT * edge.norm_squared());
+                quadrics[a] += quadric;
+                quadrics[b] += quadric;
+            }
+        }
+
+        let mut versions = vec![0usize; vertices.len()];
+        let mut heap = BinaryHeap::new();
+        for &(a, b) in edge_faces.keys() {
+            heap.push(Collapse::new(&vertices, &quadrics, &versions, a, b));
+        }
+
+        while alive_count > target_triangles {
+            let Some(collapse) = heap.pop() else { break };
+            let (u, v) = (collapse.u, collapse.v);
+            if removed[u] || removed[v] || versions[u] != collapse.versions.0 || versions[v] != collapse.versions.1 {
+                continue;
+            }
+            if !collapse_allowed(&vertices, &faces, &alive_faces, &vertex_faces, u, v, &collapse.target) {
+                continue;
+            }
+
+            vertices[u] = collapse.target;
+            quadrics[u] = quadrics[u] + quadrics[v];
+            removed[v] = true;
+            for f in std::mem::take(&mut vertex_faces[v]) {
+                if !alive_faces[f] {
+                    continue;
+                }
+                if faces[f].contains(&u) {
+                    alive_faces[f] = false;
+                    alive_count -= 1;
+                } else {
+                    faces[f] = faces[f].map(|w| if w == v { u } else { w });
+                    vertex_faces[u].push(f);
+                }
+            }
+            vertex_faces[u].retain(|&f| alive_faces[f]);
+            versions[u] += 1;
+
+            let ring: HashSet<usize> = vertex_faces[u].iter().flat_map(|&f| faces[f]).filter(|&w| w != u).collect();
+            for w in ring {
+                heap.push(Collapse::new(&vertices, &quadrics, &versions, u, w));
+            }
+        }
+
+        // оставшиеся вершины нумеруются заново
+        let mut new_index = vec![usize::MAX; vertices.len()];
+        let mut compact = Vec::new();
+        let indices = faces.iter().zip(&alive_faces)
+            .filter(|(_, alive)| **alive)
+            .map(|(face, _)| face.map(|v| {
+                if new_index[v] == usize::MAX {
+                    new_index[v] = compact.len();
+                    compact.push(vertices[v]);
+                }
+                new_index[v]
+            }))
+            .collect();
+        let mut mesh = Mesh::new(compact, indices);
+        if self.normals.is_some() {
+            mesh.ensure_normals();
+        }
+        mesh
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        self.indices.iter().map(|face| face_normal(&self.vertices, face).magnitude() / 2.0).sum()
+    }
+
+    // Объём, ограниченный замкнутым мешем (сумма тетраэдров с вершиной в начале координат).
+    // Положителен, если треугольники обходятся против часовой стрелки снаружи
+    pub fn volume(&self) -> f64 {
+        self.indices.iter().map(|&[a, b, c]| {
+            self.vertices[a].dot(&self.vertices[b].cross(&self.vertices[c])) / 6.0
+        }).sum()
+    }
+
+    // Центр масс однородного тела внутри замкнутого меша
+    pub fn centroid(&self) -> Result<Point3> {
+        let mut volume = 0.0;
+        let mut moment = Point3::zeros();
+        for &[a, b, c] in &self.indices {
+            let (a, b, c) = (self.vertices[a], self.vertices[b], self.vertices[c]);
+            let tetrahedron = a.dot(&b.cross(&c)) / 6.0;
+            volume += tetrahedron;
+            moment += (a + b + c) * (tetrahedron / 4.0);
+        }
+        if volume == 0.0 {
+            return self.get_center();
+        }
+        Ok(moment / volume)
+    }
+
+    // Тензор инерции однородного тела плотности density относительно центра масс,
+    // в осях модели. Масса тела - density * volume()
+    pub fn inertia_tensor(&self, density: f64) -> Result<Matrix3<f64>> {
+        // второй момент тетраэдра (0, a, b, c): det / 120 * (сумма v v^T по вершинам + s s^T), s = a + b + c
+        let mut second_moment = Matrix3::zeros();
+        for &[a, b, c] in &self.indices {
+            let (a, b, c) = (self.vertices[a], self.vertices[b], self.vertices[c]);
+            let det = a.dot(&b.cross(&c));
+            let s = a + b + c;
+            second_moment += (a * a.transpose() + b * b.transpose() + c * c.transpose() + s * s.transpose()) * (det / 120.0);
+        }
+        let mass = density * self.volume();
+        let centroid = self.centroid()?;
+        let covariance = second_moment * density - centroid * centroid.transpose() * mass;
+        Ok(Matrix3::identity() * covariance.trace() - covariance)
+    }
+}
+
+// Стягивание ребра (u, v) в точку target с ошибкой cost. Версии вершин отбрасывают
+// кандидатов, посчитанных до изменения квадрик
+struct Collapse {
+    cost: f64,
+    u: usize,
+    v: usize,
+    versions: (usize, usize),
+    target: Point3,
+}
+
+impl Collapse {
+    fn new(vertices: &[Point3], quadrics: &[Matrix4<f64>], versions: &[usize], u: usize, v: usize) -> Self {
+        let q = quadrics[u] + quadrics[v];
+        let error = |p: &Point3| {
+            let h = Vector4::new(p.x, p.y, p.z, 1.0);
+            (h.transpose() * q * h)[(0, 0)]
+        };
+        // точка минимума квадрики, если система невырождена, иначе лучший из концов и середины
+        let a = q.fixed_view::<3, 3>(0, 0).into_owned();
+        let b = q.fixed_view::<3, 1>(0, 3).into_owned();
+        let scale = a.norm().max(f64::MIN_POSITIVE);
+        let optimal = (a.determinant().abs() > 1e-12 * scale * scale * scale)
+            .then(|| a.try_inverse().map(|inv| -(inv * b)))
+            .flatten();
+        let candidates = [vertices[u], vertices[v], (vertices[u] + vertices[v]) / 2.0];
+        let target = optimal.into_iter().chain(candidates)
+            .min_by(|p1, p2| error(p1).total_cmp(&error(p2)))
+            .unwrap();
+        Self { cost: error(&target), u, v, versions: (versions[u], versions[v]), target }
+    }
+}
+
+// BinaryHeap - куча максимумов, первым нужен кандидат с наименьшей ошибкой
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+// Стягивание допустимо, если общие соседи u и v - только вершины общих граней (сетка остаётся
+// многообразием) и ни одна из остающихся граней не переворачивается
+fn collapse_allowed(
+    vertices: &[Point3],
+    faces: &[[usize; 3]],
+    alive_faces: &[bool],
+    vertex_faces: &[Vec<usize>],
+    u: usize,
+    v: usize,
+    target: &Point3,
+) -> bool {
+    let ring = |x: usize| -> HashSet<usize> {
+        vertex_faces[x].iter().filter(|&&f| alive_faces[f]).flat_map(|&f| faces[f]).filter(|&w| w != x).collect()
+    };
+    let shared_faces = vertex_faces[u].iter()
+        .filter(|&&f| alive_faces[f] && faces[f].contains(&v))
+        .count();
+    if ring(u).intersection(&ring(v)).count() != shared_faces {
+        return false;
+    }
+
+    [u, v].iter().all(|&x| {
+        vertex_faces[x].iter()
+            .filter(|&&f| alive_faces[f] && !(faces[f].contains(&u) && faces[f].contains(&v)))
+            .all(|&f| {
+                let before = face_normal(vertices, &faces[f]);
+                let moved: Vec<Point3> = faces[f].iter()
+                    .map(|&w| if w == u || w == v { *target } else { vertices[w] })
+                    .collect();
+                let after = (moved[1] - moved[0]).cross(&(moved[2] - moved[0]));
+                before.dot(&after) > 0.0
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    use crate::physics::ball::Ball;
+    use crate::physics::capsule::Capsule;
+    use crate::physics::cone::Cone;
+    use crate::physics::cube::Cube;
+    use crate::physics::cylinder::Cylinder;
+    use crate::physics::ellipsoid::Ellipsoid;
+    use crate::physics::geometry::Geometry;
+    use crate::physics::revolution::{ProfilePoint, revolve};
+    use crate::physics::torus::Torus;
+
+    // каждое ребро замкнутого многообразия принадлежит ровно двум граням, V - E + F = 2 для сферы
+    fn assert_closed_manifold(mesh: &Mesh) {
+        assert_closed_surface(mesh, 2);
+    }
+
+    // euler - эйлерова характеристика V - E + F: 2 для тел, гомеоморфных сфере, 0 для тора
+    fn assert_closed_surface(mesh: &Mesh, euler: i64) {
+        let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+        for &[a, b, c] in &mesh.indices {
+            assert!(a != b && b != c && c != a);
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                *edges.entry(edge_key(u, v)).or_default() += 1;
+            }
+        }
+        assert!(edges.values().all(|&n| n == 2));
+        let used: HashSet<usize> = mesh.indices.iter().flatten().copied().collect();
+        assert_eq!(used.len() as i64 - edges.len() as i64 + mesh.indices.len() as i64, euler);
+    }
+
+    fn radius_range(mesh: &Mesh) -> (f64, f64) {
+        mesh.vertices.iter().map(|v| v.magnitude()).fold((f64::MAX, 0.0), |(lo, hi), r| (lo.min(r), hi.max(r)))
+    }
+
+    // Подразбиение Лупа сжимает икосаэдр, поэтому предел сравнивается со сферой своего радиуса:
+    // уровни сходятся, а вершины расходятся по радиусу меньше чем на 2%
+    #[test]
+    fn subdivided_icosahedron_converges_to_a_sphere() {
+        let icosahedron = Ball::icosphere(1.0, 0).get_surface_mesh().unwrap();
+        let ranges: Vec<(f64, f64)> = (2..=4).map(|levels| radius_range(&icosahedron.loop_subdivided(levels))).collect();
+
+        assert!((ranges[2].1 - ranges[1].1).abs() < (ranges[1].1 - ranges[0].1).abs() / 2.0);
+        let (lo, hi) = ranges[2];
+        let mean = (lo + hi) / 2.0;
+        assert!((hi - lo) / 2.0 < 0.02 * mean);
+
+        let subdivided = icosahedron.loop_subdivided(4);
+        assert_eq!(subdivided.indices.len(), 20 * 4usize.pow(4));
+        assert_closed_manifold(&subdivided.welded(1e-9));
+    }
+
+    #[test]
+    fn unreferenced_vertices_stay_in_place() {
+        let mut mesh = Ball::icosphere(1.0, 0).get_surface_mesh().unwrap();
+        let lonely = Point3::new(5.0, 0.0, 0.0);
+        mesh.vertices.push(lonely);
+        mesh.uvs.as_mut().unwrap().push([0.0, 0.0]);
+        let subdivided = mesh.loop_subdivided(1);
+        assert_eq!(subdivided.vertices[mesh.vertices.len() - 1], lonely);
+    }
+
+    #[test]
+    fn decimation_reaches_the_target_and_stays_manifold() {
+        let sphere = Ball::icosphere(1.0, 3).get_surface_mesh().unwrap();
+        for target in [500, 100, 20] {
+            let decimated = sphere.decimated(target);
+            assert!(decimated.indices.len() <= target, "{} > {}", decimated.indices.len(), target);
+            assert_closed_manifold(&decimated);
+            assert!(decimated.volume() > 0.0);
+        }
+    }
+
+    #[test]
+    fn unit_cube_mass_properties() {
+        let cube = Cube::new(1.0).get_surface_mesh().unwrap();
+        assert!((cube.volume() - 1.0).abs() < 1e-12);
+        assert!(cube.centroid().unwrap().magnitude() < 1e-12);
+        let inertia = cube.inertia_tensor(1.0).unwrap();
+        assert!((inertia - Matrix3::identity() / 6.0).abs().max() < 1e-12, "{}", inertia);
+    }
+
+    // у UV-сферы копии вершин на шве и полюсах совпадают по положению
+    #[test]
+    fn welding_merges_uv_sphere_seams() {
+        let (segments, rings) = (16, 8);
+        let sphere = Ball::uv_sphere(1.0, segments, rings).get_surface_mesh().unwrap();
+        assert_eq!(sphere.vertices.len(), (segments + 1) * (rings + 1));
+
+        let welded = sphere.welded(1e-9);
+        assert_eq!(welded.vertices.len(), segments * (rings - 1) + 2);
+        assert_eq!(welded.indices.len(), sphere.indices.len());
+        assert_closed_manifold(&welded);
+        assert!((welded.volume() - sphere.volume()).abs() < 1e-12);
+    }
+
+    // Копии вершин на шве, полюсах и острых рёбрах сливаются, после чего тело замкнуто;
+    // объём многогранника меньше объёма гладкого тела не более чем на 2%
+    fn assert_solid(geometry: &dyn Geometry, euler: i64, expected_volume: f64) {
+        let mesh = geometry.get_surface_mesh().unwrap();
+        assert_closed_surface(&mesh.welded(1e-9), euler);
+        let volume = mesh.volume();
+        assert!(volume > 0.0);
+        assert!((volume - expected_volume).abs() < 0.02 * expected_volume, "{} vs {}", volume, expected_volume);
+    }
+
+    #[test]
+    fn generated_solids_are_closed_with_analytic_volumes() {
+        assert_solid(&Ellipsoid::new(3.0, 2.0, 1.0), 2, 4.0 / 3.0 * PI * 3.0 * 2.0 * 1.0);
+        assert_solid(&Torus::new(2.0, 0.5), 0, 2.0 * PI * PI * 2.0 * 0.5 * 0.5);
+        assert_solid(&Cylinder::new(1.0, 3.0), 2, PI * 3.0);
+        assert_solid(&Cone::new(1.5, 2.0), 2, PI * 1.5 * 1.5 * 2.0 / 3.0);
+        assert_solid(&Capsule::new(0.5, 2.0), 2, PI * 0.25 * 2.0 + 4.0 / 3.0 * PI * 0.125);
+    }
+
+    // Усечённый конус с крышками: у рёбер крышек по две точки профиля с разными нормалями
+    #[test]
+    fn revolved_frustum_is_closed_with_analytic_volume() {
+        let (top, bottom, height) = (0.5, 1.0, 2.0);
+        let side = (height, bottom - top);
+        let frustum = revolve(&[
+            ProfilePoint::new(0.0, height, (0.0, 1.0)),
+            ProfilePoint::new(top, height, (0.0, 1.0)),
+            ProfilePoint::new(top, height, side),
+            ProfilePoint::new(bottom, 0.0, side),
+            ProfilePoint::new(bottom, 0.0, (0.0, -1.0)),
+            ProfilePoint::new(0.0, 0.0, (0.0, -1.0)),
+        ], 64);
+        assert_closed_manifold(&frustum.welded(1e-9));
+        let expected = PI * height / 3.0 * (top * top + top * bottom + bottom * bottom);
+        assert!(frustum.volume() > 0.0);
+        assert!((frustum.volume() - expected).abs() < 0.02 * expected);
+    }
+}
@@ -1,4 +1,27 @@
 pub mod coords;
 pub mod geometry;
 pub mod ball;
-pub mod cube;
\ No newline at end of file
+pub mod cube;
+pub mod events;
+pub mod random;
+pub mod orbit;
+pub mod belt;
+pub mod gravity;
+pub mod comet;
+pub mod resonance;
+pub mod soi;
+pub mod nbody;
+pub mod galaxy;
+pub mod mesh_io;
+pub mod model;
+pub mod revolution;
+pub mod ellipsoid;
+pub mod annulus;
+pub mod torus;
+pub mod cylinder;
+pub mod cone;
+pub mod capsule;
+pub mod bounds;
+pub mod mesh_processing;
+pub mod noise;
+pub mod terrain;
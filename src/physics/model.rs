@@ -0,0 +1,56 @@
+use std::path::Path;
+
+use crate::error::Result;
+use crate::physics::bounds::{Aabb, BoundingSphere, Ray, RayHit};
+use crate::physics::geometry::{Geometry, Mesh, MBV, Point3};
+use crate::physics::mesh_io::load_mesh;
+
+// Модель формы из файла: астероид (например, 433 Эрос), аппарат
+pub struct Model {
+    mesh: Mesh,
+}
+
+impl Model {
+    pub fn new(mut mesh: Mesh) -> Self {
+        mesh.ensure_normals();
+        mesh.ensure_tangents();
+        Self { mesh }
+    }
+
+    // scale переводит единицы файла (обычно км или м) в единицы сцены
+    pub fn load<P: AsRef<Path>>(path: P, scale: f64) -> Result<Self> {
+        let mut mesh = load_mesh(path)?;
+        for v in mesh.vertices.iter_mut() {
+            *v *= scale;
+        }
+        Ok(Self::new(mesh))
+    }
+}
+
+impl Geometry for Model {
+    fn get_surface_mesh(&self) -> Result<Mesh> {
+        Ok(self.mesh.clone())
+    }
+
+    fn get_edges_mesh(&self, bold: f32) -> Result<Mesh> {
+        self.mesh.edges_along_normals(bold)
+    }
+
+    fn minimal_bounding_volume(&self) -> MBV {
+        let size = self.bounding_box().size();
+        MBV(size.x, size.y, size.z)
+    }
+
+    // Модель не обязана быть отцентрована
+    fn bounding_box(&self) -> Aabb {
+        self.mesh.aabb().unwrap_or(Aabb::new(Point3::zeros(), Point3::zeros()))
+    }
+
+    fn bounding_sphere(&self) -> BoundingSphere {
+        self.mesh.bounding_sphere().unwrap_or(BoundingSphere::new(Point3::zeros(), 0.0))
+    }
+
+    fn ray_cast(&self, ray: &Ray) -> Option<RayHit> {
+        self.mesh.ray_cast(ray)
+    }
+}
@@ -0,0 +1,106 @@
+use rayon::prelude::*;
+
+use crate::physics::geometry::{PARALLEL_MIN_LEN, Point3};
+
+// Тело, которое и притягивает, и притягивается (звезда, ядро галактики).
+// mu = G m, единицы задаёт сцена
+#[derive(Debug, Clone, Copy)]
+pub struct MassiveBody {
+    pub position: Point3,
+    pub velocity: Point3,
+    pub mu: f64,
+}
+
+// Прямое суммирование сил между всеми парами тел, O(N^2)
+pub struct NBodySystem {
+    pub bodies: Vec<MassiveBody>,
+    // сглаживание потенциала: убирает расходимость при тесных сближениях
+    pub softening: f64,
+    pub time: f64,
+    accelerations: Vec<Point3>,
+}
+
+// Тел на задачу rayon столько, чтобы в ней было не меньше PARALLEL_MIN_LEN парных взаимодействий
+fn min_bodies_per_task(n: usize) -> usize {
+    (PARALLEL_MIN_LEN / n.max(1)).max(1)
+}
+
+// Ускорение каждого тела суммируется в фиксированном порядке j = 0..N,
+// поэтому результат не зависит от числа потоков
+pub fn accelerations(bodies: &[MassiveBody], softening: f64) -> Vec<Point3> {
+    let eps2 = softening * softening;
+    bodies.par_iter().with_min_len(min_bodies_per_task(bodies.len())).enumerate().map(|(i, body)| {
+        bodies.iter().enumerate().fold(Point3::zeros(), |acc, (j, other)| {
+            if i == j {
+                return acc;
+            }
+            let d = other.position - body.position;
+            let r2 = d.magnitude_squared() + eps2;
+            acc + d * (other.mu / (r2 * r2.sqrt()))
+        })
+    }).collect()
+}
+
+impl NBodySystem {
+    pub fn new(bodies: Vec<MassiveBody>, softening: f64) -> Self {
+        let accelerations = accelerations(&bodies, softening);
+        Self { bodies, softening, time: 0.0, accelerations }
+    }
+
+    // Шаг leapfrog (kick-drift-kick); ускорения конца шага переиспользуются в начале следующего
+    pub fn step(&mut self, dt: f64) {
+        self.bodies.par_iter_mut().zip(self.accelerations.par_iter()).with_min_len(PARALLEL_MIN_LEN).for_each(|(body, a)| {
+            body.velocity += a * (0.5 * dt);
+            body.position += body.velocity * dt;
+        });
+        self.accelerations = accelerations(&self.bodies, self.softening);
+        self.bodies.par_iter_mut().zip(self.accelerations.par_iter()).with_min_len(PARALLEL_MIN_LEN).for_each(|(body, a)| {
+            body.velocity += a * (0.5 * dt);
+        });
+        self.time += dt;
+    }
+
+    pub fn set_softening(&mut self, softening: f64) {
+        self.softening = softening;
+        self.accelerations = accelerations(&self.bodies, softening);
+    }
+
+    // Энергии считаются в единицах G = 1, где mu совпадает с массой
+    pub fn kinetic_energy(&self) -> f64 {
+        self.bodies.iter().map(|b| 0.5 * b.mu * b.velocity.magnitude_squared()).sum()
+    }
+
+    // Потенциальная энергия с тем же сглаживанием, что и силы
+    pub fn potential_energy(&self) -> f64 {
+        let eps2 = self.softening * self.softening;
+        let n = self.bodies.len();
+        (0..n).into_par_iter().with_min_len(min_bodies_per_task(n)).map(|i| {
+            let a = &self.bodies[i];
+            self.bodies[(i + 1)..].iter().fold(0.0, |acc, b| {
+                acc - a.mu * b.mu / ((a.position - b.position).magnitude_squared() + eps2).sqrt()
+            })
+        }).collect::<Vec<f64>>().iter().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::random::Rng;
+
+    // Тел достаточно, чтобы rayon поделил их между потоками
+    #[test]
+    fn parallel_accelerations_match_serial() {
+        let mut rng = Rng::new(5);
+        let bodies: Vec<MassiveBody> = (0..1000).map(|_| MassiveBody {
+            position: Point3::new(rng.range(-1.0, 1.0), rng.range(-1.0, 1.0), rng.range(-1.0, 1.0)),
+            velocity: Point3::zeros(),
+            mu: rng.range(0.5, 1.5),
+        }).collect();
+
+        let serial = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        let expected = serial.install(|| accelerations(&bodies, 0.01));
+        let parallel = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        assert_eq!(parallel.install(|| accelerations(&bodies, 0.01)), expected);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::physics::geometry::Point3;
+use crate::physics::random::Rng;
+
+// Градиентный шум Перлина (вариант 2002 года) в трёх измерениях, значения примерно в [-1, 1].
+// Перестановка строится из seed, поэтому рельеф воспроизводим
+#[derive(Debug, Clone)]
+pub struct GradientNoise {
+    perm: [u8; 512],
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+// Скалярное произведение с одним из 12 направлений на середины рёбер куба
+fn grad(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = match h {
+        0..=3 => y,
+        12 | 14 => x,
+        _ => z,
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+impl GradientNoise {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let mut p: [u8; 256] = std::array::from_fn(|i| i as u8);
+        // перемешивание Фишера - Йейтса
+        for i in (1..256).rev() {
+            let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+            p.swap(i, j);
+        }
+        Self { perm: std::array::from_fn(|i| p[i & 255]) }
+    }
+
+    pub fn sample(&self, p: &Point3) -> f64 {
+        let cell = p.map(f64::floor);
+        let [x, y, z] = [p.x - cell.x, p.y - cell.y, p.z - cell.z];
+        let [xi, yi, zi] = [cell.x, cell.y, cell.z].map(|c| (c as i64 & 255) as usize);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let perm = &self.perm;
+        let a = perm[xi] as usize + yi;
+        let (aa, ab) = (perm[a] as usize + zi, perm[a + 1] as usize + zi);
+        let b = perm[xi + 1] as usize + yi;
+        let (ba, bb) = (perm[b] as usize + zi, perm[b + 1] as usize + zi);
+
+        lerp(w,
+            lerp(v,
+                lerp(u, grad(perm[aa], x, y, z), grad(perm[ba], x - 1.0, y, z)),
+                lerp(u, grad(perm[ab], x, y - 1.0, z), grad(perm[bb], x - 1.0, y - 1.0, z))),
+            lerp(v,
+                lerp(u, grad(perm[aa + 1], x, y, z - 1.0), grad(perm[ba + 1], x - 1.0, y, z - 1.0)),
+                lerp(u, grad(perm[ab + 1], x, y - 1.0, z - 1.0), grad(perm[bb + 1], x - 1.0, y - 1.0, z - 1.0))))
+    }
+}
+
+// Фрактальная сумма октав шума: частота каждой следующей октавы умножается на lacunarity,
+// амплитуда - на gain
+#[derive(Debug, Clone)]
+pub struct FractalNoise {
+    pub noise: GradientNoise,
+    pub frequency: f64,
+    pub octaves: u32,
+    pub lacunarity: f64,
+    pub gain: f64,
+}
+
+impl FractalNoise {
+    pub fn new(seed: u64) -> Self {
+        Self { noise: GradientNoise::new(seed), frequency: 1.5, octaves: 6, lacunarity: 2.0, gain: 0.5 }
+    }
+
+    // Значения октав с их амплитудами, сумма амплитуд нормирована на 1
+    fn octaves(&self, p: &Point3) -> impl Iterator<Item = (f64, f64)> + '_ {
+        let total: f64 = (0..self.octaves).map(|i| self.gain.powi(i as i32)).sum();
+        let p = *p;
+        (0..self.octaves).map(move |i| {
+            let frequency = self.frequency * self.lacunarity.powi(i as i32);
+            // сдвиг октав убирает совпадение нулей шума в целых точках
+            let offset = Point3::repeat(i as f64 * 17.31);
+            (self.noise.sample(&(p * frequency + offset)), self.gain.powi(i as i32) / total)
+        })
+    }
+
+    // fBm, примерно в [-1, 1]
+    pub fn fbm(&self, p: &Point3) -> f64 {
+        self.octaves(p).map(|(value, amplitude)| value * amplitude).sum()
+    }
+
+    // Гребни там, где шум проходит через ноль; в [-1, 1], гребни около 1
+    pub fn ridged(&self, p: &Point3) -> f64 {
+        let ridges: f64 = self.octaves(p).map(|(value, amplitude)| (1.0 - value.abs()).powi(2) * amplitude).sum();
+        2.0 * ridges - 1.0
+    }
+}
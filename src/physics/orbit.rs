@@ -0,0 +1,209 @@
+use std::f64::consts::PI;
+
+use crate::physics::geometry::Point3;
+
+// Гравитационный параметр Солнца в а.е.^3 / год^2
+pub const MU_SUN: f64 = 4.0 * PI * PI;
+// Масса Земли в массах Солнца
+pub const EARTH_MASS_SUN: f64 = 3.003e-6;
+
+const KEPLER_TOLERANCE: f64 = 1e-12;
+const KEPLER_MAX_ITERATIONS: usize = 50;
+// порог, ниже которого орбита считается круговой или лежащей в плоскости XY
+const ORBIT_EPSILON: f64 = 1e-10;
+
+// Кеплеровы элементы орбиты на эпоху t = 0; углы в радианах.
+// Для гиперболических орбит (e > 1) большая полуось отрицательна
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitalElements {
+    pub semi_major_axis: f64,
+    pub eccentricity: f64,
+    pub inclination: f64,
+    pub ascending_node: f64,
+    pub arg_periapsis: f64,
+    pub mean_anomaly: f64,
+}
+
+// Решение уравнения Кеплера M = E - e sin E методом Ньютона
+pub fn eccentric_anomaly(mean_anomaly: f64, e: f64) -> f64 {
+    let m = mean_anomaly.rem_euclid(2.0 * PI);
+    let mut ea = if e < 0.8 { m } else { PI };
+    for _ in 0..KEPLER_MAX_ITERATIONS {
+        let delta = (ea - e * ea.sin() - m) / (1.0 - e * ea.cos());
+        ea -= delta;
+        if delta.abs() < KEPLER_TOLERANCE {
+            break;
+        }
+    }
+    ea
+}
+
+// Гиперболическое уравнение Кеплера M = e sh H - H
+pub fn hyperbolic_anomaly(mean_anomaly: f64, e: f64) -> f64 {
+    let mut ha = (mean_anomaly / e).asinh();
+    for _ in 0..KEPLER_MAX_ITERATIONS {
+        let delta = (e * ha.sinh() - ha - mean_anomaly) / (e * ha.cosh() - 1.0);
+        ha -= delta;
+        if delta.abs() < KEPLER_TOLERANCE {
+            break;
+        }
+    }
+    ha
+}
+
+impl OrbitalElements {
+    // Элементы по перицентрическому расстоянию и моменту прохождения перицентра
+    pub fn from_perihelion(
+        perihelion: f64,
+        eccentricity: f64,
+        inclination: f64,
+        ascending_node: f64,
+        arg_periapsis: f64,
+        perihelion_time: f64,
+        mu: f64,
+    ) -> Self {
+        let mut elements = Self {
+            semi_major_axis: perihelion / (1.0 - eccentricity),
+            eccentricity,
+            inclination,
+            ascending_node,
+            arg_periapsis,
+            mean_anomaly: 0.0,
+        };
+        elements.mean_anomaly = -elements.mean_motion(mu) * perihelion_time;
+        elements
+    }
+
+    // Оскулирующие элементы по вектору состояния; полученная орбита проходит через
+    // (position, velocity) в момент t = 0. Для круговых орбит перицентр отсчитывается от узла,
+    // для орбит в плоскости XY узел отсчитывается от оси X
+    pub fn from_state(position: &Point3, velocity: &Point3, mu: f64) -> Self {
+        let r = position.magnitude();
+        let h = position.cross(velocity);
+        let node = Point3::new(-h.y, h.x, 0.0);
+        let e_vec = (position * (velocity.magnitude_squared() - mu / r) - velocity * position.dot(velocity)) / mu;
+        let e = e_vec.magnitude();
+        let energy = velocity.magnitude_squared() / 2.0 - mu / r;
+
+        let inclination = (h.z / h.magnitude()).clamp(-1.0, 1.0).acos();
+        let equatorial = node.magnitude() < ORBIT_EPSILON * h.magnitude();
+        let ascending_node = if equatorial { 0.0 } else { node.y.atan2(node.x) };
+        // единичный вектор на узел и перпендикуляр к нему в плоскости орбиты
+        let node_dir = if equatorial { Point3::new(1.0, 0.0, 0.0) } else { node.normalize() };
+        let in_plane = h.normalize().cross(&node_dir);
+
+        let angle_from_node = |v: &Point3| v.dot(&in_plane).atan2(v.dot(&node_dir));
+        let circular = e < ORBIT_EPSILON;
+        let arg_periapsis = if circular { 0.0 } else { angle_from_node(&e_vec) };
+        let true_anomaly = angle_from_node(position) - arg_periapsis;
+
+        let mean_anomaly = if e < 1.0 {
+            let ea = 2.0 * ((1.0 - e).sqrt() * (true_anomaly / 2.0).sin()).atan2((1.0 + e).sqrt() * (true_anomaly / 2.0).cos());
+            ea - e * ea.sin()
+        } else {
+            let ha = 2.0 * (((e - 1.0) / (e + 1.0)).sqrt() * (true_anomaly / 2.0).tan()).atanh();
+            e * ha.sinh() - ha
+        };
+
+        Self {
+            semi_major_axis: -mu / (2.0 * energy),
+            eccentricity: e,
+            inclination,
+            ascending_node,
+            arg_periapsis,
+            mean_anomaly,
+        }
+    }
+
+    pub fn mean_motion(&self, mu: f64) -> f64 {
+        (mu / self.semi_major_axis.abs().powi(3)).sqrt()
+    }
+
+    pub fn period(&self, mu: f64) -> f64 {
+        2.0 * PI / self.mean_motion(mu)
+    }
+
+    // Поворот из плоскости орбиты (перицентр по оси X) в систему отсчёта
+    fn rotate_to_reference(&self, v: Point3) -> Point3 {
+        let (so, co) = self.ascending_node.sin_cos();
+        let (sw, cw) = self.arg_periapsis.sin_cos();
+        let (si, ci) = self.inclination.sin_cos();
+        let p = Point3::new(co * cw - so * sw * ci, so * cw + co * sw * ci, sw * si);
+        let q = Point3::new(-co * sw - so * cw * ci, -so * sw + co * cw * ci, cw * si);
+        p * v.x + q * v.y
+    }
+
+    // Положение и скорость через время t при гравитационном параметре mu
+    pub fn state_at(&self, mu: f64, t: f64) -> (Point3, Point3) {
+        if self.eccentricity > 1.0 {
+            return self.hyperbolic_state_at(mu, t);
+        }
+        let a = self.semi_major_axis;
+        let e = self.eccentricity;
+        let m = self.mean_anomaly + self.mean_motion(mu) * t;
+        let ea = eccentric_anomaly(m, e);
+        let (sin_e, cos_e) = ea.sin_cos();
+        let b = a * (1.0 - e * e).sqrt();
+
+        let position = Point3::new(a * (cos_e - e), b * sin_e, 0.0);
+        let rate = self.mean_motion(mu) / (1.0 - e * cos_e);
+        let velocity = Point3::new(-a * sin_e * rate, b * cos_e * rate, 0.0);
+
+        (self.rotate_to_reference(position), self.rotate_to_reference(velocity))
+    }
+
+    fn hyperbolic_state_at(&self, mu: f64, t: f64) -> (Point3, Point3) {
+        let a = self.semi_major_axis;
+        let e = self.eccentricity;
+        let m = self.mean_anomaly + self.mean_motion(mu) * t;
+        let ha = hyperbolic_anomaly(m, e);
+        let (sinh_h, cosh_h) = (ha.sinh(), ha.cosh());
+        let b = -a * (e * e - 1.0).sqrt();
+
+        let position = Point3::new(a * (cosh_h - e), b * sinh_h, 0.0);
+        let rate = self.mean_motion(mu) / (e * cosh_h - 1.0);
+        let velocity = Point3::new(a * sinh_h * rate, b * cosh_h * rate, 0.0);
+
+        (self.rotate_to_reference(position), self.rotate_to_reference(velocity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_state_close(a: (Point3, Point3), b: (Point3, Point3)) {
+        assert!((a.0 - b.0).magnitude() < 1e-9 * b.0.magnitude(), "{:?} vs {:?}", a.0, b.0);
+        assert!((a.1 - b.1).magnitude() < 1e-9 * b.1.magnitude(), "{:?} vs {:?}", a.1, b.1);
+    }
+
+    // Элементы, восстановленные по состоянию в момент t, задают ту же траекторию со сдвигом на t
+    fn assert_round_trip(elements: &OrbitalElements) {
+        for t in [-0.7, -0.05, 0.0, 0.3, 2.0] {
+            let (position, velocity) = elements.state_at(MU_SUN, t);
+            let restored = OrbitalElements::from_state(&position, &velocity, MU_SUN);
+            assert!((restored.semi_major_axis - elements.semi_major_axis).abs() < 1e-9 * elements.semi_major_axis.abs());
+            assert!((restored.eccentricity - elements.eccentricity).abs() < 1e-9);
+            assert!((restored.inclination - elements.inclination).abs() < 1e-9);
+            assert_state_close(restored.state_at(MU_SUN, 0.0), (position, velocity));
+            assert_state_close(restored.state_at(MU_SUN, 0.4), elements.state_at(MU_SUN, t + 0.4));
+        }
+    }
+
+    #[test]
+    fn hyperbolic_orbit_survives_a_state_round_trip() {
+        let elements = OrbitalElements::from_perihelion(1.2, 1.6, 0.4, 1.0, 2.0, 0.1, MU_SUN);
+        assert!(elements.semi_major_axis < 0.0);
+        assert_round_trip(&elements);
+
+        // в перицентре расстояние равно q, скорость перпендикулярна радиусу
+        let (position, velocity) = elements.state_at(MU_SUN, 0.1);
+        assert!((position.magnitude() - 1.2).abs() < 1e-9);
+        assert!(position.dot(&velocity).abs() < 1e-9 * velocity.magnitude());
+    }
+
+    #[test]
+    fn elliptic_orbit_survives_a_state_round_trip() {
+        assert_round_trip(&OrbitalElements::from_perihelion(0.6, 0.8, 2.8, 4.0, 0.5, -0.2, MU_SUN));
+    }
+}
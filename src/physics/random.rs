@@ -0,0 +1,75 @@
+use std::f64::consts::PI;
+
+// Детерминированный генератор SplitMix64: одинаковый seed даёт одинаковую
+// последовательность на любой платформе и версии зависимостей
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // равномерно на [0, 1)
+    pub fn uniform(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn range(&mut self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.uniform()
+    }
+
+    // нормальное распределение (преобразование Бокса-Мюллера)
+    pub fn normal(&mut self, mean: f64, sigma: f64) -> f64 {
+        let u1 = 1.0 - self.uniform();
+        let u2 = self.uniform();
+        mean + sigma * (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+
+    // распределение Рэлея, типичное для эксцентриситетов и наклонов малых тел
+    pub fn rayleigh(&mut self, sigma: f64) -> f64 {
+        sigma * (-2.0 * (1.0 - self.uniform()).ln()).sqrt()
+    }
+
+    // степенной закон N(>x) ~ x^-q на [min, max]; при q = 0 распределение лог-равномерное
+    pub fn power_law(&mut self, min: f64, max: f64, q: f64) -> f64 {
+        let u = self.uniform();
+        if q == 0.0 {
+            return min * (max / min).powf(u);
+        }
+        let a = min.powf(-q);
+        let b = max.powf(-q);
+        (a - u * (a - b)).powf(-1.0 / q)
+    }
+}
+
+// seed генератора сцены из seed прогона и seed записи конфига.
+// При seed прогона 0 остаётся seed из конфига
+pub fn derive_seed(run_seed: u64, seed: u64) -> u64 {
+    seed ^ run_seed.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn power_law_with_zero_index_is_log_uniform() {
+        let mut rng = Rng::new(7);
+        let samples: Vec<f64> = (0..10_000).map(|_| rng.power_law(1.0, 100.0, 0.0)).collect();
+        assert!(samples.iter().all(|x| x.is_finite() && (1.0..=100.0).contains(x)));
+        // у лог-равномерного распределения на [1, 100] половина значений меньше 10
+        let below = samples.iter().filter(|&&x| x < 10.0).count() as f64 / samples.len() as f64;
+        assert!((below - 0.5).abs() < 0.02);
+    }
+}
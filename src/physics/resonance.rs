@@ -0,0 +1,262 @@
+use std::f64::consts::PI;
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::physics::orbit::OrbitalElements;
+
+// Минимальное число отсчётов, после которого можно судить о либрации
+const MIN_SAMPLES: usize = 16;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Libration {
+    // резонансный аргумент колеблется около center с полуамплитудой amplitude (рад)
+    Librating { center: f64, amplitude: f64 },
+    // аргумент проходит все значения, rate - средняя скорость (рад / ед. времени)
+    Circulating { rate: f64 },
+    Undetermined,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResonanceReport {
+    pub name: String,
+    // периоды тел относительно периода первого тела
+    pub period_ratios: Vec<(String, f64)>,
+    pub argument: f64,
+    pub libration: Libration,
+}
+
+impl fmt::Display for ResonanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.name)?;
+        for (name, ratio) in &self.period_ratios {
+            write!(f, " P({})/P0={:.4}", name, ratio)?;
+        }
+        match self.libration {
+            Libration::Librating { center, amplitude } => write!(
+                f,
+                "; librates around {:.1} deg with amplitude {:.1} deg",
+                center.to_degrees(), amplitude.to_degrees()
+            ),
+            Libration::Circulating { rate } => write!(f, "; circulates at {:.4} deg per time unit", rate.to_degrees()),
+            Libration::Undetermined => write!(f, "; not enough data to tell libration from circulation"),
+        }
+    }
+}
+
+// Резонансный аргумент phi = sum(k_i * lambda_i) + sum(m_i * varpi_i), где lambda - средняя долгота,
+// varpi - долгота перицентра. Например, Нептун-Плутон 3:2: phi = 3 lambda_P - 2 lambda_N - varpi_P
+pub struct ResonanceAnalyser {
+    pub name: String,
+    pub bodies: Vec<String>,
+    pub lambda_coeffs: Vec<i32>,
+    pub pericentre_coeffs: Vec<i32>,
+
+    samples: usize,
+    first: (f64, f64),
+    last: (f64, f64),
+    // границы развёрнутого (непрерывного) аргумента
+    min: f64,
+    max: f64,
+    // смены направления движения аргумента: либрация видна только после полного колебания
+    turning_points: usize,
+    last_delta: f64,
+    mean_motions: Vec<f64>,
+}
+
+fn wrap_angle(angle: f64) -> f64 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+pub fn mean_longitude(elements: &OrbitalElements) -> f64 {
+    elements.ascending_node + elements.arg_periapsis + elements.mean_anomaly
+}
+
+pub fn pericentre_longitude(elements: &OrbitalElements) -> f64 {
+    elements.ascending_node + elements.arg_periapsis
+}
+
+impl ResonanceAnalyser {
+    // по одному коэффициенту каждого вида на тело
+    pub fn new(name: String, bodies: Vec<String>, lambda_coeffs: Vec<i32>, pericentre_coeffs: Vec<i32>) -> Result<Self> {
+        if lambda_coeffs.len() != bodies.len() {
+            return Err(Error::InvalidParameter {
+                name: "number of mean longitude coefficients",
+                value: lambda_coeffs.len() as f64,
+                expected: "one per body",
+            });
+        }
+        if pericentre_coeffs.len() != bodies.len() {
+            return Err(Error::InvalidParameter {
+                name: "number of pericentre coefficients",
+                value: pericentre_coeffs.len() as f64,
+                expected: "one per body",
+            });
+        }
+        let n = bodies.len();
+        Ok(Self {
+            name,
+            bodies,
+            lambda_coeffs,
+            pericentre_coeffs,
+            samples: 0,
+            first: (0.0, 0.0),
+            last: (0.0, 0.0),
+            min: 0.0,
+            max: 0.0,
+            turning_points: 0,
+            last_delta: 0.0,
+            mean_motions: vec![0.0; n],
+        })
+    }
+
+    // Резонанс p:q между внутренним и внешним телом (P_outer / P_inner = p / q):
+    // phi = p lambda_outer - q lambda_inner - (p - q) varpi_outer
+    pub fn two_body(name: String, inner: String, outer: String, p: i32, q: i32) -> Result<Self> {
+        Self::new(name, vec![inner, outer], vec![-q, p], vec![0, -(p - q)])
+    }
+
+    pub fn argument(&self, elements: &[OrbitalElements]) -> f64 {
+        let phi = elements.iter().enumerate().fold(0.0, |acc, (i, el)| {
+            acc + self.lambda_coeffs[i] as f64 * mean_longitude(el)
+                + self.pericentre_coeffs[i] as f64 * pericentre_longitude(el)
+        });
+        wrap_angle(phi)
+    }
+
+    // elements - оскулирующие элементы тел в порядке self.bodies на момент t
+    pub fn add_sample(&mut self, t: f64, elements: &[OrbitalElements], mu: f64) {
+        let phi = self.argument(elements);
+        let unwrapped = if self.samples == 0 {
+            phi
+        } else {
+            self.last.1 + wrap_angle(phi - self.last.1)
+        };
+
+        if self.samples == 0 {
+            self.first = (t, unwrapped);
+            self.min = unwrapped;
+            self.max = unwrapped;
+        } else {
+            let delta = unwrapped - self.last.1;
+            if delta * self.last_delta < 0.0 {
+                self.turning_points += 1;
+            }
+            if delta != 0.0 {
+                self.last_delta = delta;
+            }
+        }
+        self.min = self.min.min(unwrapped);
+        self.max = self.max.max(unwrapped);
+        self.last = (t, unwrapped);
+
+        // скользящее среднее средних движений
+        let k = self.samples as f64;
+        for (n, el) in self.mean_motions.iter_mut().zip(elements) {
+            *n = (*n * k + el.mean_motion(mu)) / (k + 1.0);
+        }
+        self.samples += 1;
+    }
+
+    pub fn reset(&mut self) {
+        self.samples = 0;
+        self.turning_points = 0;
+        self.last_delta = 0.0;
+        self.mean_motions.iter_mut().for_each(|n| *n = 0.0);
+    }
+
+    pub fn libration(&self) -> Libration {
+        if self.samples < MIN_SAMPLES {
+            return Libration::Undetermined;
+        }
+        // при циркуляции развёрнутый аргумент уходит дальше полного оборота
+        if self.max - self.min >= 2.0 * PI {
+            let duration = self.last.0 - self.first.0;
+            return Libration::Circulating { rate: (self.last.1 - self.first.1) / duration };
+        }
+        // аргумент ещё ни разу не повернул назад: возможно, это медленная циркуляция
+        if self.turning_points < 2 {
+            return Libration::Undetermined;
+        }
+        Libration::Librating {
+            center: wrap_angle((self.max + self.min) / 2.0),
+            amplitude: (self.max - self.min) / 2.0,
+        }
+    }
+
+    pub fn report(&self) -> ResonanceReport {
+        let base = self.mean_motions.first().copied().unwrap_or(0.0);
+        ResonanceReport {
+            name: self.name.clone(),
+            period_ratios: self.bodies.iter()
+                .zip(&self.mean_motions)
+                .map(|(name, n)| (name.clone(), base / n))
+                .collect(),
+            argument: wrap_angle(self.last.1),
+            libration: self.libration(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MU: f64 = 4.0 * PI * PI;
+
+    // круговая орбита в плоскости XY со средней долготой lambda
+    fn circular(semi_major_axis: f64, lambda: f64) -> OrbitalElements {
+        OrbitalElements {
+            semi_major_axis,
+            eccentricity: 0.0,
+            inclination: 0.0,
+            ascending_node: 0.0,
+            arg_periapsis: 0.0,
+            mean_anomaly: lambda,
+        }
+    }
+
+    // Пара 1:1, в которой второе тело колеблется около точки L4:
+    // lambda_2 - lambda_1 = 60 + 10 sin(w t) градусов
+    fn sample_pair(analyser: &mut ResonanceAnalyser, offset: impl Fn(f64) -> f64) {
+        let n = 2.0 * PI / 12.0;
+        for i in 0..2000 {
+            let t = i as f64 * 0.1;
+            let lambda = n * t;
+            let elements = [circular(5.2, lambda), circular(5.2, lambda + offset(t))];
+            analyser.add_sample(t, &elements, MU);
+        }
+    }
+
+    #[test]
+    fn detects_libration_around_l4() {
+        let mut analyser = ResonanceAnalyser::new("1:1".into(), vec!["A".into(), "B".into()], vec![-1, 1], vec![0, 0]).unwrap();
+        sample_pair(&mut analyser, |t| (60.0 + 10.0 * (0.2 * t).sin()).to_radians());
+
+        let Libration::Librating { center, amplitude } = analyser.libration() else {
+            panic!("expected libration, got {:?}", analyser.libration());
+        };
+        assert!((center.to_degrees() - 60.0).abs() < 0.1, "center {}", center.to_degrees());
+        assert!((amplitude.to_degrees() - 10.0).abs() < 0.1, "amplitude {}", amplitude.to_degrees());
+        assert!((analyser.report().period_ratios[1].1 - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn detects_circulation() {
+        let mut analyser = ResonanceAnalyser::new("1:1".into(), vec!["A".into(), "B".into()], vec![-1, 1], vec![0, 0]).unwrap();
+        sample_pair(&mut analyser, |t| 0.05 * t);
+
+        let Libration::Circulating { rate } = analyser.libration() else {
+            panic!("expected circulation, got {:?}", analyser.libration());
+        };
+        assert!((rate - 0.05).abs() < 1e-9, "rate {}", rate);
+    }
+
+    #[test]
+    fn rejects_one_coefficient_too_few() {
+        let bodies = vec!["A".into(), "B".into()];
+        let result = ResonanceAnalyser::new("1:1".into(), bodies.clone(), vec![1], vec![0, 0]);
+        assert!(matches!(result, Err(Error::InvalidParameter { name: "number of mean longitude coefficients", .. })));
+        let result = ResonanceAnalyser::new("1:1".into(), bodies, vec![-1, 1], vec![0]);
+        assert!(matches!(result, Err(Error::InvalidParameter { name: "number of pericentre coefficients", .. })));
+    }
+}
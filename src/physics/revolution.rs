@@ -0,0 +1,88 @@
+use std::f64::consts::PI;
+
+use crate::physics::geometry::{Mesh, Point3};
+
+// Точка профиля в меридиональной плоскости: r - расстояние до оси Z, z - высота,
+// normal - нормаль (по r, по z). Профиль обходится так, чтобы поверхность смотрела наружу:
+// направление обхода совпадает с (normal.z, -normal.r), например сверху вниз по боковой стенке.
+// Острые рёбра задаются двумя точками с одинаковым положением и разными нормалями
+#[derive(Debug, Clone, Copy)]
+pub struct ProfilePoint {
+    pub r: f64,
+    pub z: f64,
+    pub normal: (f64, f64),
+}
+
+impl ProfilePoint {
+    pub fn new(r: f64, z: f64, normal: (f64, f64)) -> Self {
+        let norm = normal.0.hypot(normal.1);
+        Self { r, z, normal: (normal.0 / norm, normal.1 / norm) }
+    }
+}
+
+// Поверхность вращения профиля вокруг оси Z. Раскладка как у UV-сферы: u - долгота
+// (phi = 2 pi u - pi), v - длина дуги профиля от первой точки; tangent на восток,
+// bitangent = tangent x normal - по ходу профиля
+pub fn revolve(profile: &[ProfilePoint], segments: usize) -> Mesh {
+    let segments = segments.max(3);
+    let row = segments + 1;
+
+    let mut arc = vec![0.0; profile.len()];
+    for i in 1..profile.len() {
+        arc[i] = arc[i - 1] + (profile[i].r - profile[i - 1].r).hypot(profile[i].z - profile[i - 1].z);
+    }
+    let total = arc.last().copied().filter(|&l| l > 0.0).unwrap_or(1.0);
+    let on_axis = |i: usize| profile[i].r == 0.0;
+    let degenerate = |i: usize| arc[i + 1] == arc[i];
+
+    let mut vertices = Vec::with_capacity(row * profile.len());
+    let mut normals = Vec::with_capacity(vertices.capacity());
+    let mut uvs = Vec::with_capacity(vertices.capacity());
+    let mut tangents = Vec::with_capacity(vertices.capacity());
+    let mut bitangents = Vec::with_capacity(vertices.capacity());
+    for (i, p) in profile.iter().enumerate() {
+        for j in 0..=segments {
+            // точка на оси используется одним треугольником на сегмент, u берётся по его середине
+            let u = match on_axis(i) {
+                true if i + 1 < profile.len() && !degenerate(i) => (j as f64 - 0.5) / segments as f64,
+                true => (j as f64 + 0.5) / segments as f64,
+                false => j as f64 / segments as f64,
+            };
+            let (sin_phi, cos_phi) = (2.0 * PI * u - PI).sin_cos();
+            let normal = Point3::new(p.normal.0 * cos_phi, p.normal.0 * sin_phi, p.normal.1);
+            let tangent = Point3::new(-sin_phi, cos_phi, 0.0);
+            vertices.push(Point3::new(p.r * cos_phi, p.r * sin_phi, p.z));
+            normals.push(normal);
+            uvs.push([u, arc[i] / total]);
+            tangents.push(tangent);
+            bitangents.push(tangent.cross(&normal));
+        }
+    }
+
+    let mut indices = Vec::with_capacity(2 * segments * profile.len());
+    for i in 0..profile.len().saturating_sub(1) {
+        if degenerate(i) {
+            continue;
+        }
+        for j in 0..segments {
+            let a = i * row + j;
+            let b = a + row;
+            // у точек на оси одна из половин четырёхугольника вырождена
+            if !on_axis(i) {
+                indices.push([a, b, a + 1]);
+            }
+            if !on_axis(i + 1) {
+                indices.push([a + 1, b, b + 1]);
+            }
+        }
+    }
+
+    Mesh {
+        vertices,
+        indices,
+        normals: Some(normals),
+        uvs: Some(uvs),
+        tangents: Some(tangents),
+        bitangents: Some(bitangents),
+    }
+}
@@ -0,0 +1,247 @@
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::physics::geometry::Point3;
+use crate::physics::orbit::OrbitalElements;
+
+// Точность определения момента пересечения границы сферы влияния
+const CROSSING_TOLERANCE: f64 = 1e-9;
+
+// Радиус сферы Хилла: область, где притяжение тела удерживает спутники.
+// mass_ratio - отношение массы тела к массе центрального тела
+pub fn hill_radius(semi_major_axis: f64, eccentricity: f64, mass_ratio: f64) -> f64 {
+    semi_major_axis * (1.0 - eccentricity) * (mass_ratio / 3.0).cbrt()
+}
+
+// Радиус сферы влияния по Лапласу: граница, на которой в методе сопряжённых конических
+// сечений меняется центральное тело
+pub fn laplace_radius(semi_major_axis: f64, mass_ratio: f64) -> f64 {
+    semi_major_axis * mass_ratio.powf(0.4)
+}
+
+pub struct ConicBody {
+    pub name: String,
+    pub mu: f64,
+    // радиус сферы влияния; у корневого тела (Солнца) не используется
+    pub soi_radius: f64,
+    // радиус сферы Хилла, для справки и отрисовки; у корневого тела бесконечен
+    pub hill_radius: f64,
+    // тело, внутри сферы влияния которого находится данное
+    pub parent: Option<usize>,
+    // положение и скорость в системе корневого тела в момент t
+    pub state: Box<dyn Fn(f64) -> (Point3, Point3) + Send + Sync>,
+}
+
+// Аппарат движется по коническому сечению относительно текущего центрального тела;
+// elements заданы на момент epoch
+#[derive(Debug, Clone)]
+pub struct Spacecraft {
+    pub name: String,
+    pub central: usize,
+    pub elements: OrbitalElements,
+    pub epoch: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SoiTransition {
+    pub time: f64,
+    pub spacecraft: String,
+    pub from: String,
+    pub to: String,
+}
+
+impl fmt::Display for SoiTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "t={:.4}: {} leaves {} for {}", self.time, self.spacecraft, self.from, self.to)
+    }
+}
+
+// Метод сопряжённых конических сечений: в каждый момент на аппарат действует только одно тело,
+// при пересечении границы сферы влияния вектор состояния пересчитывается к новому телу
+pub struct PatchedConicPropagator {
+    pub bodies: Vec<ConicBody>,
+    // наибольший шаг поиска пересечений: более короткие пролёты через сферу влияния пропускаются
+    pub step: f64,
+}
+
+impl PatchedConicPropagator {
+    pub fn new(step: f64) -> Self {
+        Self { bodies: Vec::new(), step }
+    }
+
+    pub fn add_body(&mut self, body: ConicBody) -> usize {
+        self.bodies.push(body);
+        self.bodies.len() - 1
+    }
+
+    // Аппарат по относительному вектору состояния в момент epoch
+    pub fn spacecraft(&self, name: String, central: usize, position: &Point3, velocity: &Point3, epoch: f64) -> Spacecraft {
+        Spacecraft {
+            name,
+            central,
+            elements: OrbitalElements::from_state(position, velocity, self.bodies[central].mu),
+            epoch,
+        }
+    }
+
+    // Положение и скорость аппарата в системе корневого тела
+    pub fn state_of(&self, craft: &Spacecraft, t: f64) -> (Point3, Point3) {
+        let central = &self.bodies[craft.central];
+        let (position, velocity) = craft.elements.state_at(central.mu, t - craft.epoch);
+        let (body_position, body_velocity) = (central.state)(t);
+        (position + body_position, velocity + body_velocity)
+    }
+
+    fn distance_to(&self, craft: &Spacecraft, body: usize, t: f64) -> f64 {
+        ((self.state_of(craft, t).0) - (self.bodies[body].state)(t).0).magnitude()
+    }
+
+    // Тело, в сферу влияния которого аппарат переходит в момент t, если переход есть
+    fn next_central(&self, craft: &Spacecraft, t: f64) -> Option<usize> {
+        let central = &self.bodies[craft.central];
+        if let Some(parent) = central.parent
+            && self.distance_to(craft, craft.central, t) > central.soi_radius
+        {
+            return Some(parent);
+        }
+        (0..self.bodies.len()).find(|&i| {
+            self.bodies[i].parent == Some(craft.central) && self.distance_to(craft, i, t) < self.bodies[i].soi_radius
+        })
+    }
+
+    // Первый момент на (a, b], когда аппарат уже сменил центральное тело
+    fn crossing_time(&self, craft: &Spacecraft, mut a: f64, mut b: f64) -> f64 {
+        while b - a > CROSSING_TOLERANCE {
+            let mid = 0.5 * (a + b);
+            if self.next_central(craft, mid).is_some() {
+                b = mid;
+            } else {
+                a = mid;
+            }
+        }
+        b
+    }
+
+    fn patch(&self, craft: &mut Spacecraft, to: usize, t: f64) {
+        let (position, velocity) = self.state_of(craft, t);
+        let (body_position, body_velocity) = (self.bodies[to].state)(t);
+        craft.central = to;
+        craft.elements = OrbitalElements::from_state(&(position - body_position), &(velocity - body_velocity), self.bodies[to].mu);
+        craft.epoch = t;
+    }
+
+    // Переводит аппарат из t0 в t1 и возвращает все смены центрального тела по порядку.
+    // При ошибке аппарат остаётся в состоянии после последнего успешного перехода
+    pub fn propagate(&self, craft: &mut Spacecraft, t0: f64, t1: f64) -> Result<Vec<SoiTransition>> {
+        let mut transitions = Vec::new();
+        let mut a = t0;
+        while a < t1 {
+            let b = (a + self.step).min(t1);
+            if self.next_central(craft, b).is_none() {
+                a = b;
+                continue;
+            }
+            let t = self.crossing_time(craft, a, b);
+            let to = self.next_central(craft, t).ok_or_else(|| Error::SoiCrossing {
+                spacecraft: craft.name.clone(),
+                time: t,
+            })?;
+            transitions.push(SoiTransition {
+                time: t,
+                spacecraft: craft.name.clone(),
+                from: self.bodies[craft.central].name.clone(),
+                to: self.bodies[to].name.clone(),
+            });
+            self.patch(craft, to, t);
+            a = t;
+        }
+        Ok(transitions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::orbit::{EARTH_MASS_SUN, MU_SUN};
+
+    const MOON_MASS_EARTH: f64 = 0.0123;
+    const MOON_DISTANCE_AU: f64 = 0.00257;
+
+    #[test]
+    fn radii_match_known_values() {
+        // сфера влияния Земли около 0.0062 а.е. (925 тыс. км), сфера Хилла около 0.01 а.е.
+        assert!((laplace_radius(1.0, EARTH_MASS_SUN) - 0.0062).abs() < 0.0001);
+        assert!((hill_radius(1.0, 0.0, EARTH_MASS_SUN) - 0.0100).abs() < 0.0001);
+        // сфера влияния Луны относительно Земли около 66 тыс. км
+        assert!((laplace_radius(MOON_DISTANCE_AU, MOON_MASS_EARTH) * 1.496e8 - 66_000.0).abs() < 1_000.0);
+        // сфера Хилла считается в перицентре
+        assert!((hill_radius(1.0, 0.5, EARTH_MASS_SUN) - hill_radius(1.0, 0.0, EARTH_MASS_SUN) / 2.0).abs() < 1e-15);
+    }
+
+    // Солнце и Земля на круговой орбите радиусом 1 а.е.
+    fn sun_and_earth() -> PatchedConicPropagator {
+        let mut propagator = PatchedConicPropagator::new(1e-3);
+        let sun = propagator.add_body(ConicBody {
+            name: "Sun".to_string(),
+            mu: MU_SUN,
+            soi_radius: f64::INFINITY,
+            hill_radius: f64::INFINITY,
+            parent: None,
+            state: Box::new(|_| (Point3::zeros(), Point3::zeros())),
+        });
+        let orbit = OrbitalElements {
+            semi_major_axis: 1.0,
+            eccentricity: 0.0,
+            inclination: 0.0,
+            ascending_node: 0.0,
+            arg_periapsis: 0.0,
+            mean_anomaly: 0.0,
+        };
+        propagator.add_body(ConicBody {
+            name: "Earth".to_string(),
+            mu: MU_SUN * EARTH_MASS_SUN,
+            soi_radius: laplace_radius(1.0, EARTH_MASS_SUN),
+            hill_radius: hill_radius(1.0, 0.0, EARTH_MASS_SUN),
+            parent: Some(sun),
+            state: Box::new(move |t| orbit.state_at(MU_SUN, t)),
+        });
+        propagator
+    }
+
+    // Аппарат пролетает сквозь сферу влияния Земли: входит в неё и выходит обратно
+    #[test]
+    fn fly_through_switches_the_central_body_continuously() {
+        let propagator = sun_and_earth();
+        let (earth, earth_velocity) = (propagator.bodies[1].state)(0.0);
+        let start = propagator.spacecraft(
+            "probe".to_string(),
+            0,
+            &(earth + Point3::new(0.02, 0.002, 0.0)),
+            &(earth_velocity + Point3::new(-1.0, 0.0, 0.0)),
+            0.0,
+        );
+
+        let mut craft = start.clone();
+        let transitions = propagator.propagate(&mut craft, 0.0, 0.05).unwrap();
+        let path: Vec<(&str, &str)> = transitions.iter().map(|t| (t.from.as_str(), t.to.as_str())).collect();
+        assert_eq!(path, [("Sun", "Earth"), ("Earth", "Sun")]);
+        assert_eq!(craft.central, 0);
+
+        for transition in &transitions {
+            let t = transition.time;
+            let (mut before, mut after) = (start.clone(), start.clone());
+            propagator.propagate(&mut before, 0.0, t - 1e-6).unwrap();
+            propagator.propagate(&mut after, 0.0, t + 1e-6).unwrap();
+            assert_ne!(before.central, after.central);
+
+            // гелиоцентрическое состояние по старому и по новому коническому сечению совпадает
+            let (p0, v0) = propagator.state_of(&before, t);
+            let (p1, v1) = propagator.state_of(&after, t);
+            assert!((p0 - p1).magnitude() < 1e-9);
+            assert!((v0 - v1).magnitude() < 1e-9 * v0.magnitude());
+            // переход - на границе сферы влияния
+            let distance = (p1 - (propagator.bodies[1].state)(t).0).magnitude();
+            assert!((distance - propagator.bodies[1].soi_radius).abs() < 1e-6);
+        }
+    }
+}
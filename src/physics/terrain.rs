@@ -0,0 +1,257 @@
+use std::f64::consts::PI;
+use std::path::Path;
+
+use rayon::prelude::*;
+
+use crate::error::{Error, Result};
+use crate::physics::ball::{Ball, SphereKind, sphere_uv};
+use crate::physics::bounds::{Aabb, BoundingSphere, Ray, RayHit};
+use crate::physics::geometry::{Geometry, Mesh, MBV, PARALLEL_MIN_LEN, Point3};
+use crate::physics::noise::FractalNoise;
+use crate::physics::random::Rng;
+
+const DEFAULT_SEGMENTS: usize = 128;
+const DEFAULT_RINGS: usize = 64;
+// Профиль кратера в долях глубины: высота вала и его ширина снаружи в радиусах кратера
+const CRATER_RIM_HEIGHT: f64 = 0.2;
+const CRATER_RIM_WIDTH: f64 = 0.4;
+// дальше стольких ширин вала кратер не влияет на высоту
+const CRATER_RIM_CUTOFF: f64 = 3.0;
+
+// Карта высот в равнопромежуточной проекции, как у текстур планет:
+// u - долгота, v - от северного полюса (верхняя строка) к южному. Яркость 0..1
+#[derive(Debug, Clone)]
+pub struct Heightmap {
+    image: image::ImageBuffer<image::Luma<f32>, Vec<f32>>,
+}
+
+impl Heightmap {
+    pub fn new(image: &image::DynamicImage) -> Self {
+        Self { image: image.to_luma32f() }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let reader = image::ImageReader::open(path).map_err(|e| Error::io(path, e))?;
+        Ok(Self::new(&reader.decode().map_err(|e| Error::image(path, e))?))
+    }
+
+    // Билинейная интерполяция, по долготе изображение замыкается
+    pub fn sample(&self, uv: [f64; 2]) -> f64 {
+        let (width, height) = self.image.dimensions();
+        let x = uv[0] * width as f64 - 0.5;
+        let y = (uv[1] * height as f64 - 0.5).clamp(0.0, (height - 1) as f64);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let column = |x: f64| (x as i64).rem_euclid(width as i64) as u32;
+        let row = |y: f64| (y as u32).min(height - 1);
+        let pixel = |x: f64, y: f64| self.image.get_pixel(column(x), row(y))[0] as f64;
+        let top = pixel(x0, y0) * (1.0 - tx) + pixel(x0 + 1.0, y0) * tx;
+        let bottom = pixel(x0, y0 + 1.0) * (1.0 - tx) + pixel(x0 + 1.0, y0 + 1.0) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+}
+
+// Кратер на единичной сфере: центр и угловой радиус; глубина в долях амплитуды рельефа
+#[derive(Debug, Clone, Copy)]
+pub struct Crater {
+    pub center: Point3,
+    pub radius: f64,
+    pub depth: f64,
+}
+
+impl Crater {
+    // Чаша (x^2 - 1) с валом, спадающим наружу по гауссиане; x - расстояние в радиусах кратера
+    fn height(&self, direction: &Point3) -> f64 {
+        let x = direction.dot(&self.center).clamp(-1.0, 1.0).acos() / self.radius;
+        let profile = if x < 1.0 {
+            x * x - 1.0 + CRATER_RIM_HEIGHT
+        } else if x < 1.0 + CRATER_RIM_CUTOFF * CRATER_RIM_WIDTH {
+            CRATER_RIM_HEIGHT * (-((x - 1.0) / CRATER_RIM_WIDTH).powi(2)).exp()
+        } else {
+            0.0
+        };
+        profile * self.depth
+    }
+}
+
+// Поле кратеров со степенным распределением размеров, как у реальных поверхностей:
+// мелких кратеров много, крупных мало. Глубина пропорциональна радиусу
+#[derive(Debug, Clone)]
+pub struct CraterField {
+    pub craters: Vec<Crater>,
+}
+
+impl CraterField {
+    // Радиусы угловые, в радианах; q - показатель степени N(>r) ~ r^-q
+    pub fn generate(seed: u64, count: usize, min_radius: f64, max_radius: f64, q: f64) -> Self {
+        let mut rng = Rng::new(seed);
+        let craters = (0..count).map(|_| {
+            // равномерно по сфере
+            let z = rng.range(-1.0, 1.0);
+            let phi = rng.range(-PI, PI);
+            let r_xy = (1.0 - z * z).sqrt();
+            let radius = rng.power_law(min_radius, max_radius, q);
+            Crater {
+                center: Point3::new(r_xy * phi.cos(), r_xy * phi.sin(), z),
+                radius,
+                depth: radius / max_radius,
+            }
+        }).collect();
+        Self { craters }
+    }
+
+    pub fn height(&self, direction: &Point3) -> f64 {
+        self.craters.iter().map(|crater| crater.height(direction)).sum()
+    }
+}
+
+// Рельеф: высота как функция направления из центра, в единицах амплитуды.
+// Шумовые рельефы примерно в [-1, 1], карта высот - в [0, 1]
+#[derive(Debug, Clone)]
+pub enum Relief {
+    Heightmap(Heightmap),
+    Fbm(FractalNoise),
+    // горные хребты
+    Ridged(FractalNoise),
+    Craters(CraterField),
+    // взвешенная сумма рельефов, например, холмы и кратеры астероида
+    Layers(Vec<(f64, Relief)>),
+}
+
+impl Relief {
+    // direction - единичный вектор
+    pub fn height(&self, direction: &Point3) -> f64 {
+        match self {
+            Relief::Heightmap(heightmap) => heightmap.sample(sphere_uv(direction)),
+            Relief::Fbm(noise) => noise.fbm(direction),
+            Relief::Ridged(noise) => noise.ridged(direction),
+            Relief::Craters(field) => field.height(direction),
+            Relief::Layers(layers) => layers.iter().map(|(weight, relief)| weight * relief.height(direction)).sum(),
+        }
+    }
+}
+
+// Сфера радиуса radius, вершины которой сдвинуты по нормали на amplitude * relief.
+// Тела неправильной формы (Фобос, астероиды) и планеты с преувеличенным рельефом (Марс)
+pub struct DisplacedSphere {
+    mesh: Mesh,
+}
+
+impl DisplacedSphere {
+    pub fn new(radius: f64, amplitude: f64, relief: &Relief) -> Result<Self> {
+        Self::with_kind(radius, amplitude, relief, SphereKind::UvSphere { segments: DEFAULT_SEGMENTS, rings: DEFAULT_RINGS })
+    }
+
+    // kind задаёт разбиение исходной сферы; карте высот нужна UV-сфера, шуму подходит и икосфера
+    pub fn with_kind(radius: f64, amplitude: f64, relief: &Relief, kind: SphereKind) -> Result<Self> {
+        let mut mesh = Ball { radius: 1.0, kind }.get_surface_mesh()?;
+        mesh.vertices = mesh.vertices.par_iter().with_min_len(PARALLEL_MIN_LEN).map(|v| {
+            let direction = v.normalize();
+            direction * (radius + amplitude * relief.height(&direction))
+        }).collect();
+        // нормали пересчитываются по сдвинутым вершинам, копии на швах получают общую нормаль
+        mesh.normals = Some(mesh.seamless_normals());
+        mesh.tangents = None;
+        mesh.bitangents = None;
+        mesh.ensure_tangents();
+        Ok(Self { mesh })
+    }
+
+    pub fn load_heightmap<P: AsRef<Path>>(path: P, radius: f64, amplitude: f64) -> Result<Self> {
+        Self::new(radius, amplitude, &Relief::Heightmap(Heightmap::load(path)?))
+    }
+}
+
+impl Geometry for DisplacedSphere {
+    fn get_surface_mesh(&self) -> Result<Mesh> {
+        Ok(self.mesh.clone())
+    }
+
+    fn get_edges_mesh(&self, bold: f32) -> Result<Mesh> {
+        self.mesh.edges_along_normals(bold)
+    }
+
+    fn minimal_bounding_volume(&self) -> MBV {
+        let size = self.bounding_box().size();
+        MBV(size.x, size.y, size.z)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.mesh.aabb().unwrap_or(Aabb::new(Point3::zeros(), Point3::zeros()))
+    }
+
+    fn bounding_sphere(&self) -> BoundingSphere {
+        let radius = self.mesh.vertices.iter().fold(0.0, |acc: f64, v| acc.max(v.magnitude()));
+        BoundingSphere::new(Point3::zeros(), radius)
+    }
+
+    fn ray_cast(&self, ray: &Ray) -> Option<RayHit> {
+        self.mesh.ray_cast(ray)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KIND: SphereKind = SphereKind::Icosphere { subdivisions: 4 };
+
+    fn hills_and_craters(seed: u64) -> Relief {
+        Relief::Layers(vec![
+            (1.0, Relief::Fbm(FractalNoise::new(seed))),
+            (1.0, Relief::Craters(CraterField::generate(seed, 40, 0.05, 0.3, 2.0))),
+        ])
+    }
+
+    fn vertices(relief: &Relief) -> Vec<Point3> {
+        DisplacedSphere::with_kind(2.0, 0.1, relief, KIND).unwrap().get_surface_mesh().unwrap().vertices
+    }
+
+    #[test]
+    fn same_seed_gives_identical_vertices() {
+        assert_eq!(vertices(&hills_and_craters(7)), vertices(&hills_and_craters(7)));
+        assert_ne!(vertices(&hills_and_craters(7)), vertices(&hills_and_craters(8)));
+    }
+
+    // Вершина остаётся на луче из центра через вершину исходной сферы, на расстоянии r + h
+    #[test]
+    fn vertices_are_displaced_along_sphere_normals() {
+        let relief = hills_and_craters(3);
+        let (radius, amplitude) = (2.0, 0.1);
+        let sphere = Ball { radius: 1.0, kind: KIND }.get_surface_mesh().unwrap();
+        let displaced = DisplacedSphere::with_kind(radius, amplitude, &relief, KIND).unwrap().get_surface_mesh().unwrap();
+        assert_eq!(sphere.vertices.len(), displaced.vertices.len());
+        for (base, v) in sphere.vertices.iter().zip(&displaced.vertices) {
+            let direction = base.normalize();
+            assert!((v.normalize() - direction).magnitude() < 1e-12);
+            assert!((v.magnitude() - (radius + amplitude * relief.height(&direction))).abs() < 1e-12);
+        }
+    }
+
+    // На склоне вала пересчитанные нормали отличаются от нормалей несдвинутой сферы,
+    // вдали от кратера совпадают с ними
+    #[test]
+    fn crater_rim_changes_the_normals() {
+        let crater = Crater { center: Point3::new(0.0, 0.0, 1.0), radius: 0.3, depth: 1.0 };
+        let relief = Relief::Craters(CraterField { craters: vec![crater] });
+        let normals = |amplitude: f64| {
+            DisplacedSphere::with_kind(1.0, amplitude, &relief, KIND).unwrap().get_surface_mesh().unwrap().normals.unwrap()
+        };
+        let (flat, cratered) = (normals(0.0), normals(0.2));
+        let sphere = Ball { radius: 1.0, kind: KIND }.get_surface_mesh().unwrap();
+
+        let mut rim = 0;
+        for ((v, flat), cratered) in sphere.vertices.iter().zip(&flat).zip(&cratered) {
+            let x = v.normalize().z.clamp(-1.0, 1.0).acos() / crater.radius;
+            let angle = flat.normalize().dot(&cratered.normalize()).clamp(-1.0, 1.0).acos();
+            if (1.1..1.5).contains(&x) {
+                rim += 1;
+                assert!(angle > 2f64.to_radians(), "rim normal turned by {:.3} deg", angle.to_degrees());
+            } else if x > 2.5 {
+                assert!(angle < 1e-9);
+            }
+        }
+        assert!(rim > 0);
+    }
+}
@@ -0,0 +1,48 @@
+use std::f64::consts::PI;
+
+use crate::error::Result;
+use crate::physics::geometry::{Geometry, Mesh, MBV};
+use crate::physics::revolution::{ProfilePoint, revolve};
+
+const DEFAULT_SEGMENTS: usize = 64;
+const DEFAULT_RINGS: usize = 24;
+
+// Тор вокруг оси Z: major_radius - от оси до центра трубки, minor_radius - радиус трубки
+pub struct Torus {
+    pub major_radius: f64,
+    pub minor_radius: f64,
+    pub segments: usize,
+    pub rings: usize,
+}
+
+impl Torus {
+    pub fn new(major_radius: f64, minor_radius: f64) -> Self {
+        Self { major_radius, minor_radius, segments: DEFAULT_SEGMENTS, rings: DEFAULT_RINGS }
+    }
+
+    // Сечение трубки обходится по часовой стрелке от внутреннего экватора
+    fn build_surface_mesh(&self) -> Mesh {
+        let rings = self.rings.max(3);
+        let profile: Vec<ProfilePoint> = (0..=rings).map(|k| {
+            let psi = PI - 2.0 * PI * k as f64 / rings as f64;
+            let (sin, cos) = psi.sin_cos();
+            ProfilePoint::new(self.major_radius + self.minor_radius * cos, self.minor_radius * sin, (cos, sin))
+        }).collect();
+        revolve(&profile, self.segments)
+    }
+}
+
+impl Geometry for Torus {
+    fn get_surface_mesh(&self) -> Result<Mesh> {
+        Ok(self.build_surface_mesh())
+    }
+
+    fn get_edges_mesh(&self, bold: f32) -> Result<Mesh> {
+        self.build_surface_mesh().edges_along_normals(bold)
+    }
+
+    fn minimal_bounding_volume(&self) -> MBV {
+        let side = 2.0 * (self.major_radius + self.minor_radius);
+        MBV(side, side, 2.0 * self.minor_radius)
+    }
+}